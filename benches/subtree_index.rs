@@ -0,0 +1,57 @@
+//! Compares [`KdlDocument::get_node_by_name_indexed`] against plain linear
+//! lookups with [`KdlDocument::get`].
+//!
+//! Run with `cargo bench --bench subtree_index`.
+
+use std::{
+    hint::black_box,
+    time::{Duration, Instant},
+};
+
+use kdl::{KdlDocument, KdlNode};
+
+const LOOKUPS: usize = 1_000;
+
+fn registry(size: usize) -> KdlDocument {
+    KdlDocument::from_nodes(
+        (0..size)
+            .map(|i| KdlNode::new(format!("service-{i}")).with_property("port", i as i128))
+            .collect(),
+    )
+}
+
+fn time(mut f: impl FnMut()) -> Duration {
+    let start = Instant::now();
+    for _ in 0..LOOKUPS {
+        f();
+    }
+    start.elapsed() / LOOKUPS as u32
+}
+
+fn main() {
+    for size in [1_000, 10_000, 100_000] {
+        let mut doc = registry(size);
+        // Spread the lookups over the whole document, so the linear scan
+        // isn't just hitting the first few nodes.
+        let names: Vec<String> = (0..LOOKUPS)
+            .map(|i| format!("service-{}", i * size / LOOKUPS))
+            .collect();
+        let mut names_iter = names.iter().cycle();
+
+        let linear = time(|| {
+            black_box(doc.get(names_iter.next().unwrap()));
+        });
+
+        let start = Instant::now();
+        doc.build_subtree_index();
+        let build = start.elapsed();
+
+        let indexed = time(|| {
+            black_box(doc.get_node_by_name_indexed(names_iter.next().unwrap()));
+        });
+
+        println!(
+            "{size:>7} nodes: linear {linear:>12?}/lookup, indexed {indexed:>12?}/lookup (index built in {build:?})"
+        );
+    }
+}
@@ -0,0 +1,231 @@
+use crate::{lines::logical_lines, nesting::push_node, KdlDocument, KdlError, KdlNode};
+
+impl KdlDocument {
+    /// Parses an [Apache HTTP Server](https://httpd.apache.org/)
+    /// configuration file (such as `httpd.conf`) into a document.
+    ///
+    /// * `Directive value` lines become nodes named after the directive, with
+    ///   the rest of the line as a single string argument. If the whole value
+    ///   is one quoted string, the quotes are removed. Directives with no
+    ///   value become nodes with no arguments.
+    /// * `<Section arg>` ... `</Section>` blocks become `Section` nodes, with
+    ///   `arg` as their argument (following the same rules as directive
+    ///   values), and the block's contents as children.
+    /// * `Include` and `IncludeOptional` directives are kept as inert
+    ///   `include` nodes. For `IncludeOptional`, the node also gets an
+    ///   `optional=#true` property. The included files are not read.
+    /// * Lines ending in `\` are joined with the following line.
+    /// * Empty lines and `#` comments are skipped.
+    ///
+    /// Fails if sections are unbalanced or closed in the wrong order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_apache_config(r#"
+    /// Listen 80
+    /// <VirtualHost *:80>
+    ///     DocumentRoot "/var/www/html"
+    /// </VirtualHost>
+    /// "#).unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "Listen \"80\"\nVirtualHost *:80 {\n    DocumentRoot \"/var/www/html\"\n}\n"
+    /// );
+    /// ```
+    pub fn from_apache_config(s: &str) -> Result<KdlDocument, KdlError> {
+        // Stack of currently-open sections, with the span of their opening
+        // line, for error reporting.
+        let mut stack: Vec<(KdlNode, (usize, usize))> = Vec::new();
+        let mut doc = KdlDocument::new();
+        for (line, span) in logical_lines(s) {
+            let line = line.as_str();
+            let error = |message: &str| KdlError::single(s, span, message, None);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(close) = line.strip_prefix("</") {
+                let name = close
+                    .strip_suffix('>')
+                    .map(str::trim)
+                    .ok_or_else(|| error("Invalid Apache section closing tag"))?;
+                let (section, _) = stack
+                    .pop()
+                    .ok_or_else(|| error("Closing tag without a matching opening tag"))?;
+                if !section.name().value().eq_ignore_ascii_case(name) {
+                    return Err(error(&format!(
+                        "Expected </{}>, found </{name}>",
+                        section.name().value()
+                    )));
+                }
+                push_node(&mut doc, &mut stack, section);
+            } else if let Some(open) = line.strip_prefix('<') {
+                let header = open
+                    .strip_suffix('>')
+                    .map(str::trim)
+                    .filter(|h| !h.is_empty())
+                    .ok_or_else(|| error("Invalid Apache section opening tag"))?;
+                let mut section = directive(header);
+                section.ensure_children();
+                stack.push((section, span));
+            } else {
+                push_node(&mut doc, &mut stack, directive(line));
+            }
+        }
+        if let Some((section, span)) = stack.pop() {
+            return Err(KdlError::single(
+                s,
+                span,
+                format!("Unclosed <{}> section", section.name().value()),
+                Some("Add a matching closing tag for this section."),
+            ));
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Turns `Name rest of the line` into a node.
+fn directive(line: &str) -> KdlNode {
+    let (name, value) = match line.split_once(char::is_whitespace) {
+        Some((name, value)) => (name, Some(value.trim())),
+        None => (line, None),
+    };
+    let value = value.filter(|v| !v.is_empty()).map(unquote);
+    if name.eq_ignore_ascii_case("Include") || name.eq_ignore_ascii_case("IncludeOptional") {
+        let mut node = KdlNode::new("include");
+        if let Some(value) = value {
+            node.push(value);
+        }
+        if name.eq_ignore_ascii_case("IncludeOptional") {
+            node.insert("optional", true);
+        }
+        return node;
+    }
+    let mut node = KdlNode::new(name);
+    if let Some(value) = value {
+        node.push(value);
+    }
+    node
+}
+
+/// Removes the quotes around `value` if it's a single quoted string.
+fn unquote(value: &str) -> String {
+    for quote in ['"', '\''] {
+        if let Some(inner) = value
+            .strip_prefix(quote)
+            .and_then(|v| v.strip_suffix(quote))
+        {
+            if !inner.contains(quote) {
+                return inner.into();
+            }
+        }
+    }
+    value.into()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn virtual_host() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_apache_config,
+            r#"
+# Main site
+ServerRoot "/etc/httpd"
+Listen 80
+
+<VirtualHost *:80 [::]:80>
+    ServerName example.com
+    ServerAlias www.example.com \
+                example.org
+    DocumentRoot '/var/www/example'
+    ErrorLog "logs/error log" combined
+    <Directory "/var/www/example">
+        Options Indexes FollowSymLinks
+        Require all granted
+    </Directory>
+</virtualhost>
+"#,
+            r#"
+ServerRoot "/etc/httpd"
+Listen "80"
+VirtualHost "*:80 [::]:80" {
+    ServerName "example.com"
+    ServerAlias "www.example.com example.org"
+    DocumentRoot "/var/www/example"
+    ErrorLog "\"logs/error log\" combined"
+    Directory "/var/www/example" {
+        Options "Indexes FollowSymLinks"
+        Require "all granted"
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn location() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_apache_config,
+            r#"
+<Location /server-status>
+    SetHandler server-status
+</Location>
+<LocationMatch "^/(images|css)/">
+</LocationMatch>
+"#,
+            r#"
+Location "/server-status" {
+    SetHandler "server-status"
+}
+LocationMatch "^/(images|css)/" {}
+"#,
+        )
+    }
+
+    #[test]
+    fn if_module_and_includes() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_apache_config,
+            r#"
+Include conf.modules.d/*.conf
+<IfModule mod_ssl.c>
+    IncludeOptional conf.d/ssl/*.conf
+    SSLEngine on
+    <IfModule !mod_http2.c>
+        Protocols http/1.1
+    </IfModule>
+</IfModule>
+ServerTokens
+"#,
+            r#"
+include "conf.modules.d/*.conf"
+IfModule "mod_ssl.c" {
+    include "conf.d/ssl/*.conf" optional=#true
+    SSLEngine on
+    IfModule "!mod_http2.c" {
+        Protocols "http/1.1"
+    }
+}
+ServerTokens
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        let err = KdlDocument::from_apache_config("<VirtualHost *:80>\n  Listen 80\n").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (0, 18).into());
+
+        let err = KdlDocument::from_apache_config("<IfModule a>\n</Location>\n").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (13, 11).into());
+
+        assert!(KdlDocument::from_apache_config("</IfModule>").is_err());
+        assert!(KdlDocument::from_apache_config("<IfModule a").is_err());
+    }
+}
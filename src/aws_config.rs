@@ -0,0 +1,303 @@
+use std::fmt::Write as _;
+
+use crate::{
+    error::span_or_default, lines::lines, KdlDocument, KdlEntry, KdlError, KdlNode, KdlValue,
+};
+
+impl KdlDocument {
+    /// Parses an AWS CLI/SDK configuration file (such as `~/.aws/config` or
+    /// `~/.aws/credentials`) into a document.
+    ///
+    /// Each `[section]` becomes a toplevel node named after the section. For
+    /// sections with a prefix, like `[profile dev]` or `[sso-session corp]`,
+    /// the prefix is the node name and the rest is its only argument. Settings
+    /// become string properties on their section's node, and nested settings
+    /// (an empty `key =` followed by indented `key = value` lines, as used for
+    /// `s3` or service-specific configuration) become a child node with the
+    /// nested settings as properties.
+    ///
+    /// Empty lines and lines starting with `#` or `;` are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_aws_config(
+    ///     "[default]\nregion = us-east-1\n\n[profile dev]\nregion = us-west-2\ns3 =\n  addressing_style = path\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "default region=us-east-1\nprofile dev region=us-west-2 {\n    s3 addressing_style=path\n}\n"
+    /// );
+    /// ```
+    pub fn from_aws_config(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        // A `key =` line with no value, which may or may not turn out to be the
+        // start of a nested block.
+        let mut pending: Option<String> = None;
+        for (start, line) in lines(s) {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') || trimmed.starts_with(';') {
+                continue;
+            }
+            let span = (
+                start + (line.len() - line.trim_start().len()),
+                trimmed.len(),
+            );
+            let error = |message: &str| KdlError::single(s, span, message, None);
+            let indented = line.starts_with([' ', '\t']);
+
+            if trimmed.starts_with('[') {
+                flush_pending(&mut doc, &mut pending);
+                let header = trimmed
+                    .strip_prefix('[')
+                    .and_then(|h| h.strip_suffix(']'))
+                    .map(str::trim)
+                    .filter(|h| !h.is_empty())
+                    .ok_or_else(|| error("Invalid AWS config section header"))?;
+                let node = match header.split_once(char::is_whitespace) {
+                    Some((kind, name)) => KdlNode::new(kind).with_appended_arg(name.trim()),
+                    None => KdlNode::new(header),
+                };
+                doc.nodes_mut().push(node);
+                continue;
+            }
+
+            let (key, value) = trimmed
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .filter(|(k, _)| !k.is_empty())
+                .ok_or_else(|| error("Expected a `key = value` setting"))?;
+            let section = doc
+                .nodes_mut()
+                .last_mut()
+                .ok_or_else(|| error("AWS config settings must be inside a [section]"))?;
+            if indented {
+                let parent = if let Some(name) = pending.take() {
+                    section
+                        .ensure_children()
+                        .nodes_mut()
+                        .push(KdlNode::new(name));
+                    section.ensure_children().nodes_mut().last_mut()
+                } else if let Some(children) = section.children_mut() {
+                    children.nodes_mut().last_mut()
+                } else {
+                    None
+                };
+                let parent =
+                    parent.ok_or_else(|| error("Nested settings must follow an empty `key =`"))?;
+                parent.insert(key, value);
+            } else {
+                flush_pending(&mut doc, &mut pending);
+                if value.is_empty() {
+                    pending = Some(key.into());
+                } else {
+                    doc.nodes_mut().last_mut().unwrap().insert(key, value);
+                }
+            }
+        }
+        flush_pending(&mut doc, &mut pending);
+        doc.autoformat();
+        Ok(doc)
+    }
+
+    /// Writes this document out as an AWS CLI/SDK configuration file. This is
+    /// the reverse of [`KdlDocument::from_aws_config`]:
+    ///
+    /// * Toplevel nodes become `[section]` headers. A node with a single
+    ///   argument, like `profile dev`, becomes `[profile dev]`.
+    /// * Properties become `key = value` settings.
+    /// * Child nodes become nested settings blocks, with their properties as
+    ///   the nested settings.
+    ///
+    /// Fails if the document can't be represented in this format: toplevel
+    /// nodes with more than one argument, child nodes with arguments or
+    /// children of their own, `#null` or non-finite float values, and values
+    /// containing newlines.
+    pub fn to_aws_config(&self) -> Result<String, KdlError> {
+        let mut out = String::new();
+        for (idx, node) in self.nodes().iter().enumerate() {
+            if idx > 0 {
+                out.push('\n');
+            }
+            let mut args = node.iter().filter(|e| e.name().is_none());
+            match (args.next(), args.next()) {
+                (None, _) => writeln!(out, "[{}]", node.name().value()).unwrap(),
+                (Some(arg), None) => {
+                    let name = value_to_string(self, arg)?;
+                    writeln!(out, "[{} {name}]", node.name().value()).unwrap();
+                }
+                (Some(_), Some(extra)) => {
+                    return Err(KdlError::in_document(
+                        self,
+                        span_or_default(extra),
+                        "AWS config sections can only have a single name",
+                    ));
+                }
+            }
+            write_settings(&mut out, self, node, "")?;
+            for child in node.iter_children() {
+                if let Some(arg) = child.iter().find(|e| e.name().is_none()) {
+                    return Err(KdlError::in_document(
+                        self,
+                        span_or_default(arg),
+                        "Nested AWS config settings can't have arguments",
+                    ));
+                }
+                if child.children().is_some_and(|c| !c.nodes().is_empty()) {
+                    return Err(KdlError::in_document(
+                        self,
+                        span_or_default(child),
+                        "AWS config settings can only be nested one level deep",
+                    ));
+                }
+                writeln!(out, "{} =", child.name().value()).unwrap();
+                write_settings(&mut out, self, child, "  ")?;
+            }
+        }
+        Ok(out)
+    }
+}
+
+fn flush_pending(doc: &mut KdlDocument, pending: &mut Option<String>) {
+    if let Some(key) = pending.take() {
+        if let Some(section) = doc.nodes_mut().last_mut() {
+            section.insert(key, "");
+        }
+    }
+}
+
+fn write_settings(
+    out: &mut String,
+    doc: &KdlDocument,
+    node: &KdlNode,
+    indent: &str,
+) -> Result<(), KdlError> {
+    for entry in node.iter() {
+        if let Some(name) = entry.name() {
+            let value = value_to_string(doc, entry)?;
+            if value.is_empty() {
+                writeln!(out, "{indent}{} =", name.value()).unwrap();
+            } else {
+                writeln!(out, "{indent}{} = {value}", name.value()).unwrap();
+            }
+        }
+    }
+    Ok(())
+}
+
+fn value_to_string(doc: &KdlDocument, entry: &KdlEntry) -> Result<String, KdlError> {
+    let value = match entry.value() {
+        KdlValue::String(s) if s.contains(['\n', '\r']) => {
+            return Err(KdlError::in_document(
+                doc,
+                span_or_default(entry),
+                "AWS config values can't contain newlines",
+            ))
+        }
+        KdlValue::String(s) => s.clone(),
+        KdlValue::Integer(i) => i.to_string(),
+        KdlValue::Float(f) if f.is_finite() => format!("{f:?}"),
+        KdlValue::Bool(b) => b.to_string(),
+        KdlValue::Float(_) | KdlValue::Null => {
+            return Err(KdlError::in_document(
+                doc,
+                span_or_default(entry),
+                "AWS config values must be strings, numbers, or booleans",
+            ))
+        }
+    };
+    Ok(value)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const CONFIG: &str = "\
+[default]
+region = us-east-1
+output = json
+
+[profile dev]
+role_arn = arn:aws:iam::123456789012:role/dev
+source_profile = default
+credential_process = /opt/bin/creds --profile dev
+s3 =
+  max_concurrent_requests = 20
+  addressing_style = path
+cli_pager =
+
+[sso-session corp]
+sso_start_url = https://corp.awsapps.com/start
+";
+
+    #[test]
+    fn from_aws_config() -> miette::Result<()> {
+        let config = format!("# leading comment\n{CONFIG}; trailing comment\n");
+        let mut doc = KdlDocument::from_aws_config(&config)?;
+        let mut expected: KdlDocument = r#"
+default region=us-east-1 output=json
+profile dev role_arn="arn:aws:iam::123456789012:role/dev" source_profile=default credential_process="/opt/bin/creds --profile dev" cli_pager="" {
+    s3 max_concurrent_requests="20" addressing_style=path
+}
+sso-session corp sso_start_url="https://corp.awsapps.com/start"
+"#
+        .parse()?;
+        doc.clear_format_recursive();
+        expected.clear_format_recursive();
+        assert_eq!(doc, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrip() -> miette::Result<()> {
+        let doc = KdlDocument::from_aws_config(CONFIG)?;
+        // The empty `cli_pager` setting moves up among the other settings,
+        // since properties are written before nested blocks.
+        let expected = CONFIG
+            .replace("cli_pager =\n", "")
+            .replace("s3 =\n", "cli_pager =\ns3 =\n");
+        assert_eq!(doc.to_aws_config()?, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn to_aws_config() -> miette::Result<()> {
+        let doc: KdlDocument = "default retries=3 debug=#true ratio=0.5".parse()?;
+        assert_eq!(
+            doc.to_aws_config()?,
+            "[default]\nretries = 3\ndebug = true\nratio = 0.5\n"
+        );
+
+        for bad in [
+            "profile a b",
+            "default key=#null",
+            "default { s3 1 }",
+            "default { s3 { deeper } }",
+            "default key=\"multi\\nline\"",
+        ] {
+            let doc: KdlDocument = bad.parse()?;
+            assert!(doc.to_aws_config().is_err(), "{bad} should fail");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "region = us-east-1",
+            "[default\nregion = x",
+            "[default]\njunk",
+            "[default]\n  nested = 1",
+        ] {
+            assert!(
+                KdlDocument::from_aws_config(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        let err = KdlDocument::from_aws_config("[default]\n  nested = 1").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (12, 10).into());
+    }
+}
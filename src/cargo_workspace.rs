@@ -0,0 +1,300 @@
+use toml::{Table, Value};
+
+use crate::{KdlDocument, KdlError, KdlNode, KdlValue};
+
+impl KdlDocument {
+    /// Parses a Cargo workspace's root `Cargo.toml` into a document.
+    ///
+    /// The `[workspace]` table becomes a toplevel `workspace` node:
+    ///
+    /// * `members`, `default-members` and `exclude` become child nodes with
+    ///   one `member` child per path.
+    /// * Each of the `[workspace.dependencies]` becomes a `dep` node under a
+    ///   `dependencies` child, with the dependency's name as its argument. A
+    ///   plain version requirement becomes a `version` property, and detailed
+    ///   dependencies get a property per setting, with lists like `features`
+    ///   as children.
+    /// * Other settings, like `resolver` or `[workspace.package]`, are
+    ///   converted generically: values become properties, and tables and lists
+    ///   become children.
+    ///
+    /// Other toplevel tables, like `[package]` or `[profile.release]`, are
+    /// converted generically too.
+    ///
+    /// Fails if the TOML is invalid, or if it doesn't have a `[workspace]`
+    /// table.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_cargo_workspace(r#"
+    /// [workspace]
+    /// members = ["crates/*"]
+    ///
+    /// [workspace.dependencies]
+    /// serde = "1.0"
+    /// "#).unwrap();
+    /// assert_eq!(doc.to_string(), r#"workspace {
+    ///     members {
+    ///         member "crates/*"
+    ///     }
+    ///     dependencies {
+    ///         dep serde version="1.0"
+    ///     }
+    /// }
+    /// "#);
+    /// ```
+    pub fn from_cargo_workspace(toml: &str) -> Result<KdlDocument, KdlError> {
+        let table = toml.parse::<Table>().map_err(|e| {
+            let span = e.span().unwrap_or(0..0);
+            KdlError::single(toml, span, "Invalid TOML", Some(e.message()))
+        })?;
+        if !matches!(table.get("workspace"), Some(Value::Table(_))) {
+            return Err(KdlError::single(
+                toml,
+                (0, 0),
+                "Not a Cargo workspace manifest",
+                Some("Workspace manifests must have a [workspace] table."),
+            ));
+        }
+        let mut doc = KdlDocument::new();
+        for (key, value) in table {
+            let node = match (key.as_str(), value) {
+                ("workspace", Value::Table(workspace)) => workspace_node(workspace),
+                (key, Value::Table(table)) => table_node(key, table),
+                (key, value) => value_node(key, value),
+            };
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+fn workspace_node(workspace: Table) -> KdlNode {
+    let mut node = KdlNode::new("workspace");
+    for (key, value) in workspace {
+        match (key.as_str(), value) {
+            ("members" | "default-members" | "exclude", Value::Array(paths)) => {
+                let mut list = KdlNode::new(key);
+                let children = list.ensure_children();
+                for path in paths {
+                    children
+                        .nodes_mut()
+                        .push(KdlNode::new("member").with_appended_arg(scalar(path)));
+                }
+                node.ensure_children().nodes_mut().push(list);
+            }
+            ("dependencies", Value::Table(deps)) => {
+                let mut list = KdlNode::new("dependencies");
+                let children = list.ensure_children();
+                for (name, dep) in deps {
+                    let dep = match dep {
+                        Value::Table(table) => {
+                            let mut dep = table_node("dep", table);
+                            dep.entries_mut().insert(0, name.into());
+                            dep
+                        }
+                        version => KdlNode::new("dep")
+                            .with_appended_arg(name)
+                            .with_property("version", scalar(version)),
+                    };
+                    children.nodes_mut().push(dep);
+                }
+                node.ensure_children().nodes_mut().push(list);
+            }
+            (_, value) => add_value(&mut node, key, value),
+        }
+    }
+    node
+}
+
+/// Converts a table into a node, with its values as properties and its
+/// nested tables and lists as children.
+fn table_node(name: &str, table: Table) -> KdlNode {
+    let mut node = KdlNode::new(name);
+    for (key, value) in table {
+        add_value(&mut node, key, value);
+    }
+    node
+}
+
+fn add_value(node: &mut KdlNode, key: String, value: Value) {
+    match value {
+        Value::Table(table) => {
+            let child = table_node(&key, table);
+            node.ensure_children().nodes_mut().push(child);
+        }
+        Value::Array(items) => {
+            let child = value_node(&key, Value::Array(items));
+            node.ensure_children().nodes_mut().push(child);
+        }
+        value => {
+            node.insert(key, scalar(value));
+        }
+    }
+}
+
+/// Converts a value into a node called `name`. Lists of values become
+/// arguments, and lists of tables (like `[[bin]]`) become one child per
+/// table.
+fn value_node(name: &str, value: Value) -> KdlNode {
+    match value {
+        Value::Table(table) => table_node(name, table),
+        Value::Array(items) => {
+            let mut node = KdlNode::new(name);
+            for item in items {
+                match item {
+                    Value::Table(table) => node
+                        .ensure_children()
+                        .nodes_mut()
+                        .push(table_node("-", table)),
+                    Value::Array(nested) => node
+                        .ensure_children()
+                        .nodes_mut()
+                        .push(value_node("-", Value::Array(nested))),
+                    value => node.push(scalar(value)),
+                }
+            }
+            node
+        }
+        value => KdlNode::new(name).with_appended_arg(scalar(value)),
+    }
+}
+
+fn scalar(value: Value) -> KdlValue {
+    match value {
+        Value::String(s) => KdlValue::String(s),
+        Value::Integer(i) => KdlValue::Integer(i.into()),
+        Value::Float(f) => KdlValue::Float(f),
+        Value::Boolean(b) => KdlValue::Bool(b),
+        Value::Datetime(dt) => KdlValue::String(dt.to_string()),
+        value @ (Value::Array(_) | Value::Table(_)) => KdlValue::String(value.to_string()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn minimal_workspace() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_cargo_workspace,
+            r#"
+[workspace]
+members = ["app", "lib"]
+resolver = "2"
+"#,
+            r#"
+workspace resolver="2" {
+    members {
+        member app
+        member lib
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn feature_rich_workspace() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_cargo_workspace,
+            r#"
+[workspace]
+members = ["crates/*", "tools/xtask"]
+default-members = ["crates/core"]
+exclude = ["crates/legacy"]
+resolver = "2"
+
+[workspace.package]
+version = "0.3.1"
+edition = "2021"
+authors = ["Ada <ada@example.com>", "Grace <grace@example.com>"]
+rust-version = "1.81"
+
+[workspace.dependencies]
+anyhow = "1.0"
+serde = { version = "1.0.200", features = ["derive", "rc"], default-features = false }
+core = { path = "crates/core", version = "=0.3.1" }
+tokio = { version = "1", optional = true, features = ["full"] }
+
+[workspace.lints.rust]
+unsafe_code = "forbid"
+
+[workspace.lints.clippy]
+all = { level = "warn", priority = -1 }
+
+[workspace.metadata.release]
+shared-version = true
+
+[profile.release]
+lto = "thin"
+codegen-units = 1
+opt-level = 3
+
+[patch.crates-io]
+serde = { git = "https://github.com/serde-rs/serde" }
+"#,
+            r#"
+workspace resolver="2" {
+    members {
+        member "crates/*"
+        member "tools/xtask"
+    }
+    default-members {
+        member "crates/core"
+    }
+    exclude {
+        member "crates/legacy"
+    }
+    package version="0.3.1" edition="2021" rust-version="1.81" {
+        authors "Ada <ada@example.com>" "Grace <grace@example.com>"
+    }
+    dependencies {
+        dep anyhow version="1.0"
+        dep serde version="1.0.200" default-features=#false {
+            features derive rc
+        }
+        dep core path="crates/core" version="=0.3.1"
+        dep tokio version="1" optional=#true {
+            features full
+        }
+    }
+    lints {
+        rust unsafe_code=forbid
+        clippy {
+            all level=warn priority=-1
+        }
+    }
+    metadata {
+        release shared-version=#true
+    }
+}
+profile {
+    release lto=thin codegen-units=1 opt-level=3
+}
+patch {
+    crates-io {
+        serde git="https://github.com/serde-rs/serde"
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        let err = KdlDocument::from_cargo_workspace("[package]\nname = \"x\"\n").unwrap_err();
+        assert_eq!(
+            err.diagnostics[0].message.as_deref(),
+            Some("Not a Cargo workspace manifest")
+        );
+        let err = KdlDocument::from_cargo_workspace("[workspace]\nmembers = [\n").unwrap_err();
+        assert_eq!(err.diagnostics[0].message.as_deref(), Some("Invalid TOML"));
+        assert!(KdlDocument::from_cargo_workspace("workspace = 1").is_err());
+    }
+}
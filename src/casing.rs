@@ -0,0 +1,252 @@
+use crate::{error::span_or_default, KdlDocument, KdlLintWarning};
+
+/// A naming convention for identifiers, used by
+/// [`KdlDocument::lint_property_casing`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum CasingConvention {
+    /// Lowercase words separated by dashes, like `max-connections`. This is
+    /// the usual convention for KDL documents.
+    KebabCase,
+    /// Lowercase words separated by underscores, like `max_connections`.
+    SnakeCase,
+    /// Capitalized words with no separators, except for the first one, which
+    /// is lowercase, like `maxConnections`.
+    CamelCase,
+    /// Capitalized words with no separators, like `MaxConnections`.
+    PascalCase,
+}
+
+impl CasingConvention {
+    /// Converts `name` to this convention.
+    ///
+    /// `name` is first split into words at dashes, underscores, and any other
+    /// characters that aren't letters or digits (which are dropped), and
+    /// wherever the case changes, so `HTTPServer` and `http_server` are both
+    /// the words `HTTP` and `Server`. Digits stay with the word before them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::CasingConvention;
+    /// assert_eq!(CasingConvention::KebabCase.convert("maxHTTPConnections"), "max-http-connections");
+    /// assert_eq!(CasingConvention::SnakeCase.convert("tls-v1.3"), "tls_v1_3");
+    /// assert_eq!(CasingConvention::CamelCase.convert("use_ipv6_only"), "useIpv6Only");
+    /// assert_eq!(CasingConvention::PascalCase.convert("content-type"), "ContentType");
+    /// ```
+    pub fn convert(&self, name: &str) -> String {
+        let words = split_words(name);
+        match self {
+            CasingConvention::KebabCase => join_lowercase(&words, "-"),
+            CasingConvention::SnakeCase => join_lowercase(&words, "_"),
+            CasingConvention::CamelCase => {
+                let mut out = String::new();
+                for (idx, word) in words.iter().enumerate() {
+                    if idx == 0 {
+                        out.push_str(&word.to_lowercase());
+                    } else {
+                        push_capitalized(&mut out, word);
+                    }
+                }
+                out
+            }
+            CasingConvention::PascalCase => {
+                let mut out = String::new();
+                for word in &words {
+                    push_capitalized(&mut out, word);
+                }
+                out
+            }
+        }
+    }
+}
+
+impl KdlDocument {
+    /// Checks that every property name in this document, at any depth,
+    /// follows `convention`, returning a [`KdlLintWarning`] for each one
+    /// that doesn't, in document order.
+    ///
+    /// A name follows the convention if [`CasingConvention::convert`] leaves
+    /// it unchanged, and the warning's suggestion is the converted name.
+    /// Names without any letters or digits are skipped, since there's
+    /// nothing to suggest.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::{CasingConvention, KdlDocument};
+    /// let doc: KdlDocument = "server max-connections=10 readTimeout=30".parse().unwrap();
+    /// let warnings = doc.lint_property_casing(CasingConvention::KebabCase);
+    /// assert_eq!(warnings.len(), 1);
+    /// assert_eq!(warnings[0].name, "readTimeout");
+    /// assert_eq!(warnings[0].suggestion, "read-timeout");
+    /// ```
+    pub fn lint_property_casing(&self, convention: CasingConvention) -> Vec<KdlLintWarning> {
+        let mut warnings = Vec::new();
+        for node in self.descendants() {
+            for name in node.entries().iter().filter_map(|e| e.name()) {
+                let suggestion = convention.convert(name.value());
+                if !suggestion.is_empty() && suggestion != name.value() {
+                    warnings.push(KdlLintWarning {
+                        name: name.value().into(),
+                        suggestion,
+                        span: span_or_default(name),
+                    });
+                }
+            }
+        }
+        warnings
+    }
+}
+
+fn split_words(name: &str) -> Vec<&str> {
+    let chars: Vec<(usize, char)> = name.char_indices().collect();
+    let mut words = Vec::new();
+    let mut start = None;
+    for (idx, &(offset, c)) in chars.iter().enumerate() {
+        if !c.is_alphanumeric() {
+            if let Some(start) = start.take() {
+                words.push(&name[start..offset]);
+            }
+            continue;
+        }
+        if let Some(word_start) = start {
+            let prev = chars[idx - 1].1;
+            let next_is_lower = chars.get(idx + 1).is_some_and(|(_, n)| n.is_lowercase());
+            let boundary = c.is_uppercase()
+                && (prev.is_lowercase()
+                    || prev.is_numeric()
+                    || (prev.is_uppercase() && next_is_lower));
+            if boundary {
+                words.push(&name[word_start..offset]);
+                start = Some(offset);
+            }
+        } else {
+            start = Some(offset);
+        }
+    }
+    if let Some(start) = start {
+        words.push(&name[start..]);
+    }
+    words
+}
+
+fn join_lowercase(words: &[&str], separator: &str) -> String {
+    words
+        .iter()
+        .map(|word| word.to_lowercase())
+        .collect::<Vec<_>>()
+        .join(separator)
+}
+
+fn push_capitalized(out: &mut String, word: &str) {
+    let mut chars = word.chars();
+    if let Some(first) = chars.next() {
+        out.extend(first.to_uppercase());
+        out.push_str(&chars.as_str().to_lowercase());
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const INPUT: &str = r#"
+server max-connections=10 read_timeout=30 {
+    tls certFile="a.pem" KeyFile="b.pem" verify=#true
+    listen port=80 "HTTP2Enabled"=#true
+}
+"#;
+
+    fn lint(convention: CasingConvention) -> Vec<(String, String)> {
+        let doc: KdlDocument = INPUT.parse().unwrap();
+        doc.lint_property_casing(convention)
+            .into_iter()
+            .map(|w| (w.name, w.suggestion))
+            .collect()
+    }
+
+    fn pairs(pairs: &[(&str, &str)]) -> Vec<(String, String)> {
+        pairs
+            .iter()
+            .map(|(a, b)| (a.to_string(), b.to_string()))
+            .collect()
+    }
+
+    #[test]
+    fn kebab_case() {
+        assert_eq!(
+            lint(CasingConvention::KebabCase),
+            pairs(&[
+                ("read_timeout", "read-timeout"),
+                ("certFile", "cert-file"),
+                ("KeyFile", "key-file"),
+                ("HTTP2Enabled", "http2-enabled"),
+            ])
+        );
+    }
+
+    #[test]
+    fn snake_case() {
+        assert_eq!(
+            lint(CasingConvention::SnakeCase),
+            pairs(&[
+                ("max-connections", "max_connections"),
+                ("certFile", "cert_file"),
+                ("KeyFile", "key_file"),
+                ("HTTP2Enabled", "http2_enabled"),
+            ])
+        );
+    }
+
+    #[test]
+    fn camel_case() {
+        assert_eq!(
+            lint(CasingConvention::CamelCase),
+            pairs(&[
+                ("max-connections", "maxConnections"),
+                ("read_timeout", "readTimeout"),
+                ("KeyFile", "keyFile"),
+                ("HTTP2Enabled", "http2Enabled"),
+            ])
+        );
+    }
+
+    #[test]
+    fn pascal_case() {
+        assert_eq!(
+            lint(CasingConvention::PascalCase),
+            pairs(&[
+                ("max-connections", "MaxConnections"),
+                ("read_timeout", "ReadTimeout"),
+                ("certFile", "CertFile"),
+                ("verify", "Verify"),
+                ("port", "Port"),
+                ("HTTP2Enabled", "Http2Enabled"),
+            ])
+        );
+    }
+
+    #[test]
+    fn convert() {
+        let kebab = CasingConvention::KebabCase;
+        assert_eq!(kebab.convert("XMLHttpRequest"), "xml-http-request");
+        assert_eq!(kebab.convert("__private--name__"), "private-name");
+        assert_eq!(kebab.convert("ÜberCool"), "über-cool");
+        assert_eq!(kebab.convert("v2"), "v2");
+        assert_eq!(kebab.convert("-"), "");
+        assert_eq!(CasingConvention::CamelCase.convert("ID"), "id");
+        assert_eq!(CasingConvention::PascalCase.convert("getHTTP"), "GetHttp");
+    }
+
+    #[cfg(feature = "span")]
+    #[test]
+    fn spans() {
+        let doc: KdlDocument = INPUT.parse().unwrap();
+        let warnings = doc.lint_property_casing(CasingConvention::KebabCase);
+        let span = warnings[0].span;
+        assert_eq!(
+            &INPUT[span.offset()..span.offset() + span.len()],
+            "read_timeout"
+        );
+    }
+}
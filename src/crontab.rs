@@ -0,0 +1,211 @@
+use crate::{lines::trimmed_lines, KdlDocument, KdlError, KdlNode};
+
+/// The schedule fields of a crontab job, in order.
+const FIELDS: [&str; 5] = ["minute", "hour", "dom", "month", "dow"];
+
+/// The `@` schedules that can replace the five schedule fields.
+const SPECIAL_SCHEDULES: [&str; 8] = [
+    "@reboot",
+    "@yearly",
+    "@annually",
+    "@monthly",
+    "@weekly",
+    "@daily",
+    "@midnight",
+    "@hourly",
+];
+
+impl KdlDocument {
+    /// Parses a [crontab](https://man7.org/linux/man-pages/man5/crontab.5.html)
+    /// file into a document.
+    ///
+    /// Each `minute hour dom month dow command` line becomes a `job` node
+    /// with `minute`, `hour`, `dom`, `month`, `dow` and `command` properties.
+    /// Schedule fields are kept as strings, exactly as written (like `*/5` or
+    /// `1-5`), and the command is the rest of the line:
+    ///
+    /// ```kdl
+    /// env SHELL="/bin/bash"
+    /// job minute="*/5" hour=* dom=* month=* dow="1-5" command="run-backup --quiet"
+    /// @reboot command="start-agent"
+    /// ```
+    ///
+    /// Jobs using a special schedule, like `@reboot` or `@hourly`, become a
+    /// node named after the schedule, with just a `command` property.
+    /// Environment variable assignments (`NAME=value`) become an `env` node
+    /// with a single `NAME` property, and quotes around the value are
+    /// removed. Blank lines and lines starting with `#` are skipped.
+    ///
+    /// This is only available with the `cron` feature.
+    ///
+    /// Fails if a job is missing any of its schedule fields or its command,
+    /// or if it uses an unknown `@` schedule.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_crontab("# Nightly.\n30 2 * * * /usr/bin/backup\n").unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "job minute=\"30\" hour=\"2\" dom=* month=* dow=* command=\"/usr/bin/backup\"\n"
+    /// );
+    /// ```
+    pub fn from_crontab(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        for (start, line) in trimmed_lines(s) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str, help: Option<&str>| {
+                KdlError::single(s, (start, line.len()), message, help)
+            };
+            if let Some((name, value)) = parse_env(line) {
+                let mut node = KdlNode::new("env");
+                node.insert(name, value);
+                doc.nodes_mut().push(node);
+                continue;
+            }
+            if line.starts_with('@') {
+                let (schedule, command) =
+                    line.split_once(char::is_whitespace).unwrap_or((line, ""));
+                if !SPECIAL_SCHEDULES.contains(&schedule) {
+                    return Err(error(
+                        &format!("Unknown schedule `{schedule}`"),
+                        Some("Special schedules are @reboot, @yearly, @annually, @monthly, @weekly, @daily, @midnight and @hourly."),
+                    ));
+                }
+                let command = command.trim_start();
+                if command.is_empty() {
+                    return Err(error("Expected a command after the schedule", None));
+                }
+                let mut node = KdlNode::new(schedule);
+                node.insert("command", command);
+                doc.nodes_mut().push(node);
+                continue;
+            }
+            let mut node = KdlNode::new("job");
+            let mut rest = line;
+            for name in FIELDS {
+                let (field, remaining) = rest.split_once(char::is_whitespace).ok_or_else(|| {
+                    error(
+                        "Expected five schedule fields followed by a command",
+                        Some("Jobs look like `minute hour dom month dow command`."),
+                    )
+                })?;
+                node.insert(name, field);
+                rest = remaining.trim_start();
+            }
+            if rest.is_empty() {
+                return Err(error(
+                    "Expected five schedule fields followed by a command",
+                    Some("Jobs look like `minute hour dom month dow command`."),
+                ));
+            }
+            node.insert("command", rest);
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Parses a `NAME=value` environment variable assignment, where `NAME` has
+/// no whitespace in it. Quotes around the value, if any, are removed.
+fn parse_env(line: &str) -> Option<(&str, &str)> {
+    let (name, value) = line.split_once('=')?;
+    let name = name.trim_end();
+    if name.is_empty() || name.contains(char::is_whitespace) {
+        return None;
+    }
+    let value = value.trim();
+    let unquoted = ['"', '\''].into_iter().find_map(|quote| {
+        value
+            .strip_prefix(quote)
+            .and_then(|value| value.strip_suffix(quote))
+    });
+    Some((name, unquoted.unwrap_or(value)))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn standard_jobs() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_crontab,
+            "*/5 * * * * /usr/local/bin/check-health
+0   2 * * 1-5   tar czf /backup/home.tgz /home  >/dev/null 2>&1
+15 14 1 jan,jul * echo \"Half-yearly report\" | mail -s Report admin@example.com\t
+",
+            r#"
+job minute="*/5" hour="*" dom="*" month="*" dow="*" command="/usr/local/bin/check-health"
+job minute="0" hour="2" dom="*" month="*" dow="1-5" command="tar czf /backup/home.tgz /home  >/dev/null 2>&1"
+job minute="15" hour="14" dom="1" month="jan,jul" dow="*" command="echo \"Half-yearly report\" | mail -s Report admin@example.com"
+"#,
+        )
+    }
+
+    #[test]
+    fn special_schedules() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_crontab,
+            "@reboot /usr/bin/start-agent --daemon\n@hourly   rotate-logs\n@midnight cleanup /tmp\n",
+            r#"
+"@reboot" command="/usr/bin/start-agent --daemon"
+"@hourly" command="rotate-logs"
+"@midnight" command="cleanup /tmp"
+"#,
+        )
+    }
+
+    #[test]
+    fn environment_and_comments() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_crontab,
+            r#"
+# Environment.
+SHELL=/bin/bash
+MAILTO = "ops@example.com"
+  PATH='/usr/local/bin:/usr/bin'
+EMPTY=
+
+   # Jobs.
+0 * * * * FOO=bar run-task
+"#,
+            r#"
+env SHELL="/bin/bash"
+env MAILTO="ops@example.com"
+env PATH="/usr/local/bin:/usr/bin"
+env EMPTY=""
+job minute="0" hour="*" dom="*" month="*" dow="*" command="FOO=bar run-task"
+"#,
+        )?;
+        assert!(KdlDocument::from_crontab("").unwrap().nodes().is_empty());
+        assert!(KdlDocument::from_crontab("\n  \n# only comments\n")
+            .unwrap()
+            .nodes()
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "* * * * *\n",
+            "* * * *   \n",
+            "0 2 * *\n",
+            "@reboot\n",
+            "@every-minute run\n",
+        ] {
+            assert!(
+                KdlDocument::from_crontab(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        let err = KdlDocument::from_crontab("SHELL=/bin/sh\n  0 2 *\n").unwrap_err();
+        assert_eq!(err.diagnostics[0].span.offset(), 16);
+    }
+}
@@ -0,0 +1,265 @@
+use std::fmt::Write;
+
+use crate::{KdlDocument, KdlEntry, KdlIdentifier, KdlNode};
+
+impl KdlDocument {
+    /// Prints [`KdlDocument::debug_string`] to stderr.
+    ///
+    /// This is only available with the `debug-print` feature.
+    pub fn debug_print(&self) {
+        eprintln!("{}", self.debug_string());
+    }
+
+    /// Returns a detailed, multi-line description of this document's AST,
+    /// including every node, entry and identifier, their reprs, spans, and
+    /// formatting. This is meant for debugging unexpected parse results, and
+    /// the exact output may change between releases.
+    ///
+    /// This is only available with the `debug-print` feature.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "node 0x10".parse().unwrap();
+    /// let debug = doc.debug_string();
+    /// assert!(debug.starts_with("KdlDocument\n"));
+    /// assert!(debug.contains("value: Integer(16)"));
+    /// assert!(debug.contains("format.value_repr: \"0x10\""));
+    /// ```
+    pub fn debug_string(&self) -> String {
+        let mut out = String::new();
+        write_document(&mut out, self, 0);
+        out
+    }
+
+    /// Prints [`KdlDocument::sprint_node_tree`] to stdout.
+    ///
+    /// This is only available with the `debug-print` feature.
+    pub fn print_node_tree(&self) {
+        print!("{}", self.sprint_node_tree());
+    }
+}
+
+impl KdlNode {
+    /// Prints [`KdlNode::debug_string`] to stderr.
+    ///
+    /// This is only available with the `debug-print` feature.
+    pub fn debug_print(&self) {
+        eprintln!("{}", self.debug_string());
+    }
+
+    /// Returns a detailed, multi-line description of this node's AST. See
+    /// [`KdlDocument::debug_string`].
+    ///
+    /// This is only available with the `debug-print` feature.
+    pub fn debug_string(&self) -> String {
+        let mut out = String::new();
+        write_node(&mut out, self, 0);
+        out
+    }
+}
+
+// Writing to a `String` can't fail, so the results of `writeln!` below are
+// ignored.
+
+/// Writes the span of `$item` as a `start..end` range. Spans are only written
+/// with the `span` feature.
+macro_rules! write_span {
+    ($out:expr, $pad:expr, $field:expr, $item:expr) => {
+        #[cfg(feature = "span")]
+        {
+            let span = $item.span();
+            let _ = writeln!(
+                $out,
+                "{}  {}: {}..{}",
+                $pad,
+                $field,
+                span.offset(),
+                span.offset() + span.len()
+            );
+        }
+    };
+}
+
+fn write_document(out: &mut String, doc: &KdlDocument, depth: usize) {
+    let pad = "  ".repeat(depth);
+    let _ = writeln!(out, "{pad}KdlDocument");
+    write_span!(out, pad, "span", doc);
+    match doc.format() {
+        Some(format) => {
+            let _ = writeln!(out, "{pad}  format.leading: {:?}", format.leading);
+            let _ = writeln!(out, "{pad}  format.trailing: {:?}", format.trailing);
+        }
+        None => {
+            let _ = writeln!(out, "{pad}  format: None");
+        }
+    }
+    let _ = writeln!(out, "{pad}  nodes: {}", doc.nodes().len());
+    for node in doc.nodes() {
+        write_node(out, node, depth + 2);
+    }
+}
+
+fn write_node(out: &mut String, node: &KdlNode, depth: usize) {
+    let pad = "  ".repeat(depth);
+    let _ = writeln!(out, "{pad}KdlNode");
+    write_ident(out, &pad, "name", Some(node.name()));
+    write_ident(out, &pad, "ty", node.ty());
+    write_span!(out, pad, "span", node);
+    match node.format() {
+        Some(format) => {
+            for (field, value) in [
+                ("leading", &format.leading),
+                ("before_ty_name", &format.before_ty_name),
+                ("after_ty_name", &format.after_ty_name),
+                ("after_ty", &format.after_ty),
+                ("before_children", &format.before_children),
+                ("before_terminator", &format.before_terminator),
+                ("terminator", &format.terminator),
+                ("trailing", &format.trailing),
+            ] {
+                let _ = writeln!(out, "{pad}  format.{field}: {value:?}");
+            }
+        }
+        None => {
+            let _ = writeln!(out, "{pad}  format: None");
+        }
+    }
+    let _ = writeln!(out, "{pad}  entries: {}", node.entries().len());
+    for entry in node.entries() {
+        write_entry(out, entry, depth + 2);
+    }
+    match node.children() {
+        Some(children) => {
+            let _ = writeln!(out, "{pad}  children:");
+            write_document(out, children, depth + 2);
+        }
+        None => {
+            let _ = writeln!(out, "{pad}  children: None");
+        }
+    }
+}
+
+fn write_entry(out: &mut String, entry: &KdlEntry, depth: usize) {
+    let pad = "  ".repeat(depth);
+    let _ = writeln!(out, "{pad}KdlEntry");
+    write_ident(out, &pad, "name", entry.name());
+    write_ident(out, &pad, "ty", entry.ty());
+    let _ = writeln!(out, "{pad}  value: {:?}", entry.value());
+    write_span!(out, pad, "span", entry);
+    match entry.format() {
+        Some(format) => {
+            for (field, value) in [
+                ("value_repr", &format.value_repr),
+                ("leading", &format.leading),
+                ("trailing", &format.trailing),
+                ("after_ty", &format.after_ty),
+                ("before_ty_name", &format.before_ty_name),
+                ("after_ty_name", &format.after_ty_name),
+                ("after_key", &format.after_key),
+                ("after_eq", &format.after_eq),
+            ] {
+                let _ = writeln!(out, "{pad}  format.{field}: {value:?}");
+            }
+            let _ = writeln!(
+                out,
+                "{pad}  format.autoformat_keep: {}",
+                format.autoformat_keep
+            );
+        }
+        None => {
+            let _ = writeln!(out, "{pad}  format: None");
+        }
+    }
+}
+
+fn write_ident(out: &mut String, pad: &str, field: &str, ident: Option<&KdlIdentifier>) {
+    let Some(ident) = ident else {
+        let _ = writeln!(out, "{pad}  {field}: None");
+        return;
+    };
+    let _ = writeln!(out, "{pad}  {field}: {:?}", ident.value());
+    let _ = writeln!(out, "{pad}  {field}.repr: {:?}", ident.repr());
+    write_span!(out, pad, format!("{field}.span"), ident);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[cfg(feature = "span")]
+    #[test]
+    fn debug_string() -> miette::Result<()> {
+        let doc: KdlDocument = "(t)node key=#true {\n    child\n}".parse()?;
+        let debug = doc.debug_string();
+        let expected = r##"KdlDocument
+  span: 0..31
+  format.leading: ""
+  format.trailing: ""
+  nodes: 1
+    KdlNode
+      name: "node"
+      name.repr: Some("node")
+      name.span: 3..7
+      ty: "t"
+      ty.repr: Some("t")
+      ty.span: 1..2
+      span: 0..31
+      format.leading: ""
+      format.before_ty_name: ""
+      format.after_ty_name: ""
+      format.after_ty: ""
+      format.before_children: " "
+      format.before_terminator: ""
+      format.terminator: ""
+      format.trailing: ""
+      entries: 1
+        KdlEntry
+          name: "key"
+          name.repr: Some("key")
+          name.span: 8..11
+          ty: None
+          value: Bool(true)
+          span: 8..17
+          format.value_repr: "#true"
+          format.leading: " "
+          format.trailing: ""
+          format.after_ty: ""
+          format.before_ty_name: ""
+          format.after_ty_name: ""
+          format.after_key: ""
+          format.after_eq: ""
+          format.autoformat_keep: false
+      children:
+        KdlDocument
+          span: 24..29
+          format.leading: "\n    "
+          format.trailing: ""
+          nodes: 1
+            KdlNode
+              name: "child"
+              name.repr: Some("child")
+              name.span: 24..29
+              ty: None
+              span: 24..29
+              format.leading: ""
+              format.before_ty_name: ""
+              format.after_ty_name: ""
+              format.after_ty: ""
+              format.before_children: ""
+              format.before_terminator: ""
+              format.terminator: "\n"
+              format.trailing: ""
+              entries: 0
+              children: None
+"##;
+        pretty_assertions::assert_eq!(debug, expected);
+
+        let child = &doc.nodes()[0].children().unwrap().nodes()[0];
+        let child_debug = child.debug_string();
+        assert!(child_debug.starts_with("KdlNode\n  name: \"child\"\n"));
+        assert!(child_debug.ends_with("  entries: 0\n  children: None\n"));
+        Ok(())
+    }
+}
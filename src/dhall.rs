@@ -0,0 +1,425 @@
+use crate::{KdlDocument, KdlError, KdlNode};
+
+impl KdlDocument {
+    /// Generates a schema document from a [Dhall](https://dhall-lang.org/)
+    /// type expression, like `{ host : Text, port : Natural }`.
+    ///
+    /// Each field of a record type becomes a node named after the field,
+    /// with the field's type as the node's type annotation:
+    ///
+    /// * Types like `Text`, `Natural` or `Bool` (or any other type name,
+    ///   which is kept as written) are just the annotation, as in
+    ///   `(Text)host`.
+    /// * `Optional T` and `List T` are annotated `(Optional)` and `(List)`,
+    ///   with `T` described by a single child named `-`.
+    /// * Nested records are annotated `(Record)`, with their fields as
+    ///   children.
+    /// * Unions are annotated `(Union)`, with one child per alternative.
+    ///   Alternatives that carry a value are annotated with its type, and
+    ///   ones that don't have no annotation at all.
+    ///
+    /// ```kdl
+    /// (Text)host
+    /// (Optional)port {
+    ///     (Natural)-
+    /// }
+    /// (Union)mode {
+    ///     (Text)Custom
+    ///     Default
+    /// }
+    /// ```
+    ///
+    /// The toplevel type must be a record, whose fields become the toplevel
+    /// nodes, or a union, whose alternatives do. Comments are skipped.
+    ///
+    /// This is only available with the `dhall` feature. Type expressions are
+    /// read by a small parser built into this crate, rather than by a Dhall
+    /// implementation, so only record and union types, `Optional`, `List`,
+    /// type names and parentheses are supported. Nothing is evaluated:
+    /// imports, `let` bindings and functions are rejected, and type names are
+    /// kept as written, even if they'd refer to a type alias in Dhall.
+    ///
+    /// Fails if the expression isn't a supported type expression, or isn't a
+    /// record or union type.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_dhall_type("{ host : Text, tags : List Text }").unwrap();
+    /// assert_eq!(doc.to_string(), "(Text)host\n(List)tags {\n    (Text)-\n}\n");
+    /// ```
+    pub fn from_dhall_type(type_expr: &str) -> Result<KdlDocument, KdlError> {
+        let mut parser = Parser {
+            input: type_expr,
+            pos: 0,
+        };
+        let error = |(offset, message): (usize, String)| {
+            KdlError::single(type_expr, (offset, 0), message, None)
+        };
+        let ty = parser.parse_type().map_err(error)?;
+        parser.skip_whitespace().map_err(error)?;
+        if parser.pos < type_expr.len() {
+            return parser.error("Unexpected input after type").map_err(error);
+        }
+        let mut doc = KdlDocument::new();
+        match ty {
+            DhallType::Record(fields) => {
+                for (name, ty) in fields {
+                    doc.nodes_mut().push(ty.into_node(&name));
+                }
+            }
+            DhallType::Union(alternatives) => {
+                for (name, ty) in alternatives {
+                    doc.nodes_mut().push(alternative_node(&name, ty));
+                }
+            }
+            _ => return Err(error((0, "Expected a record or union type".into()))),
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+enum DhallType {
+    Name(String),
+    Optional(Box<DhallType>),
+    List(Box<DhallType>),
+    Record(Vec<(String, DhallType)>),
+    Union(Vec<(String, Option<DhallType>)>),
+}
+
+impl DhallType {
+    fn into_node(self, name: &str) -> KdlNode {
+        let mut node = KdlNode::new(name);
+        match self {
+            DhallType::Name(ty) => node.set_ty(ty),
+            DhallType::Optional(ty) => {
+                node.set_ty("Optional");
+                node.ensure_children().nodes_mut().push(ty.into_node("-"));
+            }
+            DhallType::List(ty) => {
+                node.set_ty("List");
+                node.ensure_children().nodes_mut().push(ty.into_node("-"));
+            }
+            DhallType::Record(fields) => {
+                node.set_ty("Record");
+                let children = node.ensure_children();
+                for (name, ty) in fields {
+                    children.nodes_mut().push(ty.into_node(&name));
+                }
+            }
+            DhallType::Union(alternatives) => {
+                node.set_ty("Union");
+                let children = node.ensure_children();
+                for (name, ty) in alternatives {
+                    children.nodes_mut().push(alternative_node(&name, ty));
+                }
+            }
+        }
+        node
+    }
+}
+
+fn alternative_node(name: &str, ty: Option<DhallType>) -> KdlNode {
+    match ty {
+        Some(ty) => ty.into_node(name),
+        None => KdlNode::new(name),
+    }
+}
+
+type ParseResult<T> = Result<T, (usize, String)>;
+
+struct Parser<'a> {
+    input: &'a str,
+    pos: usize,
+}
+
+impl Parser<'_> {
+    fn rest(&self) -> &str {
+        &self.input[self.pos..]
+    }
+
+    fn error<T>(&self, message: &str) -> ParseResult<T> {
+        Err((self.pos, message.into()))
+    }
+
+    /// Skips whitespace, `-- line comments` and (nested) `{- block
+    /// comments -}`.
+    fn skip_whitespace(&mut self) -> ParseResult<()> {
+        loop {
+            self.pos = self.input.len() - self.rest().trim_start().len();
+            let rest = self.rest();
+            if rest.starts_with("--") {
+                self.pos += rest.find('\n').unwrap_or(rest.len());
+            } else if rest.starts_with("{-") {
+                let start = self.pos;
+                let mut depth = 0;
+                loop {
+                    let rest = self.rest();
+                    if rest.starts_with("{-") {
+                        depth += 1;
+                        self.pos += 2;
+                    } else if rest.starts_with("-}") {
+                        depth -= 1;
+                        self.pos += 2;
+                        if depth == 0 {
+                            break;
+                        }
+                    } else if let Some(c) = rest.chars().next() {
+                        self.pos += c.len_utf8();
+                    } else {
+                        return Err((start, "Unterminated block comment".into()));
+                    }
+                }
+            } else {
+                return Ok(());
+            }
+        }
+    }
+
+    /// Skips whitespace, then consumes `c` if it's next.
+    fn eat(&mut self, c: char) -> ParseResult<bool> {
+        self.skip_whitespace()?;
+        if self.rest().starts_with(c) {
+            self.pos += c.len_utf8();
+            Ok(true)
+        } else {
+            Ok(false)
+        }
+    }
+
+    fn parse_type(&mut self) -> ParseResult<DhallType> {
+        let ty = self.parse_atom()?;
+        let wrap: fn(Box<DhallType>) -> DhallType = match &ty {
+            DhallType::Name(name) if name == "Optional" => DhallType::Optional,
+            DhallType::Name(name) if name == "List" => DhallType::List,
+            _ => return Ok(ty),
+        };
+        Ok(wrap(Box::new(self.parse_atom()?)))
+    }
+
+    fn parse_atom(&mut self) -> ParseResult<DhallType> {
+        if self.eat('(')? {
+            let ty = self.parse_type()?;
+            if !self.eat(')')? {
+                return self.error("Expected `)`");
+            }
+            Ok(ty)
+        } else if self.eat('{')? {
+            self.parse_record()
+        } else if self.eat('<')? {
+            self.parse_union()
+        } else {
+            let start = self.pos;
+            let name = self
+                .rest()
+                .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/' | '.')))
+                .unwrap_or(self.rest().len());
+            if name == 0
+                || !self
+                    .rest()
+                    .starts_with(|c: char| c.is_alphabetic() || c == '_')
+            {
+                return self.error("Expected a type");
+            }
+            self.pos += name;
+            Ok(DhallType::Name(self.input[start..self.pos].into()))
+        }
+    }
+
+    fn parse_label(&mut self) -> ParseResult<String> {
+        self.skip_whitespace()?;
+        if let Some(quoted) = self.rest().strip_prefix('`') {
+            let Some(len) = quoted.find('`') else {
+                return self.error("Unterminated label");
+            };
+            let label = quoted[..len].to_string();
+            self.pos += len + 2;
+            return Ok(label);
+        }
+        let len = self
+            .rest()
+            .find(|c: char| !(c.is_alphanumeric() || matches!(c, '_' | '-' | '/')))
+            .unwrap_or(self.rest().len());
+        if len == 0
+            || !self
+                .rest()
+                .starts_with(|c: char| c.is_alphabetic() || c == '_')
+        {
+            return self.error("Expected a label");
+        }
+        let label = self.rest()[..len].to_string();
+        self.pos += len;
+        Ok(label)
+    }
+
+    /// Parses the rest of a `{ field : Type, ... }` record type.
+    fn parse_record(&mut self) -> ParseResult<DhallType> {
+        let mut fields = Vec::new();
+        self.eat(',')?;
+        while !self.eat('}')? {
+            let label = self.parse_label()?;
+            if !self.eat(':')? {
+                return self.error("Expected `:` after record field name");
+            }
+            fields.push((label, self.parse_type()?));
+            if !self.eat(',')? && !self.rest().starts_with('}') {
+                return self.error("Expected `,` or `}`");
+            }
+        }
+        Ok(DhallType::Record(fields))
+    }
+
+    /// Parses the rest of a `< Alternative : Type | ... >` union type.
+    fn parse_union(&mut self) -> ParseResult<DhallType> {
+        let mut alternatives = Vec::new();
+        self.eat('|')?;
+        while !self.eat('>')? {
+            let label = self.parse_label()?;
+            let ty = if self.eat(':')? {
+                Some(self.parse_type()?)
+            } else {
+                None
+            };
+            alternatives.push((label, ty));
+            if !self.eat('|')? && !self.rest().starts_with('>') {
+                return self.error("Expected `|` or `>`");
+            }
+        }
+        Ok(DhallType::Union(alternatives))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts_formatted;
+
+    #[test]
+    fn record_types() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_dhall_type,
+            r#"
+-- A server.
+{ host : Text
+, port : Natural
+, `max conns` : Integer {- nested {- comment -} -}
+, tls : { enabled : Bool, cert : Prelude.Path }
+, empty : {}
+}
+"#,
+            r#"
+(Text)host
+(Natural)port
+(Integer)"max conns"
+(Record)tls {
+    (Bool)enabled
+    (Prelude.Path)cert
+}
+(Record)empty {
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn union_types() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_dhall_type,
+            "{ mode : < Default | Custom : Text | Split : { left : Double, right : Double } > }",
+            r#"
+(Union)mode {
+    Default
+    (Text)Custom
+    (Record)Split {
+        (Double)left
+        (Double)right
+    }
+}
+"#,
+        )?;
+        assert_converts_formatted(
+            KdlDocument::from_dhall_type,
+            "< | Red | Green | Other : Text >",
+            r#"
+Red
+Green
+(Text)Other
+"#,
+        )
+    }
+
+    #[test]
+    fn optional_fields() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_dhall_type,
+            "{ nickname : Optional Text, proxy : Optional { url : Text }, port : (Optional (Natural)) }",
+            r#"
+(Optional)nickname {
+    (Text)-
+}
+(Optional)proxy {
+    (Record)- {
+        (Text)url
+    }
+}
+(Optional)port {
+    (Natural)-
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn list_fields() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_dhall_type,
+            "{ tags : List Text, matrix : List (List Double), users : List { name : Text, admin : Optional Bool } }",
+            r#"
+(List)tags {
+    (Text)-
+}
+(List)matrix {
+    (List)- {
+        (Double)-
+    }
+}
+(List)users {
+    (Record)- {
+        (Text)name
+        (Optional)admin {
+            (Bool)-
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "",
+            "Text",
+            "List Text",
+            "{ host : Text",
+            "{ host Text }",
+            "{ host : }",
+            "{ host : Text } extra",
+            "{ host : Text Natural }",
+            "{ host : (Text }",
+            "< A | B : >",
+            "{ `unterminated : Text }",
+            "{- unterminated { a : Text }",
+            "let T = { a : Text } in T",
+            "./types.dhall",
+            "{ a : ./types.dhall }",
+        ] {
+            assert!(
+                KdlDocument::from_dhall_type(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+    }
+}
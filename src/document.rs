@@ -1,10 +1,21 @@
 #[cfg(feature = "span")]
 use miette::SourceSpan;
-use std::fmt::Display;
+use std::{
+    collections::{hash_map::DefaultHasher, BTreeMap, BTreeSet, HashMap, HashSet},
+    fmt::Display,
+    hash::{Hash, Hasher},
+};
 
 #[cfg(feature = "v1")]
 use crate::KdlNodeFormat;
-use crate::{FormatConfig, KdlError, KdlNode, KdlValue};
+use crate::{
+    error::span_or_default, FormatConfig, KdlCycleError, KdlEntry, KdlError, KdlIdentifier,
+    KdlNode, KdlRefError, KdlUnknownPropError, KdlValue,
+};
+
+/// The widest a document can be for [`KdlDocument::to_adaptive_string`] to
+/// write it on a single line, in characters.
+const ADAPTIVE_LINE_WIDTH: usize = 80;
 
 /// Represents a KDL
 /// [`Document`](https://github.com/kdl-org/kdl/blob/main/SPEC.md#document).
@@ -20,18 +31,32 @@ use crate::{FormatConfig, KdlError, KdlNode, KdlValue};
 /// # use kdl::KdlDocument;
 /// let kdl: KdlDocument = "foo 1 2 3\nbar 4 5 6".parse().expect("parse failed");
 /// ```
+///
+/// Parsed documents keep all of their comments, along with the original
+/// whitespace, and [`Display`] writes them back out in the same places, so
+/// printing a parsed document gives back the exact input:
+/// ```rust
+/// # use kdl::KdlDocument;
+/// let input = "// Settings.\nfoo /* inline */ 1 /-2 {\n    bar // trailing\n}\n";
+/// let kdl: KdlDocument = input.parse().expect("parse failed");
+/// assert_eq!(kdl.to_string(), input);
+/// ```
 #[derive(Debug, Clone, Eq)]
 pub struct KdlDocument {
     pub(crate) nodes: Vec<KdlNode>,
     pub(crate) format: Option<KdlDocumentFormat>,
     #[cfg(feature = "span")]
     pub(crate) span: SourceSpan,
+    /// Lookup index built by [`KdlDocument::build_subtree_index`]. Dropped
+    /// whenever the nodes might have been changed.
+    pub(crate) name_index: Option<BTreeMap<String, usize>>,
 }
 
 impl PartialEq for KdlDocument {
     fn eq(&self, other: &Self) -> bool {
         self.nodes == other.nodes && self.format == other.format
         // Intentionally omitted: self.span == other.span
+        // Intentionally omitted: self.name_index == other.name_index
     }
 }
 
@@ -40,6 +65,7 @@ impl std::hash::Hash for KdlDocument {
         self.nodes.hash(state);
         self.format.hash(state);
         // Intentionally omitted: self.span.hash(state)
+        // Intentionally omitted: self.name_index.hash(state)
     }
 }
 
@@ -50,6 +76,7 @@ impl Default for KdlDocument {
             format: Default::default(),
             #[cfg(feature = "span")]
             span: SourceSpan::from(0..0),
+            name_index: None,
         }
     }
 }
@@ -60,6 +87,75 @@ impl KdlDocument {
         Default::default()
     }
 
+    /// Builds a document out of a flat list of key/value pairs, such as
+    /// those produced by a CLI argument parser or a set of environment
+    /// variables.
+    ///
+    /// Keys are split on `separator`, and every segment but the last becomes
+    /// a (possibly shared) parent node, so `server.tls.port` ends up as a
+    /// `port` node inside `tls` inside `server`. Values become arguments of
+    /// the leaf node. If the same key shows up more than once, its values
+    /// are appended to the same node as additional arguments, in order. An
+    /// empty `separator` disables nesting altogether.
+    ///
+    /// The resulting document has no formatting information. Use
+    /// [`KdlDocument::autoformat`] if you want it to look nice.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::{KdlDocument, KdlValue};
+    /// let mut doc = KdlDocument::from_structured_pairs(
+    ///     [
+    ///         ("verbose", KdlValue::from(true)),
+    ///         ("server.port", KdlValue::from(8080)),
+    ///     ],
+    ///     ".",
+    /// );
+    /// doc.autoformat();
+    /// assert_eq!(doc.to_string(), "verbose #true\nserver {\n    port 8080\n}\n");
+    /// ```
+    pub fn from_structured_pairs<K, V>(
+        pairs: impl IntoIterator<Item = (K, V)>,
+        separator: &str,
+    ) -> Self
+    where
+        K: AsRef<str>,
+        V: Into<KdlValue>,
+    {
+        let mut doc = KdlDocument::new();
+        for (key, value) in pairs {
+            let key = key.as_ref();
+            let mut segments: Vec<&str> = if separator.is_empty() {
+                vec![key]
+            } else {
+                key.split(separator).collect()
+            };
+            // `split()` always yields at least one item.
+            let leaf = segments.pop().unwrap_or(key);
+            let mut current = &mut doc;
+            for segment in segments {
+                let idx = current.position_or_push(segment);
+                current = current.nodes[idx].ensure_children();
+            }
+            let idx = current.position_or_push(leaf);
+            current.nodes[idx].push(value.into());
+        }
+        doc
+    }
+
+    /// Returns the index of the first node named `name`, appending a new,
+    /// empty node with that name if there isn't one yet.
+    fn position_or_push(&mut self, name: &str) -> usize {
+        self.name_index = None;
+        if let Some(idx) = self.nodes.iter().position(|n| n.name().value() == name) {
+            idx
+        } else {
+            self.nodes.push(KdlNode::new(name));
+            self.nodes.len() - 1
+        }
+    }
+
     /// Gets this document's span.
     ///
     /// This value will be properly initialized when created via [`KdlDocument::parse`]
@@ -83,6 +179,7 @@ impl KdlDocument {
 
     /// Gets a reference to the first child node with a matching name.
     pub fn get_mut(&mut self, name: &str) -> Option<&mut KdlNode> {
+        self.name_index = None;
         self.nodes
             .iter_mut()
             .find(move |n| n.name().value() == name)
@@ -190,6 +287,7 @@ impl KdlDocument {
 
     /// Returns a mutable reference to this document's child nodes.
     pub fn nodes_mut(&mut self) -> &mut Vec<KdlNode> {
+        self.name_index = None;
         &mut self.nodes
     }
 
@@ -267,214 +365,1654 @@ impl KdlDocument {
         };
     }
 
-    // TODO(@zkat): These should all be moved into the query module itself,
-    // instead of being methods on the models
-    //
-    // /// Queries this Document's children according to the KQL query language,
-    // /// returning an iterator over all matching nodes.
-    // ///
-    // /// # NOTE
-    // ///
-    // /// Any query selectors that try to select the toplevel `scope()` will
-    // /// fail to match when using this method, since there's no [`KdlNode`] to
-    // /// return in this case.
-    // pub fn query_all(
-    //     &self,
-    //     query: impl IntoKdlQuery,
-    // ) -> Result<KdlQueryIterator<'_>, KdlDiagnostic> {
-    //     let parsed = query.into_query()?;
-    //     Ok(KdlQueryIterator::new(None, Some(self), parsed))
-    // }
-
-    // /// Queries this Document's children according to the KQL query language,
-    // /// returning the first match, if any.
-    // ///
-    // /// # NOTE
-    // ///
-    // /// Any query selectors that try to select the toplevel `scope()` will
-    // /// fail to match when using this method, since there's no [`KdlNode`] to
-    // /// return in this case.
-    // pub fn query(&self, query: impl IntoKdlQuery) -> Result<Option<&KdlNode>, KdlDiagnostic> {
-    //     let mut iter = self.query_all(query)?;
-    //     Ok(iter.next())
-    // }
-
-    // /// Queries this Document's children according to the KQL query language,
-    // /// picking the first match, and calling `.get(key)` on it, if the query
-    // /// succeeded.
-    // ///
-    // /// # NOTE
-    // ///
-    // /// Any query selectors that try to select the toplevel `scope()` will
-    // /// fail to match when using this method, since there's no [`KdlNode`] to
-    // /// return in this case.
-    // pub fn query_get(
-    //     &self,
-    //     query: impl IntoKdlQuery,
-    //     key: impl Into<NodeKey>,
-    // ) -> Result<Option<&KdlValue>, KdlDiagnostic> {
-    //     Ok(self.query(query)?.and_then(|node| node.get(key)))
-    // }
+    /// Returns the chain of nodes leading from this document's top level
+    /// down to `target`, inclusive of `target` itself, or `None` if `target`
+    /// isn't part of this document.
+    ///
+    /// Nodes are compared by identity (pointer equality), not by value, so
+    /// `target` must be a reference into this very document. This is mostly
+    /// useful for error reporting, where you might want to print something
+    /// like `server > tls > cert`. It walks the whole tree, so it's `O(n)` in
+    /// the number of nodes.
+    pub fn node_path_to<'a>(&'a self, target: &KdlNode) -> Option<Vec<&'a KdlNode>> {
+        let mut path = Vec::new();
+        if self.node_path_to_impl(target, &mut path) {
+            Some(path)
+        } else {
+            None
+        }
+    }
 
-    // /// Queries this Document's children according to the KQL query language,
-    // /// returning an iterator over all matching nodes, returning the requested
-    // /// field from each of those nodes and filtering out nodes that don't have
-    // /// it.
-    // ///
-    // /// # NOTE
-    // ///
-    // /// Any query selectors that try to select the toplevel `scope()` will
-    // /// fail to match when using this method, since there's no [`KdlNode`] to
-    // /// return in this case.
-    // pub fn query_get_all(
-    //     &self,
-    //     query: impl IntoKdlQuery,
-    //     key: impl Into<NodeKey>,
-    // ) -> Result<impl Iterator<Item = &KdlValue>, KdlDiagnostic> {
-    //     let key: NodeKey = key.into();
-    //     Ok(self
-    //         .query_all(query)?
-    //         .filter_map(move |node| node.get(key.clone())))
-    // }
+    fn node_path_to_impl<'a>(&'a self, target: &KdlNode, path: &mut Vec<&'a KdlNode>) -> bool {
+        for node in &self.nodes {
+            path.push(node);
+            if std::ptr::eq(node, target) {
+                return true;
+            }
+            if let Some(children) = node.children() {
+                if children.node_path_to_impl(target, path) {
+                    return true;
+                }
+            }
+            path.pop();
+        }
+        false
+    }
 
-    /// Parses a string into a document.
+    /// Removes nodes with duplicate names at every level of this document,
+    /// returning the total number of nodes that were removed or merged away.
     ///
-    /// If the `v1-fallback` feature is enabled, this method will first try to
-    /// parse the string as a KDL v2 document, and, if that fails, it will try
-    /// to parse again as a KDL v1 document. If both fail, a heuristic will be
-    /// applied to try and detect the "intended" KDL version, and that version's
-    /// error(s) will be returned.
-    pub fn parse(s: &str) -> Result<Self, KdlError> {
-        #[cfg(not(feature = "v1-fallback"))]
-        {
-            Self::parse_v2(s)
-        }
-        #[cfg(feature = "v1-fallback")]
-        {
-            let v2_res = KdlDocument::parse_v2(s);
-            if v2_res.is_err() {
-                let v1_res = KdlDocument::parse_v1(s);
-                if v1_res.is_ok() || detect_v1(s) {
-                    v1_res
+    /// When `merge` is `false`, only the _last_ node with any given name is
+    /// kept (much like INI files tend to work). When `merge` is `true`, all
+    /// nodes with the same name are folded into the first one: arguments are
+    /// concatenated in order, properties are merged with the last value
+    /// winning, and children blocks are concatenated before being
+    /// deduplicated themselves.
+    pub fn deduplicate_children_recursive(&mut self, merge: bool) -> usize {
+        self.name_index = None;
+        let mut count = 0;
+        if merge {
+            let mut seen: HashMap<String, usize> = HashMap::new();
+            let mut merged: Vec<KdlNode> = Vec::with_capacity(self.nodes.len());
+            for node in std::mem::take(&mut self.nodes) {
+                if let Some(&idx) = seen.get(node.name().value()) {
+                    merge_node_into(&mut merged[idx], node);
+                    count += 1;
                 } else {
-                    // TODO(@zkat): maybe we can add something to the error
-                    // message to specify that it's "uncertain"?
-                    // YOLO.
-                    v2_res
+                    seen.insert(node.name().value().into(), merged.len());
+                    merged.push(node);
                 }
-            } else {
-                v2_res
             }
+            self.nodes = merged;
+        } else {
+            let mut last: HashMap<&str, usize> = HashMap::new();
+            for (idx, node) in self.nodes.iter().enumerate() {
+                last.insert(node.name().value(), idx);
+            }
+            let keep: Vec<bool> = self
+                .nodes
+                .iter()
+                .enumerate()
+                .map(|(idx, node)| last.get(node.name().value()) == Some(&idx))
+                .collect();
+            let before = self.nodes.len();
+            let mut keep = keep.into_iter();
+            self.nodes.retain(|_| keep.next().unwrap_or(true));
+            count += before - self.nodes.len();
         }
+        for node in &mut self.nodes {
+            if let Some(children) = node.children_mut() {
+                count += children.deduplicate_children_recursive(merge);
+            }
+        }
+        count
     }
 
-    /// Parses a KDL v2 string into a document.
-    pub fn parse_v2(s: &str) -> Result<Self, KdlError> {
-        crate::v2_parser::try_parse(crate::v2_parser::document, s)
+    /// Applies `f` to every value (arguments and property values alike) of
+    /// the node found at `path`, without descending into its children.
+    ///
+    /// `path` is a list of node names, starting from this document's top
+    /// level, where the first node with a matching name is picked at each
+    /// step. Nothing happens if there's no node at `path`. Values that `f`
+    /// changes have their representation reset, so the new value is what
+    /// gets written out, but surrounding whitespace and comments are kept.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::{KdlDocument, KdlValue};
+    /// let mut doc: KdlDocument = "server { listen 80 8080; }\nother 80".parse().unwrap();
+    /// doc.apply_to_values_at_path(&["server", "listen"], |v| {
+    ///     if let KdlValue::Integer(port) = v {
+    ///         *port += 1;
+    ///     }
+    /// });
+    /// assert_eq!(doc.to_string(), "server { listen 81 8081; }\nother 80");
+    /// ```
+    pub fn apply_to_values_at_path<F: Fn(&mut KdlValue)>(&mut self, path: &[&str], f: F) {
+        if let Some(node) = self.node_at_path_mut(path) {
+            apply_to_node_values(node, &f, false);
+        }
     }
 
-    /// Parses a KDL v1 string into a document.
-    #[cfg(feature = "v1")]
-    pub fn parse_v1(s: &str) -> Result<Self, KdlError> {
-        let ret: Result<kdlv1::KdlDocument, kdlv1::KdlError> = s.parse();
-        ret.map(|x| x.into()).map_err(|e| e.into())
+    /// Like [`KdlDocument::apply_to_values_at_path`], but also applies `f` to
+    /// the values of all of that node's descendants.
+    pub fn apply_to_all_values_at_path<F: Fn(&mut KdlValue)>(&mut self, path: &[&str], f: F) {
+        if let Some(node) = self.node_at_path_mut(path) {
+            apply_to_node_values(node, &f, true);
+        }
     }
 
-    /// Takes a KDL v1 document string and returns the same document, but
-    /// autoformatted into valid KDL v2 syntax.
-    #[cfg(feature = "v1")]
-    pub fn v1_to_v2(s: &str) -> Result<String, KdlError> {
-        let mut doc = KdlDocument::parse_v1(s)?;
-        doc.ensure_v2();
-        Ok(doc.to_string())
+    /// Walks down `path`, picking the first node with a matching name at
+    /// each level.
+    pub(crate) fn node_at_path_mut(&mut self, path: &[&str]) -> Option<&mut KdlNode> {
+        let (first, rest) = path.split_first()?;
+        let node = self.get_mut(first)?;
+        if rest.is_empty() {
+            Some(node)
+        } else {
+            node.children_mut().as_mut()?.node_at_path_mut(rest)
+        }
     }
 
-    /// Takes a KDL v2 document string and returns the same document, but
-    /// autoformatted into valid KDL v1 syntax.
-    #[cfg(feature = "v1")]
-    pub fn v2_to_v1(s: &str) -> Result<String, KdlError> {
-        let mut doc = KdlDocument::parse_v2(s)?;
-        doc.ensure_v1();
-        Ok(doc.to_string())
+    /// Creates a new document containing `nodes`, with no formatting of its
+    /// own. This is the reverse of [`KdlDocument::collect_nodes`].
+    pub fn from_nodes(nodes: Vec<KdlNode>) -> Self {
+        Self {
+            nodes,
+            ..Default::default()
+        }
     }
 
-    /// Makes sure this document is in v2 format.
-    pub fn ensure_v2(&mut self) {
-        // No need to touch KdlDocumentFormat, probably. In the longer term,
-        // we'll want to make sure to parse out whitespace and comments and make
-        // sure they're actually compliant, but this is good enough for now.
-        for node in self.nodes_mut().iter_mut() {
-            node.ensure_v2();
-        }
+    /// Consumes this document, returning its toplevel nodes. Any
+    /// document-level formatting (leading/trailing whitespace and comments)
+    /// is discarded.
+    pub fn collect_nodes(self) -> Vec<KdlNode> {
+        self.nodes
     }
 
-    /// Makes sure this document is in v1 format.
-    #[cfg(feature = "v1")]
-    pub fn ensure_v1(&mut self) {
-        // No need to touch KdlDocumentFormat, probably. In the longer term,
-        // we'll want to make sure to parse out whitespace and comments and make
-        // sure they're actually compliant, but this is good enough for now.
+    /// Removes all toplevel nodes from this document, lazily yielding them in
+    /// order. The document itself (and its formatting) is left in place.
+    ///
+    /// As with [`Vec::drain`], all nodes are removed even if the iterator is
+    /// dropped before being fully consumed.
+    pub fn drain_nodes(&mut self) -> impl Iterator<Item = KdlNode> + '_ {
+        self.name_index = None;
+        self.nodes.drain(..)
+    }
 
-        // the last node in v1 docs/children has to have a semicolon.
-        let mut iter = self.nodes_mut().iter_mut().rev();
-        let last = iter.next();
-        let penult = iter.next();
-        if let Some(last) = last {
-            if let Some(fmt) = last.format_mut() {
-                if !fmt.trailing.contains(';')
-                    && fmt
-                        .trailing
-                        .chars()
-                        .any(|c| crate::v2_parser::NEWLINES.iter().any(|nl| nl.contains(c)))
-                {
-                    fmt.terminator = ";".into();
-                }
-            } else {
-                let maybe_indent = {
-                    if let Some(penult) = penult {
-                        if let Some(fmt) = penult.format() {
-                            fmt.leading.clone()
-                        } else {
-                            "".into()
-                        }
-                    } else {
-                        "".into()
-                    }
-                };
-                last.format = Some(KdlNodeFormat {
-                    leading: maybe_indent,
-                    terminator: "\n".into(),
-                    ..Default::default()
-                })
+    /// Consumes this document, splitting its toplevel nodes into two new
+    /// documents: one with all the nodes for which `pred` returns `true`, and
+    /// one with all the rest. Node order is preserved within each half.
+    ///
+    /// This is the document equivalent of [`Iterator::partition`].
+    /// Document-level formatting is discarded, since there's no telling which
+    /// half it belongs to.
+    pub fn partition_nodes(self, pred: impl Fn(&KdlNode) -> bool) -> (KdlDocument, KdlDocument) {
+        let (matching, rest): (Vec<KdlNode>, Vec<KdlNode>) =
+            self.nodes.into_iter().partition(|node| pred(node));
+        (
+            KdlDocument::from_nodes(matching),
+            KdlDocument::from_nodes(rest),
+        )
+    }
+
+    /// Hashes every node's subtree (the node itself, including its entries
+    /// and children), returning a map from each node's path, like
+    /// `"server/tls"`, to its hash.
+    ///
+    /// Since a change anywhere in a subtree changes the hashes of all its
+    /// ancestors, but not those of its siblings, comparing two of these maps
+    /// shows exactly which parts of a document changed. Formatting counts as
+    /// part of a node, so call [`KdlDocument::clear_format_recursive`] first
+    /// if whitespace and comment changes should be ignored.
+    ///
+    /// The first node with a given name under a parent gets the plain name
+    /// as its path segment. Later siblings with the same name get their
+    /// occurrence index appended: `item`, `item[1]`, `item[2]`, and so on.
+    ///
+    /// Hashes are stable across runs of the same build, but aren't
+    /// guaranteed to be stable across Rust versions, so they shouldn't be
+    /// persisted long-term.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let old: KdlDocument = "server { tls #true; port 80 }".parse().unwrap();
+    /// let new: KdlDocument = "server { tls #false; port 80 }".parse().unwrap();
+    /// let (old, new) = (old.checksum_node_paths(), new.checksum_node_paths());
+    /// assert_ne!(old["server"], new["server"]);
+    /// assert_ne!(old["server/tls"], new["server/tls"]);
+    /// assert_eq!(old["server/port"], new["server/port"]);
+    /// ```
+    pub fn checksum_node_paths(&self) -> HashMap<String, u64> {
+        let mut checksums = HashMap::new();
+        self.checksum_node_paths_impl("", &mut checksums);
+        checksums
+    }
+
+    fn checksum_node_paths_impl(&self, prefix: &str, checksums: &mut HashMap<String, u64>) {
+        let mut seen: HashMap<&str, usize> = HashMap::new();
+        for node in &self.nodes {
+            let name = node.name().value();
+            let count = seen.entry(name).or_default();
+            let path = match *count {
+                0 => format!("{prefix}{name}"),
+                n => format!("{prefix}{name}[{n}]"),
+            };
+            *count += 1;
+            let mut hasher = DefaultHasher::new();
+            node.hash(&mut hasher);
+            if let Some(children) = node.children() {
+                children.checksum_node_paths_impl(&format!("{path}/"), checksums);
             }
-        }
-        for node in self.nodes_mut().iter_mut() {
-            node.ensure_v1();
+            checksums.insert(path, hasher.finish());
         }
     }
-}
 
-#[cfg(feature = "v1")]
-impl From<kdlv1::KdlDocument> for KdlDocument {
-    fn from(value: kdlv1::KdlDocument) -> Self {
-        Self {
-            nodes: value.nodes().iter().map(|x| x.clone().into()).collect(),
-            format: Some(KdlDocumentFormat {
-                leading: value.leading().unwrap_or("").into(),
-                trailing: value.trailing().unwrap_or("").into(),
-            }),
-            #[cfg(feature = "span")]
-            span: SourceSpan::new(value.span().offset().into(), value.span().len()),
+    /// Replaces the toplevel node at `index` with `new_node`, returning the
+    /// node that was there before. Other nodes stay where they are.
+    ///
+    /// The document's own formatting is cleared, so it'll be rendered with
+    /// default formatting around its nodes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index` is out of bounds.
+    pub fn replace_node_at(&mut self, index: usize, new_node: KdlNode) -> KdlNode {
+        self.name_index = None;
+        let old = std::mem::replace(&mut self.nodes[index], new_node);
+        self.format = None;
+        old
+    }
+
+    /// Builds an index from each distinct toplevel node name to the position
+    /// of the first node with that name, so that
+    /// [`KdlDocument::get_node_by_name_indexed`] can find nodes in `O(log n)`
+    /// time instead of scanning through all of them. This is worth it for
+    /// large, registry-style documents that get looked up a lot.
+    ///
+    /// The index is dropped as soon as the document's nodes could have been
+    /// changed (for example, by calling [`KdlDocument::nodes_mut`] or
+    /// [`KdlDocument::get_mut`]), and lookups go back to scanning until this
+    /// is called again.
+    pub fn build_subtree_index(&mut self) {
+        let mut index = BTreeMap::new();
+        for (idx, node) in self.nodes.iter().enumerate() {
+            index.entry(node.name().value().to_string()).or_insert(idx);
         }
+        self.name_index = Some(index);
     }
-}
 
-/// Applies heuristics to get an idea of whether the string might be intended to
-/// be v2.
-#[allow(unused)]
-pub(crate) fn detect_v2(input: &str) -> bool {
-    for line in input.lines() {
-        if line.contains("kdl-version 2")
+    /// Gets the first toplevel node with a matching name, just like
+    /// [`KdlDocument::get`], but using the index built by
+    /// [`KdlDocument::build_subtree_index`] if it's still valid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::{KdlDocument, KdlNode};
+    /// let mut doc = KdlDocument::from_nodes(
+    ///     (0..1000).map(|i| KdlNode::new(format!("service-{i}"))).collect(),
+    /// );
+    /// doc.build_subtree_index();
+    /// assert_eq!(
+    ///     doc.get_node_by_name_indexed("service-999").map(|n| n.name().value()),
+    ///     Some("service-999")
+    /// );
+    /// ```
+    pub fn get_node_by_name_indexed(&self, name: &str) -> Option<&KdlNode> {
+        match &self.name_index {
+            Some(index) => index.get(name).map(|&idx| &self.nodes[idx]),
+            None => self.get(name),
+        }
+    }
+
+    /// Iterates over every node in this document, at any depth, in document
+    /// order (each node comes right before its children).
+    pub(crate) fn descendants(&self) -> Descendants<'_> {
+        Descendants {
+            stack: vec![self.nodes.iter()],
+        }
+    }
+
+    /// Returns an iterator over all nodes in this document, at any depth,
+    /// that don't have any children. Nodes with an empty children block
+    /// (`node {}`) count as leaves.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "a { b; c { d } }\ne {}".parse().unwrap();
+    /// let leaves: Vec<_> = doc.leaf_nodes().map(|n| n.name().value()).collect();
+    /// assert_eq!(leaves, vec!["b", "d", "e"]);
+    /// let branches: Vec<_> = doc.branch_nodes().map(|n| n.name().value()).collect();
+    /// assert_eq!(branches, vec!["a", "c"]);
+    /// ```
+    pub fn leaf_nodes(&self) -> impl Iterator<Item = &KdlNode> {
+        self.descendants().filter(|node| !has_children(node))
+    }
+
+    /// Returns an iterator over all nodes in this document, at any depth,
+    /// that have at least one child node.
+    pub fn branch_nodes(&self) -> impl Iterator<Item = &KdlNode> {
+        self.descendants().filter(|node| has_children(node))
+    }
+
+    /// Like [`KdlDocument::leaf_nodes`], but only looks at this document's
+    /// toplevel nodes.
+    pub fn top_level_leaf_nodes(&self) -> impl Iterator<Item = &KdlNode> {
+        self.nodes.iter().filter(|node| !has_children(node))
+    }
+
+    /// Like [`KdlDocument::branch_nodes`], but only looks at this document's
+    /// toplevel nodes.
+    pub fn top_level_branch_nodes(&self) -> impl Iterator<Item = &KdlNode> {
+        self.nodes.iter().filter(|node| has_children(node))
+    }
+
+    /// Collects the values of every property named `prop_name`, on every node
+    /// in this document at any depth, in document order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "a timeout=5 { b timeout=10 }\nc timeout=-1".parse().unwrap();
+    /// let timeouts = doc.collect_property_values("timeout");
+    /// assert_eq!(timeouts, vec![&5.into(), &10.into(), &(-1).into()]);
+    /// assert!(!timeouts.iter().all(|t| t.as_integer().is_some_and(|t| t > 0)));
+    /// ```
+    pub fn collect_property_values(&self, prop_name: &str) -> Vec<&KdlValue> {
+        self.descendants()
+            .flat_map(|node| node.iter())
+            .filter(|entry| entry.name().map(|n| n.value()) == Some(prop_name))
+            .map(|entry| entry.value())
+            .collect()
+    }
+
+    /// Mutable version of [`KdlDocument::collect_property_values`].
+    pub fn collect_property_values_mut(&mut self, prop_name: &str) -> Vec<&mut KdlValue> {
+        let mut values = Vec::new();
+        collect_property_values_mut(self, prop_name, &mut values);
+        values
+    }
+
+    /// Consumes this document, splitting it into two: one with the toplevel
+    /// nodes in `[0, index)`, and one with the nodes in `[index, len)`. This
+    /// is the document equivalent of [`Vec::split_off`]. Document-level
+    /// formatting is discarded.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `index > len`, just like [`Vec::split_off`].
+    pub fn split_at_node(mut self, index: usize) -> (KdlDocument, KdlDocument) {
+        let rest = self.nodes.split_off(index);
+        (
+            KdlDocument::from_nodes(self.nodes),
+            KdlDocument::from_nodes(rest),
+        )
+    }
+
+    /// Appends `node` to the children of the node at `path`, creating that
+    /// node's children block if needed. Each segment of `path` picks the
+    /// first node with a matching name at its level, and an empty `path`
+    /// appends `node` to this document itself.
+    ///
+    /// If a node along `path` doesn't exist, this fails, unless
+    /// `create_missing` is `true`, in which case any missing nodes are created
+    /// along the way.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::{KdlDocument, KdlNode};
+    /// let mut doc = KdlDocument::new();
+    /// let route = KdlNode::new("route").with_appended_arg("/");
+    /// assert!(doc.inject_at_path(&["http", "server"], route.clone(), false).is_err());
+    /// doc.inject_at_path(&["http", "server"], route, true).unwrap();
+    /// doc.autoformat();
+    /// assert_eq!(doc.to_string(), "http {\n    server {\n        route \"/\"\n    }\n}\n");
+    /// ```
+    pub fn inject_at_path(
+        &mut self,
+        path: &[&str],
+        node: KdlNode,
+        create_missing: bool,
+    ) -> Result<(), KdlError> {
+        if !create_missing {
+            // Spans from parsing may not line up with the document anymore if
+            // it's been changed since, so don't point at anything.
+            let missing = |depth: usize| {
+                KdlError::single(
+                    "",
+                    (0, 0),
+                    format!("No node at path `{}`", path[..=depth].join("/")),
+                    Some("Pass `create_missing: true` to create missing nodes."),
+                )
+            };
+            let mut current: &KdlDocument = self;
+            for (depth, segment) in path.iter().enumerate() {
+                let next = current.get(segment).ok_or_else(|| missing(depth))?;
+                match next.children() {
+                    Some(children) => current = children,
+                    None if depth + 1 < path.len() => return Err(missing(depth + 1)),
+                    None => {}
+                }
+            }
+        }
+        let mut current = self;
+        for segment in path {
+            let idx = current.position_or_push(segment);
+            current = current.nodes[idx].ensure_children();
+        }
+        current.nodes_mut().push(node);
+        Ok(())
+    }
+
+    /// Sorts this document's nodes so every node comes after all of the nodes
+    /// it depends on. `dep_fn` returns the names of the nodes a given node
+    /// depends on. A dependency on a name shared by several nodes is a
+    /// dependency on all of them, and names that don't match any node are
+    /// ignored.
+    ///
+    /// Only this document's own nodes are sorted, not their children.
+    /// Whenever more than one node has all of its dependencies in place, the
+    /// one that comes first in the document goes next, so an already-sorted
+    /// document keeps its order.
+    ///
+    /// Fails with a [`KdlCycleError`] describing the cycle if the dependencies
+    /// form one.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "app deps=db\ndb deps=disk\ndisk".parse().unwrap();
+    /// let sorted = doc
+    ///     .topological_sort(|node| node.get("deps").and_then(|d| d.as_string()).into_iter().collect())
+    ///     .unwrap();
+    /// let names: Vec<_> = sorted.iter().map(|n| n.name().value()).collect();
+    /// assert_eq!(names, vec!["disk", "db", "app"]);
+    /// ```
+    pub fn topological_sort<'a>(
+        &'a self,
+        dep_fn: impl Fn(&'a KdlNode) -> Vec<&'a str>,
+    ) -> Result<Vec<&'a KdlNode>, KdlCycleError> {
+        let nodes = self.nodes();
+        let deps = nodes
+            .iter()
+            .map(|node| {
+                dep_fn(node)
+                    .into_iter()
+                    .flat_map(|name| {
+                        nodes
+                            .iter()
+                            .enumerate()
+                            .filter(move |(_, n)| n.name().value() == name)
+                            .map(|(idx, _)| idx)
+                    })
+                    .collect::<Vec<_>>()
+            })
+            .collect::<Vec<_>>();
+        let mut dependents = vec![Vec::new(); nodes.len()];
+        for (idx, node_deps) in deps.iter().enumerate() {
+            for &dep in node_deps {
+                dependents[dep].push(idx);
+            }
+        }
+        let mut pending: Vec<usize> = deps.iter().map(Vec::len).collect();
+        let mut ready: BTreeSet<usize> = (0..nodes.len()).filter(|&i| pending[i] == 0).collect();
+        let mut out = Vec::with_capacity(nodes.len());
+        while let Some(idx) = ready.pop_first() {
+            out.push(&nodes[idx]);
+            for &dependent in &dependents[idx] {
+                pending[dependent] -= 1;
+                if pending[dependent] == 0 {
+                    ready.insert(dependent);
+                }
+            }
+        }
+        if out.len() < nodes.len() {
+            // Every node left has a dependency that's also left, so following
+            // those from any of them has to run into a cycle.
+            let mut path = vec![(0..nodes.len()).find(|&i| pending[i] > 0).unwrap()];
+            loop {
+                let last = *path.last().unwrap();
+                let next = *deps[last].iter().find(|&&dep| pending[dep] > 0).unwrap();
+                if let Some(start) = path.iter().position(|&i| i == next) {
+                    let cycle = path[start..]
+                        .iter()
+                        .chain(std::iter::once(&next))
+                        .map(|&i| nodes[i].name().value().to_string())
+                        .collect();
+                    return Err(KdlCycleError { cycle });
+                }
+                path.push(next);
+            }
+        }
+        Ok(out)
+    }
+
+    /// Passes this document through `transform`, returning the result. This
+    /// lets you chain document transformations fluently:
+    ///
+    /// ```rust
+    /// # use kdl::{KdlDocument, KdlNode};
+    /// fn add_defaults(mut doc: KdlDocument) -> KdlDocument {
+    ///     if doc.get("log-level").is_none() {
+    ///         doc.nodes_mut().push(KdlNode::new("log-level").with_appended_arg("info"));
+    ///     }
+    ///     doc
+    /// }
+    ///
+    /// fn normalize(mut doc: KdlDocument) -> KdlDocument {
+    ///     doc.autoformat();
+    ///     doc
+    /// }
+    ///
+    /// let doc: KdlDocument = "port    8080".parse().unwrap();
+    /// let doc = doc.pipe(add_defaults).pipe(normalize);
+    /// assert_eq!(doc.to_string(), "port 8080\nlog-level info\n");
+    /// ```
+    pub fn pipe(self, transform: impl Fn(KdlDocument) -> KdlDocument) -> KdlDocument {
+        transform(self)
+    }
+
+    /// Like [`KdlDocument::pipe`], but for transformations that can fail.
+    /// Chain these with `?` (or [`Result::and_then`]) to stop at the first
+    /// failing transformation.
+    pub fn try_pipe(
+        self,
+        transform: impl Fn(KdlDocument) -> Result<KdlDocument, KdlError>,
+    ) -> Result<KdlDocument, KdlError> {
+        transform(self)
+    }
+
+    /// Fills in missing properties from `defaults`, without overwriting any
+    /// existing ones.
+    ///
+    /// Each node in this document is matched with the first node with the same
+    /// name at the same level in `defaults`, and gets a copy of every property
+    /// the default node has that it doesn't. Children are then matched
+    /// recursively against the default node's children. Nodes with no
+    /// matching default are left untouched, and nodes in `defaults` with no
+    /// matching node aren't added.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let mut doc: KdlDocument = "server port=9090 {\n    tls cert=a.pem\n}".parse().unwrap();
+    /// let defaults: KdlDocument = "server host=localhost port=8080 {\n    tls enabled=#true\n}".parse().unwrap();
+    /// doc.annotate_with_defaults(&defaults);
+    /// assert_eq!(doc.to_string(), "server port=9090 host=localhost {\n    tls cert=a.pem enabled=#true\n}");
+    /// ```
+    pub fn annotate_with_defaults(&mut self, defaults: &KdlDocument) {
+        for node in self.nodes_mut() {
+            let Some(default) = defaults.get(node.name().value()) else {
+                continue;
+            };
+            for entry in default.iter() {
+                if let Some(name) = entry.name() {
+                    if node.entry(name.value()).is_none() {
+                        node.push(entry.clone());
+                    }
+                }
+            }
+            if let (Some(children), Some(default_children)) =
+                (node.children_mut(), default.children())
+            {
+                children.annotate_with_defaults(default_children);
+            }
+        }
+    }
+
+    /// Returns this document as a string, with every comment it was parsed
+    /// with, including slashdashed nodes and entries, written back out where
+    /// it was. This is the same as its [`Display`] output, and is valid KDL.
+    ///
+    /// Comments are only kept as long as the formatting they're part of is:
+    /// [`KdlDocument::autoformat`] and [`KdlDocument::clear_format_recursive`]
+    /// can both drop them.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let input = "// Settings.\nport /* default */ 8080 /-9090\n";
+    /// let doc: KdlDocument = input.parse().unwrap();
+    /// assert_eq!(doc.to_string_with_comments(), input);
+    /// ```
+    pub fn to_string_with_comments(&self) -> String {
+        self.to_string()
+    }
+
+    /// Returns this document as a string, formatted on a single line with
+    /// its nodes separated by `;` if it [fits on one
+    /// line](KdlDocument::fits_on_one_line) of 80 characters, and
+    /// [autoformatted](KdlDocument::autoformat) over multiple lines otherwise.
+    ///
+    /// Both forms ignore the document's original formatting, including
+    /// comments, and always parse back into an equivalent document.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "name  \"kdl\"\nversion 2\nstable".parse().unwrap();
+    /// assert_eq!(doc.to_adaptive_string(), "name kdl; version 2; stable");
+    ///
+    /// let doc: KdlDocument = "server port=8080 host=localhost".parse().unwrap();
+    /// assert_eq!(doc.to_adaptive_string(), "server port=8080 host=localhost\n");
+    /// ```
+    pub fn to_adaptive_string(&self) -> String {
+        match self.single_line_string() {
+            Some(line) if line.chars().count() <= ADAPTIVE_LINE_WIDTH => line,
+            _ => {
+                let mut doc = self.clone();
+                doc.autoformat_no_comments();
+                doc.to_string()
+            }
+        }
+    }
+
+    /// Returns `true` if this document can be written on a single line of at
+    /// most `max_width` characters, with its nodes separated by `;`. That's
+    /// only the case if none of its nodes have children, and each of them
+    /// has at most one entry.
+    pub fn fits_on_one_line(&self, max_width: usize) -> bool {
+        self.single_line_string()
+            .is_some_and(|line| line.chars().count() <= max_width)
+    }
+
+    fn single_line_string(&self) -> Option<String> {
+        let ident = |ident: &KdlIdentifier| KdlValue::String(ident.value().into()).to_string();
+        let mut nodes = Vec::with_capacity(self.nodes.len());
+        for node in &self.nodes {
+            if node.children().is_some() || node.entries().len() > 1 {
+                return None;
+            }
+            let mut line = String::new();
+            if let Some(ty) = node.ty() {
+                line.push('(');
+                line.push_str(&ident(ty));
+                line.push(')');
+            }
+            line.push_str(&ident(node.name()));
+            for entry in node.entries() {
+                line.push(' ');
+                line.push_str(&entry.to_inline_string());
+            }
+            nodes.push(line);
+        }
+        Some(nodes.join("; "))
+    }
+
+    /// Consumes this document, returning a new node called `name` with this
+    /// document as its children. This is the inverse of
+    /// [`KdlNode::into_document`].
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "host localhost\nport 8080".parse().unwrap();
+    /// let mut node = doc.wrap_in_node("server");
+    /// node.autoformat();
+    /// assert_eq!(node.to_string(), "server {\n    host localhost\n    port 8080\n}\n");
+    /// ```
+    pub fn wrap_in_node(self, name: impl Into<KdlIdentifier>) -> KdlNode {
+        let mut node = KdlNode::new(name);
+        node.set_children(self);
+        node
+    }
+
+    /// Counts how many of this document's toplevel nodes, and the entries on
+    /// them, have each type annotation. Both nodes and entries are counted,
+    /// in the same map, and ones without a type annotation are counted under
+    /// `"(none)"`. Children aren't included; use
+    /// [`KdlDocument::count_by_type_annotation_recursive`] for that.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "(timestamp)created 1700000000\n(timestamp)updated (u64)1".parse().unwrap();
+    /// let counts = doc.count_by_type_annotation();
+    /// assert_eq!(counts["timestamp"], 2);
+    /// assert_eq!(counts["u64"], 1);
+    /// assert_eq!(counts["(none)"], 1);
+    /// ```
+    pub fn count_by_type_annotation(&self) -> HashMap<&str, usize> {
+        let mut counts = HashMap::new();
+        for node in &self.nodes {
+            count_type_annotations(node, &mut counts, Some("(none)"));
+        }
+        counts
+    }
+
+    /// Like [`KdlDocument::count_by_type_annotation`], but counts nodes and
+    /// entries at every depth.
+    pub fn count_by_type_annotation_recursive(&self) -> HashMap<&str, usize> {
+        let mut counts = HashMap::new();
+        for node in self.descendants() {
+            count_type_annotations(node, &mut counts, Some("(none)"));
+        }
+        counts
+    }
+
+    /// Collects every distinct type annotation used in this document, on
+    /// nodes and entries at any depth.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "(date)created \"2024-01-01\" {\n    (u8)retries (u8)3 5\n}".parse().unwrap();
+    /// let types = doc.collect_all_type_annotations();
+    /// assert_eq!(types.len(), 2);
+    /// assert!(types.contains("date") && types.contains("u8"));
+    /// ```
+    pub fn collect_all_type_annotations(&self) -> HashSet<&str> {
+        self.collect_type_annotations_with_counts()
+            .into_keys()
+            .collect()
+    }
+
+    /// Like [`KdlDocument::collect_all_type_annotations`], but also counts
+    /// how many nodes and entries use each type annotation. Unlike
+    /// [`KdlDocument::count_by_type_annotation_recursive`], nodes and entries
+    /// without a type annotation aren't counted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "(date)created \"2024-01-01\" {\n    (u8)retries (u8)3 5\n}".parse().unwrap();
+    /// let counts = doc.collect_type_annotations_with_counts();
+    /// assert_eq!(counts["u8"], 2);
+    /// assert_eq!(counts["date"], 1);
+    /// assert_eq!(counts.len(), 2);
+    /// ```
+    pub fn collect_type_annotations_with_counts(&self) -> HashMap<&str, usize> {
+        let mut counts = HashMap::new();
+        for node in self.descendants() {
+            count_type_annotations(node, &mut counts, None);
+        }
+        counts
+    }
+
+    /// Returns a quick overview of this document's structure, with one line
+    /// per node giving its depth, name, and how many entries and children it
+    /// has, indented by depth:
+    ///
+    /// ```text
+    /// [0] server (2 entries, 1 child)
+    ///   [1] route (1 entry, 0 children)
+    /// ```
+    ///
+    /// Values aren't included; see `KdlDocument::debug_string` (with the
+    /// `debug-print` feature) for a complete description of the AST.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "server host=localhost port=80 {\n    route \"/\"\n}".parse().unwrap();
+    /// assert_eq!(
+    ///     doc.sprint_node_tree(),
+    ///     "[0] server (2 entries, 1 child)\n  [1] route (1 entry, 0 children)\n"
+    /// );
+    /// ```
+    pub fn sprint_node_tree(&self) -> String {
+        fn sprint(doc: &KdlDocument, depth: usize, out: &mut String) {
+            for node in doc.nodes() {
+                let entries = node.entries().len();
+                let children = node.children().map_or(0, |c| c.nodes().len());
+                out.push_str(&format!(
+                    "{}[{depth}] {} ({entries} {}, {children} {})\n",
+                    "  ".repeat(depth),
+                    node.name().value(),
+                    if entries == 1 { "entry" } else { "entries" },
+                    if children == 1 { "child" } else { "children" },
+                ));
+                if let Some(children) = node.children() {
+                    sprint(children, depth + 1, out);
+                }
+            }
+        }
+
+        let mut out = String::new();
+        sprint(self, 0, &mut out);
+        out
+    }
+
+    /// Returns a copy of `base` with `overlay` merged on top of it, merging
+    /// nodes that appear in both all the way down.
+    ///
+    /// Each node in `overlay` is matched with the first node with the same
+    /// name at the same level in `base`, and nodes with no match are
+    /// appended. For matched nodes:
+    ///
+    /// * Properties from `overlay` replace the ones with the same name in
+    ///   `base`, and any others are added.
+    /// * If the `overlay` node has any arguments, they replace all of the
+    ///   `base` node's arguments.
+    /// * If both nodes have children, they're merged recursively. Otherwise,
+    ///   whichever node has children keeps them.
+    ///
+    /// Nodes that only exist in `base` are left untouched.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let base: KdlDocument = "server \"main\" port=8080 {\n    tls enabled=#false\n}\n".parse().unwrap();
+    /// let overlay: KdlDocument = "server port=9090 {\n    tls cert=a.pem\n}\nlog".parse().unwrap();
+    /// let merged = KdlDocument::merge_recursive(&base, &overlay);
+    /// assert_eq!(merged.to_string(), "server \"main\" port=9090 {\n    tls enabled=#false cert=a.pem\n}\nlog");
+    /// ```
+    pub fn merge_recursive(base: &KdlDocument, overlay: &KdlDocument) -> KdlDocument {
+        let mut merged = base.clone();
+        merged.name_index = None;
+        for node in overlay.nodes() {
+            match merged
+                .nodes
+                .iter_mut()
+                .find(|n| n.name().value() == node.name().value())
+            {
+                Some(target) => merge_node_recursive(target, node),
+                None => merged.nodes.push(node.clone()),
+            }
+        }
+        merged
+    }
+
+    /// Applies `overrides` on top of this document, like an object spread:
+    /// each node in `overrides` updates the first node with the same name
+    /// among this document's toplevel nodes, and nodes with no match are
+    /// appended, in order.
+    ///
+    /// For matched nodes, properties from `overrides` replace the ones with
+    /// the same name and any others are added, while properties that only
+    /// exist in this document are left as they are. If the `overrides` node
+    /// has any arguments, they replace all of this node's arguments, and if
+    /// it has children, they replace this node's children. Unlike
+    /// [`KdlDocument::merge_recursive`], children aren't merged.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let mut doc: KdlDocument = "server \"main\" host=localhost port=8080\n".parse().unwrap();
+    /// let overrides: KdlDocument = "server port=9090\nlog level=debug".parse().unwrap();
+    /// doc.apply_overrides_from(&overrides);
+    /// assert_eq!(doc.to_string(), "server \"main\" host=localhost port=9090\nlog level=debug");
+    /// ```
+    pub fn apply_overrides_from(&mut self, overrides: &KdlDocument) {
+        self.name_index = None;
+        for node in overrides.nodes() {
+            match self
+                .nodes
+                .iter_mut()
+                .find(|n| n.name().value() == node.name().value())
+            {
+                Some(target) => {
+                    override_entries(target, node);
+                    if let Some(children) = node.children() {
+                        target.set_children(children.clone());
+                    }
+                }
+                None => self.nodes.push(node.clone()),
+            }
+        }
+    }
+
+    /// Checks cross-references between nodes, where one node's argument
+    /// names another node, returning one [`KdlRefError`] per broken
+    /// reference.
+    ///
+    /// Each `(node_name, arg_index, target_node_name)` in `ref_spec` says
+    /// that every node named `node_name`, at any depth, refers through its
+    /// argument at `arg_index` to some other node named `target_node_name`,
+    /// whose own name is its first argument. So `("service", 1, "db")`
+    /// checks that `service web postgres` has a matching `db postgres`
+    /// somewhere in the document. A node without an argument at `arg_index`
+    /// is reported as a broken reference too.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "db main\nservice web main\nservice api replica".parse().unwrap();
+    /// let errors = doc.verify_referential_integrity(&[("service", 1, "db")]);
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].reference, Some("replica".into()));
+    /// ```
+    pub fn verify_referential_integrity(
+        &self,
+        ref_spec: &[(&str, usize, &str)],
+    ) -> Vec<KdlRefError> {
+        // How many nodes named each target name have each first argument.
+        // These are counts rather than sets so a node that's also a target
+        // can't satisfy its own reference.
+        let mut targets: HashMap<&str, HashMap<&KdlValue, usize>> = HashMap::new();
+        for &(_, _, target_node_name) in ref_spec {
+            targets.entry(target_node_name).or_insert_with(|| {
+                let mut counts = HashMap::new();
+                for target in self.descendants() {
+                    if target.name().value() == target_node_name {
+                        if let Some(value) = target.get(0) {
+                            *counts.entry(value).or_insert(0) += 1;
+                        }
+                    }
+                }
+                counts
+            });
+        }
+        let mut errors = Vec::new();
+        for &(node_name, arg_index, target_node_name) in ref_spec {
+            let counts = &targets[target_node_name];
+            for node in self.descendants() {
+                if node.name().value() != node_name {
+                    continue;
+                }
+                let arg = node
+                    .entries
+                    .iter()
+                    .filter(|e| e.name.is_none())
+                    .nth(arg_index);
+                let found = arg.is_some_and(|arg| {
+                    let is_own_target = node_name == target_node_name
+                        && node.get(0).is_some_and(|name| *name == arg.value);
+                    counts
+                        .get(&arg.value)
+                        .is_some_and(|&count| count > usize::from(is_own_target))
+                });
+                if found {
+                    continue;
+                }
+                let span = arg.map_or_else(|| span_or_default(node), span_or_default);
+                errors.push(KdlRefError {
+                    node_name: node_name.into(),
+                    arg_index,
+                    target_node_name: target_node_name.into(),
+                    reference: arg.map(|arg| arg.value.clone()),
+                    span,
+                });
+            }
+        }
+        errors
+    }
+
+    /// Checks that every node named `node_name`, at any depth, only has
+    /// properties whose names are in `allowed`, returning one
+    /// [`KdlUnknownPropError`] per unknown property, in document order.
+    ///
+    /// Each error suggests the name in `allowed` closest to the unknown one,
+    /// by Levenshtein distance, if it's close enough to be a likely typo: at
+    /// most one edit for every three characters of the unknown name, rounding
+    /// up.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "server host=localhost prot=80".parse().unwrap();
+    /// let errors = doc
+    ///     .assert_no_unknown_properties("server", &["host", "port"])
+    ///     .unwrap_err();
+    /// assert_eq!(errors.len(), 1);
+    /// assert_eq!(errors[0].name, "prot");
+    /// assert_eq!(errors[0].suggestion.as_deref(), Some("port"));
+    /// ```
+    pub fn assert_no_unknown_properties(
+        &self,
+        node_name: &str,
+        allowed: &[&str],
+    ) -> Result<(), Vec<KdlUnknownPropError>> {
+        let mut errors = Vec::new();
+        for node in self.descendants() {
+            if node.name().value() != node_name {
+                continue;
+            }
+            for name in node.entries.iter().filter_map(|e| e.name()) {
+                let name = name.value();
+                if allowed.contains(&name) {
+                    continue;
+                }
+                let max_distance = (name.chars().count() + 2) / 3;
+                let suggestion = allowed
+                    .iter()
+                    .map(|candidate| (levenshtein(name, candidate), candidate))
+                    .filter(|(distance, _)| *distance <= max_distance)
+                    .min_by_key(|(distance, _)| *distance)
+                    .map(|(_, candidate)| candidate.to_string());
+                let span = span_or_default(node);
+                errors.push(KdlUnknownPropError {
+                    node_name: node_name.into(),
+                    name: name.into(),
+                    suggestion,
+                    span,
+                });
+            }
+        }
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            Err(errors)
+        }
+    }
+
+    /// Collects every property and argument value in this document, at any
+    /// depth, into a map keyed by their dot-separated paths.
+    ///
+    /// A property's path is the names of the nodes leading to it followed by
+    /// its own name, like `server.tls.port`, and an argument's path is its
+    /// node's path followed by its index in brackets, like `server.[0]`.
+    /// Type annotations and formatting are discarded.
+    ///
+    /// Fails if two values would have the same path, like when there are two
+    /// sibling nodes with the same name and entries, or a node has the same
+    /// property twice.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "server main {\n    tls port=443 enabled=#true\n}".parse().unwrap();
+    /// let props = doc.to_flattened_properties().unwrap();
+    /// assert_eq!(props["server.[0]"], "main".into());
+    /// assert_eq!(props["server.tls.port"], 443.into());
+    /// assert_eq!(props["server.tls.enabled"], true.into());
+    /// ```
+    pub fn to_flattened_properties(&self) -> Result<HashMap<String, KdlValue>, KdlError> {
+        let mut props = HashMap::new();
+        self.to_flattened_properties_impl("", &mut props)
+            .map_err(|(span, path)| {
+                KdlError::single(
+                    &self.to_string(),
+                    span,
+                    format!("Ambiguous path `{path}`"),
+                    Some("Rename one of the nodes or properties so every path is unique."),
+                )
+            })?;
+        Ok(props)
+    }
+
+    fn to_flattened_properties_impl(
+        &self,
+        prefix: &str,
+        props: &mut HashMap<String, KdlValue>,
+    ) -> Result<(), (miette::SourceSpan, String)> {
+        for node in &self.nodes {
+            let node_path = format!("{prefix}{}", node.name().value());
+            let mut arg_idx = 0;
+            for entry in node.entries() {
+                let path = match entry.name() {
+                    Some(name) => format!("{node_path}.{}", name.value()),
+                    None => {
+                        arg_idx += 1;
+                        format!("{node_path}.[{}]", arg_idx - 1)
+                    }
+                };
+                if props.contains_key(&path) {
+                    return Err((span_or_default(entry), path));
+                }
+                props.insert(path, entry.value().clone());
+            }
+            if let Some(children) = node.children() {
+                children.to_flattened_properties_impl(&format!("{node_path}."), props)?;
+            }
+        }
+        Ok(())
+    }
+
+    /// Returns a copy of this document with the same structure, but with
+    /// every value replaced by a type-annotated placeholder, for use as a
+    /// template for documents like this one.
+    ///
+    /// Strings become `(string)""`, integers become `(integer)0`, floats
+    /// become `(float)0.0` and booleans become `(boolean)#false`, replacing
+    /// any type annotations they already had. `#null`s are left as they are.
+    /// Node names, property names, children and comments are all kept, and
+    /// the result is autoformatted.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "server \"main\" port=8080 {\n    tls #true\n}".parse().unwrap();
+    /// assert_eq!(
+    ///     doc.mirror().to_string(),
+    ///     "server (string)\"\" port=(integer)0 {\n    tls (boolean)#false\n}\n"
+    /// );
+    /// ```
+    pub fn mirror(&self) -> KdlDocument {
+        let mut doc = self.clone();
+        doc.mirror_impl();
+        doc.autoformat();
+        doc
+    }
+
+    fn mirror_impl(&mut self) {
+        self.name_index = None;
+        for node in &mut self.nodes {
+            for entry in node.entries_mut() {
+                let (ty, placeholder) = match entry.value() {
+                    KdlValue::String(_) => ("string", KdlValue::String(String::new())),
+                    KdlValue::Integer(_) => ("integer", KdlValue::Integer(0)),
+                    KdlValue::Float(_) => ("float", KdlValue::Float(0.0)),
+                    KdlValue::Bool(_) => ("boolean", KdlValue::Bool(false)),
+                    KdlValue::Null => continue,
+                };
+                entry.set_value(placeholder);
+                entry.set_ty(ty);
+                entry.format = None;
+            }
+            if let Some(children) = node.children_mut() {
+                children.mirror_impl();
+            }
+        }
+    }
+
+    /// Returns how deeply nested this document's nodes are: `0` if it only
+    /// has toplevel nodes (or no nodes at all), `1` if some of them have
+    /// children but none of those do, and so on. Empty children blocks
+    /// (`node {}`) don't count.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "a { b { c } }\nd {}".parse().unwrap();
+    /// assert_eq!(doc.depth(), 2);
+    /// ```
+    pub fn depth(&self) -> usize {
+        self.nodes
+            .iter()
+            .filter_map(|node| node.children())
+            .filter(|children| !children.nodes.is_empty())
+            .map(|children| children.depth() + 1)
+            .max()
+            .unwrap_or(0)
+    }
+
+    /// Collects every node in this document into groups by depth, where the
+    /// group at index `0` has the toplevel nodes, the one at index `1` has
+    /// their children, and so on. Nodes in each group are in document order.
+    ///
+    /// The returned `Vec`'s length is always `self.depth() + 1`, so an empty
+    /// document returns a single, empty group.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "a { b; c { d } }\ne { f }".parse().unwrap();
+    /// let names: Vec<Vec<_>> = doc
+    ///     .collect_nodes_by_depth()
+    ///     .iter()
+    ///     .map(|level| level.iter().map(|n| n.name().value()).collect())
+    ///     .collect();
+    /// assert_eq!(names, vec![vec!["a", "e"], vec!["b", "c", "f"], vec!["d"]]);
+    /// ```
+    pub fn collect_nodes_by_depth(&self) -> Vec<Vec<&KdlNode>> {
+        let mut levels = vec![self.nodes.iter().collect::<Vec<_>>()];
+        loop {
+            let next: Vec<&KdlNode> = levels
+                .last()
+                .unwrap()
+                .iter()
+                .filter_map(|node| node.children())
+                .flat_map(|children| children.nodes.iter())
+                .collect();
+            if next.is_empty() {
+                return levels;
+            }
+            levels.push(next);
+        }
+    }
+
+    /// Returns an iterator over every entry in this document, at any depth,
+    /// along with its path: the names of the nodes leading to it, from the
+    /// toplevel node down to the node the entry belongs to.
+    ///
+    /// Entries are yielded depth-first: each node's entries come in order,
+    /// left to right, before the entries of its children.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "server port=80 { tls cert=a.pem }\nlog debug".parse().unwrap();
+    /// let entries: Vec<_> = doc
+    ///     .iter_entries()
+    ///     .map(|(path, entry)| (path.join("."), entry.to_string()))
+    ///     .collect();
+    /// assert_eq!(
+    ///     entries,
+    ///     vec![
+    ///         ("server".to_string(), " port=80".to_string()),
+    ///         ("server.tls".to_string(), " cert=a.pem".to_string()),
+    ///         ("log".to_string(), " debug".to_string()),
+    ///     ]
+    /// );
+    /// ```
+    pub fn iter_entries(&self) -> impl Iterator<Item = (Vec<String>, &KdlEntry)> {
+        let mut entries = Vec::new();
+        collect_entries(&self.nodes, &mut Vec::new(), &mut entries);
+        entries.into_iter()
+    }
+
+    /// Like [`KdlDocument::iter_entries`], but yields mutable references to
+    /// the entries, in the same order.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let mut doc: KdlDocument = "server host=old.example.com { backup host=old.example.com }".parse().unwrap();
+    /// for (_, entry) in doc.iter_entries_mut() {
+    ///     if entry.value().as_string() == Some("old.example.com") {
+    ///         entry.set_value("new.example.com");
+    ///     }
+    /// }
+    /// assert!(doc
+    ///     .iter_entries()
+    ///     .all(|(_, entry)| entry.value().as_string() == Some("new.example.com")));
+    /// ```
+    pub fn iter_entries_mut(&mut self) -> impl Iterator<Item = (Vec<String>, &mut KdlEntry)> {
+        let mut entries = Vec::new();
+        collect_entries_mut(&mut self.nodes, &mut Vec::new(), &mut entries);
+        entries.into_iter()
+    }
+
+    // TODO(@zkat): These should all be moved into the query module itself,
+    // instead of being methods on the models
+    //
+    // /// Queries this Document's children according to the KQL query language,
+    // /// returning an iterator over all matching nodes.
+    // ///
+    // /// # NOTE
+    // ///
+    // /// Any query selectors that try to select the toplevel `scope()` will
+    // /// fail to match when using this method, since there's no [`KdlNode`] to
+    // /// return in this case.
+    // pub fn query_all(
+    //     &self,
+    //     query: impl IntoKdlQuery,
+    // ) -> Result<KdlQueryIterator<'_>, KdlDiagnostic> {
+    //     let parsed = query.into_query()?;
+    //     Ok(KdlQueryIterator::new(None, Some(self), parsed))
+    // }
+
+    // /// Queries this Document's children according to the KQL query language,
+    // /// returning the first match, if any.
+    // ///
+    // /// # NOTE
+    // ///
+    // /// Any query selectors that try to select the toplevel `scope()` will
+    // /// fail to match when using this method, since there's no [`KdlNode`] to
+    // /// return in this case.
+    // pub fn query(&self, query: impl IntoKdlQuery) -> Result<Option<&KdlNode>, KdlDiagnostic> {
+    //     let mut iter = self.query_all(query)?;
+    //     Ok(iter.next())
+    // }
+
+    // /// Queries this Document's children according to the KQL query language,
+    // /// picking the first match, and calling `.get(key)` on it, if the query
+    // /// succeeded.
+    // ///
+    // /// # NOTE
+    // ///
+    // /// Any query selectors that try to select the toplevel `scope()` will
+    // /// fail to match when using this method, since there's no [`KdlNode`] to
+    // /// return in this case.
+    // pub fn query_get(
+    //     &self,
+    //     query: impl IntoKdlQuery,
+    //     key: impl Into<NodeKey>,
+    // ) -> Result<Option<&KdlValue>, KdlDiagnostic> {
+    //     Ok(self.query(query)?.and_then(|node| node.get(key)))
+    // }
+
+    // /// Queries this Document's children according to the KQL query language,
+    // /// returning an iterator over all matching nodes, returning the requested
+    // /// field from each of those nodes and filtering out nodes that don't have
+    // /// it.
+    // ///
+    // /// # NOTE
+    // ///
+    // /// Any query selectors that try to select the toplevel `scope()` will
+    // /// fail to match when using this method, since there's no [`KdlNode`] to
+    // /// return in this case.
+    // pub fn query_get_all(
+    //     &self,
+    //     query: impl IntoKdlQuery,
+    //     key: impl Into<NodeKey>,
+    // ) -> Result<impl Iterator<Item = &KdlValue>, KdlDiagnostic> {
+    //     let key: NodeKey = key.into();
+    //     Ok(self
+    //         .query_all(query)?
+    //         .filter_map(move |node| node.get(key.clone())))
+    // }
+
+    /// Parses a string into a document.
+    ///
+    /// If the `v1-fallback` feature is enabled, this method will first try to
+    /// parse the string as a KDL v2 document, and, if that fails, it will try
+    /// to parse again as a KDL v1 document. If both fail, a heuristic will be
+    /// applied to try and detect the "intended" KDL version, and that version's
+    /// error(s) will be returned.
+    pub fn parse(s: &str) -> Result<Self, KdlError> {
+        #[cfg(not(feature = "v1-fallback"))]
+        {
+            Self::parse_v2(s)
+        }
+        #[cfg(feature = "v1-fallback")]
+        {
+            let v2_res = KdlDocument::parse_v2(s);
+            if v2_res.is_err() {
+                let v1_res = KdlDocument::parse_v1(s);
+                if v1_res.is_ok() || detect_v1(s) {
+                    v1_res
+                } else {
+                    // TODO(@zkat): maybe we can add something to the error
+                    // message to specify that it's "uncertain"?
+                    // YOLO.
+                    v2_res
+                }
+            } else {
+                v2_res
+            }
+        }
+    }
+
+    /// Parses a KDL v2 string into a document.
+    pub fn parse_v2(s: &str) -> Result<Self, KdlError> {
+        crate::v2_parser::try_parse(crate::v2_parser::document, s)
+    }
+
+    /// Parses a bare list of nodes, as would appear inside a node's `{ ... }`
+    /// children block, into a document.
+    ///
+    /// This is handy when embedding KDL snippets in other systems, like
+    /// templates. Unlike [`KdlDocument::parse`], this always uses the KDL v2
+    /// grammar, and does not accept a leading byte order mark. All spans are
+    /// relative to the start of `s`.
+    pub fn parse_fragment(s: &str) -> Result<Self, KdlError> {
+        crate::v2_parser::try_parse(crate::v2_parser::fragment, s)
+    }
+
+    /// Parses a KDL v1 string into a document.
+    #[cfg(feature = "v1")]
+    pub fn parse_v1(s: &str) -> Result<Self, KdlError> {
+        let ret: Result<kdlv1::KdlDocument, kdlv1::KdlError> = s.parse();
+        ret.map(|x| x.into()).map_err(|e| e.into())
+    }
+
+    /// Takes a KDL v1 document string and returns the same document, but
+    /// autoformatted into valid KDL v2 syntax.
+    #[cfg(feature = "v1")]
+    pub fn v1_to_v2(s: &str) -> Result<String, KdlError> {
+        let mut doc = KdlDocument::parse_v1(s)?;
+        doc.ensure_v2();
+        Ok(doc.to_string())
+    }
+
+    /// Takes a KDL v2 document string and returns the same document, but
+    /// autoformatted into valid KDL v1 syntax.
+    #[cfg(feature = "v1")]
+    pub fn v2_to_v1(s: &str) -> Result<String, KdlError> {
+        let mut doc = KdlDocument::parse_v2(s)?;
+        doc.ensure_v1();
+        Ok(doc.to_string())
+    }
+
+    /// Makes sure this document is in v2 format.
+    pub fn ensure_v2(&mut self) {
+        // No need to touch KdlDocumentFormat, probably. In the longer term,
+        // we'll want to make sure to parse out whitespace and comments and make
+        // sure they're actually compliant, but this is good enough for now.
+        for node in self.nodes_mut().iter_mut() {
+            node.ensure_v2();
+        }
+    }
+
+    /// Makes sure this document is in v1 format.
+    #[cfg(feature = "v1")]
+    pub fn ensure_v1(&mut self) {
+        // No need to touch KdlDocumentFormat, probably. In the longer term,
+        // we'll want to make sure to parse out whitespace and comments and make
+        // sure they're actually compliant, but this is good enough for now.
+
+        // the last node in v1 docs/children has to have a semicolon.
+        let mut iter = self.nodes_mut().iter_mut().rev();
+        let last = iter.next();
+        let penult = iter.next();
+        if let Some(last) = last {
+            if let Some(fmt) = last.format_mut() {
+                if !fmt.trailing.contains(';')
+                    && fmt
+                        .trailing
+                        .chars()
+                        .any(|c| crate::v2_parser::NEWLINES.iter().any(|nl| nl.contains(c)))
+                {
+                    fmt.terminator = ";".into();
+                }
+            } else {
+                let maybe_indent = {
+                    if let Some(penult) = penult {
+                        if let Some(fmt) = penult.format() {
+                            fmt.leading.clone()
+                        } else {
+                            "".into()
+                        }
+                    } else {
+                        "".into()
+                    }
+                };
+                last.format = Some(KdlNodeFormat {
+                    leading: maybe_indent,
+                    terminator: "\n".into(),
+                    ..Default::default()
+                })
+            }
+        }
+        for node in self.nodes_mut().iter_mut() {
+            node.ensure_v1();
+        }
+    }
+}
+
+#[cfg(feature = "v1")]
+impl From<kdlv1::KdlDocument> for KdlDocument {
+    fn from(value: kdlv1::KdlDocument) -> Self {
+        Self {
+            nodes: value.nodes().iter().map(|x| x.clone().into()).collect(),
+            format: Some(KdlDocumentFormat {
+                leading: value.leading().unwrap_or("").into(),
+                trailing: value.trailing().unwrap_or("").into(),
+            }),
+            #[cfg(feature = "span")]
+            span: SourceSpan::new(value.span().offset().into(), value.span().len()),
+            name_index: None,
+        }
+    }
+}
+
+/// Folds `other`'s entries and children into `target`. Arguments are
+/// appended, properties replace any existing property with the same name.
+fn merge_node_into(target: &mut KdlNode, other: KdlNode) {
+    for entry in other.entries {
+        let existing = entry.name().and_then(|name| {
+            target
+                .entries
+                .iter_mut()
+                .rev()
+                .find(|e| e.name().map(|n| n.value()) == Some(name.value()))
+        });
+        if let Some(existing) = existing {
+            *existing = entry;
+        } else {
+            target.entries.push(entry);
+        }
+    }
+    if let Some(children) = other.children {
+        target.ensure_children().nodes.extend(children.nodes);
+    }
+}
+
+fn merge_node_recursive(target: &mut KdlNode, overlay: &KdlNode) {
+    override_entries(target, overlay);
+    match (target.children_mut(), overlay.children()) {
+        (Some(children), Some(overlay_children)) => {
+            *children = KdlDocument::merge_recursive(children, overlay_children);
+        }
+        (None, Some(overlay_children)) => target.set_children(overlay_children.clone()),
+        (_, None) => {}
+    }
+}
+
+/// Replaces `target`'s arguments with `overlay`'s, if it has any, and its
+/// properties with the ones of the same name in `overlay`, adding the rest.
+fn override_entries(target: &mut KdlNode, overlay: &KdlNode) {
+    if overlay.entries.iter().any(|e| e.name.is_none()) {
+        target.entries.retain(|e| e.name.is_some());
+        let args = overlay.entries.iter().filter(|e| e.name.is_none()).cloned();
+        target.entries.splice(0..0, args);
+    }
+    for entry in overlay.entries.iter().filter(|e| e.name.is_some()) {
+        let name = entry.name().map(|n| n.value());
+        match target
+            .entries
+            .iter_mut()
+            .rev()
+            .find(|e| e.name().map(|n| n.value()) == name)
+        {
+            Some(existing) => *existing = entry.clone(),
+            None => target.entries.push(entry.clone()),
+        }
+    }
+}
+
+fn apply_to_node_values(node: &mut KdlNode, f: &impl Fn(&mut KdlValue), recursive: bool) {
+    for entry in node.entries.iter_mut() {
+        let before = entry.value.clone();
+        f(&mut entry.value);
+        if entry.value != before {
+            let repr = entry.value.to_string();
+            if let Some(fmt) = entry.format_mut() {
+                fmt.value_repr = repr;
+            }
+        }
+    }
+    if recursive {
+        for child in node.iter_children_mut() {
+            apply_to_node_values(child, f, true);
+        }
+    }
+}
+fn has_children(node: &KdlNode) -> bool {
+    node.children().is_some_and(|c| !c.nodes.is_empty())
+}
+
+/// Depth-first iterator over all the nodes in a [`KdlDocument`], returned by
+/// [`KdlDocument::descendants`].
+pub(crate) struct Descendants<'a> {
+    stack: Vec<std::slice::Iter<'a, KdlNode>>,
+}
+
+impl<'a> Iterator for Descendants<'a> {
+    type Item = &'a KdlNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        loop {
+            let iter = self.stack.last_mut()?;
+            if let Some(node) = iter.next() {
+                if let Some(children) = node.children() {
+                    self.stack.push(children.nodes.iter());
+                }
+                return Some(node);
+            }
+            self.stack.pop();
+        }
+    }
+}
+
+/// The number of single-character insertions, deletions and substitutions
+/// needed to turn `a` into `b`.
+fn levenshtein(a: &str, b: &str) -> usize {
+    let b: Vec<char> = b.chars().collect();
+    let mut row: Vec<usize> = (0..=b.len()).collect();
+    for (i, ca) in a.chars().enumerate() {
+        let mut diagonal = row[0];
+        row[0] = i + 1;
+        for (j, &cb) in b.iter().enumerate() {
+            let above = row[j + 1];
+            row[j + 1] = if ca == cb {
+                diagonal
+            } else {
+                1 + diagonal.min(above).min(row[j])
+            };
+            diagonal = above;
+        }
+    }
+    row[b.len()]
+}
+
+fn collect_entries<'a>(
+    nodes: &'a [KdlNode],
+    path: &mut Vec<String>,
+    entries: &mut Vec<(Vec<String>, &'a KdlEntry)>,
+) {
+    for node in nodes {
+        path.push(node.name.value().into());
+        entries.extend(node.entries.iter().map(|entry| (path.clone(), entry)));
+        if let Some(children) = &node.children {
+            collect_entries(&children.nodes, path, entries);
+        }
+        path.pop();
+    }
+}
+
+fn collect_entries_mut<'a>(
+    nodes: &'a mut [KdlNode],
+    path: &mut Vec<String>,
+    entries: &mut Vec<(Vec<String>, &'a mut KdlEntry)>,
+) {
+    for node in nodes {
+        path.push(node.name.value().into());
+        entries.extend(node.entries.iter_mut().map(|entry| (path.clone(), entry)));
+        if let Some(children) = &mut node.children {
+            collect_entries_mut(&mut children.nodes, path, entries);
+        }
+        path.pop();
+    }
+}
+
+/// Counts the type annotations on `node` and its entries. Ones without a type
+/// annotation are counted under `untyped`, or skipped if it's `None`.
+fn count_type_annotations<'a>(
+    node: &'a KdlNode,
+    counts: &mut HashMap<&'a str, usize>,
+    untyped: Option<&'a str>,
+) {
+    let tys = std::iter::once(node.ty()).chain(node.entries.iter().map(|e| e.ty()));
+    for ty in tys {
+        if let Some(ty) = ty.map(|ty| ty.value()).or(untyped) {
+            *counts.entry(ty).or_default() += 1;
+        }
+    }
+}
+
+fn collect_property_values_mut<'a>(
+    doc: &'a mut KdlDocument,
+    prop_name: &str,
+    values: &mut Vec<&'a mut KdlValue>,
+) {
+    for node in &mut doc.nodes {
+        for entry in &mut node.entries {
+            if entry.name().map(|n| n.value()) == Some(prop_name) {
+                values.push(entry.value_mut());
+            }
+        }
+        if let Some(children) = &mut node.children {
+            collect_property_values_mut(children, prop_name, values);
+        }
+    }
+}
+
+/// Applies heuristics to get an idea of whether the string might be intended to
+/// be v2.
+#[allow(unused)]
+pub(crate) fn detect_v2(input: &str) -> bool {
+    for line in input.lines() {
+        if line.contains("kdl-version 2")
             || line.contains("#true")
             || line.contains("#false")
             || line.contains("#null")
@@ -495,597 +2033,1809 @@ pub(crate) fn detect_v2(input: &str) -> bool {
                         .unwrap_or_default()
                 }))
         {
-            return true;
+            return true;
+        }
+    }
+    false
+}
+
+/// Applies heuristics to get an idea of whether the string might be intended to
+/// be v2.
+#[allow(unused)]
+pub(crate) fn detect_v1(input: &str) -> bool {
+    input
+        .lines()
+        .next()
+        .map(|l| l.contains("kdl-version 1"))
+        .unwrap_or(false)
+        || input.contains(" true")
+        || input.contains(" false")
+        || input.contains(" null")
+        || input.contains("r#\"")
+        || input.contains(" \"\n")
+        || input.contains(" \"\r\n")
+}
+
+impl std::str::FromStr for KdlDocument {
+    type Err = KdlError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Self::parse(s)
+    }
+}
+
+impl Display for KdlDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.stringify(f, 0)
+    }
+}
+
+impl KdlDocument {
+    pub(crate) fn stringify(
+        &self,
+        f: &mut std::fmt::Formatter<'_>,
+        indent: usize,
+    ) -> std::fmt::Result {
+        if let Some(KdlDocumentFormat { leading, .. }) = self.format() {
+            write!(f, "{leading}")?;
+        }
+        for node in &self.nodes {
+            node.stringify(f, indent)?;
+        }
+        if let Some(KdlDocumentFormat { trailing, .. }) = self.format() {
+            write!(f, "{trailing}")?;
+        }
+        Ok(())
+    }
+}
+
+/// Consumes the document, yielding its toplevel nodes by value, in order.
+///
+/// The nodes are moved out of the document without being cloned, and the
+/// iterator is both a [`DoubleEndedIterator`] and an [`ExactSizeIterator`].
+impl IntoIterator for KdlDocument {
+    type Item = KdlNode;
+    type IntoIter = std::vec::IntoIter<Self::Item>;
+
+    fn into_iter(self) -> Self::IntoIter {
+        self.nodes.into_iter()
+    }
+}
+
+/// Formatting details for [`KdlDocument`]s.
+#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
+pub struct KdlDocumentFormat {
+    /// Whitespace and comments preceding the document's first node.
+    pub leading: String,
+    /// Whitespace and comments following the document's last node.
+    pub trailing: String,
+}
+
+#[cfg(test)]
+mod test {
+    #[cfg(feature = "span")]
+    use crate::KdlIdentifier;
+    use crate::{KdlEntry, KdlValue};
+
+    use super::*;
+
+    #[test]
+    fn canonical_clear_fmt() -> miette::Result<()> {
+        let left_src = r#"
+// There is a node here
+first_node /*with cool comments, too */ param=1.03e2 /-"commented" "argument" {
+    // With nested nodes too
+    nested 1 2 3
+    nested_2 "hi" "world" // this one is cool
+}
+second_node param=153 { nested one=1 two=2; }"#;
+        let right_src = r#"
+first_node param=103.0       "argument" {
+        // Different indentation, because
+        // Why not
+        nested 1 2 3
+        nested_2 "hi" /* actually, "hello" */ "world"
+}
+// There is a node here
+second_node /* This time, the comment is here */ param=153 {
+        nested one=1 two=2
+}"#;
+        let mut left_doc: KdlDocument = left_src.parse()?;
+        let mut right_doc: KdlDocument = right_src.parse()?;
+        assert_ne!(left_doc, right_doc);
+        left_doc.clear_format_recursive();
+        right_doc.clear_format_recursive();
+        assert_eq!(left_doc, right_doc);
+        Ok(())
+    }
+
+    #[test]
+    fn basic_parsing() -> miette::Result<()> {
+        let src = r#"
+            // Hello, world!
+            node 1
+            node two
+            node item="three";
+            node {
+                nested 1 2 3
+                nested_2 hi "world"
+            }
+            (type)node ("type")what?
+            +false #true
+            null_id null_prop=#null
+                    foo indented
+            // normal comment?
+            /- comment
+            /* block comment */
+            inline /*comment*/ here
+            another /-comment there
+
+
+            after some whitespace
+            trailing /* multiline */
+            trailing // single line
+            "#;
+        let _doc: KdlDocument = src.parse()?;
+        Ok(())
+    }
+
+    #[test]
+    fn parsing() -> miette::Result<()> {
+        let src = "
+// This is the first node
+foo 1 2 three #null #true bar=\"baz\" {
+    - 1
+    - 2
+    - three
+    (mytype)something (\"name\")else\r
+}
+
+null_id null_prop=#null
+true_id true_prop=#null
++false #true
+
+         bar \"indented\" // trailing whitespace after this\t
+/*
+Some random comment
+ */
+
+a; b; c;
+/-commented \"node\"
+
+another /*foo*/ \"node\" /-1 /*bar*/ #null;
+final;";
+        let mut doc: KdlDocument = src.parse()?;
+
+        assert_eq!(doc.get_arg("foo"), Some(&1.into()));
+        assert_eq!(
+            doc.iter_dash_args("foo").collect::<Vec<&KdlValue>>(),
+            vec![&1.into(), &2.into(), &"three".into()]
+        );
+        assert_eq!(
+            doc.format().map(|f| &f.leading[..]),
+            Some("\n// This is the first node\n")
+        );
+
+        let foo = doc.get("foo").expect("expected a foo node");
+        assert_eq!(foo.format().map(|f| &f.terminator[..]), Some("\n"));
+        assert_eq!(&foo[2], &"three".into());
+        assert_eq!(&foo["bar"], &"baz".into());
+        assert_eq!(
+            foo.children().unwrap().get_arg("something"),
+            Some(&"else".into())
+        );
+        assert_eq!(doc.get_arg("another"), Some(&"node".into()));
+
+        let null = doc.get("null_id").expect("expected a null_id node");
+        assert_eq!(&null["null_prop"], &KdlValue::Null);
+
+        let tru = doc.get("true_id").expect("expected a true_id node");
+        assert_eq!(&tru["true_prop"], &KdlValue::Null);
+
+        let plusfalse = doc.get("+false").expect("expected a +false node");
+        assert_eq!(&plusfalse[0], &KdlValue::Bool(true));
+
+        let bar = doc.get("bar").expect("expected a bar node");
+        assert_eq!(
+            format!("{}", bar),
+            "\n         bar \"indented\" // trailing whitespace after this\t\n"
+        );
+
+        let a = doc.get("a").expect("expected a node");
+        assert_eq!(
+            format!("{}", a),
+            "/*\nSome random comment\n */\n\na;".to_string()
+        );
+
+        let b = doc.get("b").expect("expected a node");
+        assert_eq!(format!("{}", b), " b;".to_string());
+
+        // Round-tripping works.
+        assert_eq!(format!("{}", doc), src);
+
+        // Programmatic manipulation works.
+        let mut node: KdlNode = "new\n".parse()?;
+        // Manual entry parsing preserves formatting/reprs. Note that
+        // if you're making KdlEntries this way, you need to inject
+        // your own whitespace (or format the node)
+        node.push(" \"blah\"=0xDEADbeef".parse::<KdlEntry>()?);
+        doc.nodes_mut().push(node);
+
+        assert_eq!(
+            format!("{}", doc),
+            format!("{}new \"blah\"=0xDEADbeef\n", src)
+        );
+
+        Ok(())
+    }
+
+    #[test]
+    fn construction() {
+        let mut doc = KdlDocument::new();
+        doc.nodes_mut().push(KdlNode::new("foo"));
+
+        let mut bar = KdlNode::new("bar");
+        bar.insert("prop", "value");
+        bar.push(1);
+        bar.push(2);
+        bar.push(false);
+        bar.push(KdlValue::Null);
+
+        let subdoc = bar.ensure_children();
+        subdoc.nodes_mut().push(KdlNode::new("barchild"));
+        doc.nodes_mut().push(bar);
+        doc.nodes_mut().push(KdlNode::new("baz"));
+
+        doc.autoformat();
+
+        assert_eq!(
+            r#"foo
+bar prop=value 1 2 #false #null {
+    barchild
+}
+baz
+"#,
+            format!("{}", doc)
+        );
+    }
+
+    #[ignore = "There's still issues around formatting comments and esclines."]
+    #[test]
+    fn autoformat() -> miette::Result<()> {
+        let mut doc: KdlDocument = r##"
+
+        /* x */ foo    1 "bar"=0xDEADbeef {
+    child1     1  ;
+
+ // child 2 comment
+
+        child2 2 /-3 // comment
+
+               child3    "
+
+   string\t
+   " \
+{
+       /*
+
+
+       multiline*/
+                                    inner1    \
+                    #"value"# \
+                    ;
+
+        inner2      \ //comment
+        {
+            inner3
         }
     }
-    false
+               }
+
+        // trailing comment here
+
+        "##
+        .parse()?;
+
+        KdlDocument::autoformat(&mut doc);
+
+        assert_eq!(
+            doc.to_string(),
+            r#"/* x */
+foo 1 bar=0xdeadbeef {
+    child1 1
+    // child 2 comment
+    child2 2 /-3 // comment
+    child3 "\nstring\t" {
+        /*
+
+
+       multiline*/
+        inner1 value
+        inner2 {
+            inner3
+        }
+    }
+}
+// trailing comment here"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn simple_autoformat() -> miette::Result<()> {
+        let mut doc: KdlDocument = "a { b { c { }; }; }".parse().unwrap();
+        KdlDocument::autoformat(&mut doc);
+        assert_eq!(
+            doc.to_string(),
+            r#"a {
+    b {
+        c {
+
+        }
+    }
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn simple_autoformat_two_spaces() -> miette::Result<()> {
+        let mut doc: KdlDocument = "a { b { c { }; }; }".parse().unwrap();
+        KdlDocument::autoformat_config(
+            &mut doc,
+            &FormatConfig {
+                indent: "  ",
+                ..Default::default()
+            },
+        );
+        assert_eq!(
+            doc.to_string(),
+            r#"a {
+  b {
+    c {
+
+    }
+  }
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn simple_autoformat_single_tabs() -> miette::Result<()> {
+        let mut doc: KdlDocument = "a { b { c { }; }; }".parse().unwrap();
+        KdlDocument::autoformat_config(
+            &mut doc,
+            &FormatConfig {
+                indent: "\t",
+                ..Default::default()
+            },
+        );
+        assert_eq!(doc.to_string(), "a {\n\tb {\n\t\tc {\n\n\t\t}\n\t}\n}\n");
+        Ok(())
+    }
+
+    #[test]
+    fn simple_autoformat_no_comments() -> miette::Result<()> {
+        let mut doc: KdlDocument =
+            "// a comment\na {\n// another comment\n b { c { // another comment\n }; }; }"
+                .parse()
+                .unwrap();
+        KdlDocument::autoformat_no_comments(&mut doc);
+        assert_eq!(
+            doc.to_string(),
+            r#"a {
+    b {
+        c {
+
+        }
+    }
+}
+"#
+        );
+        Ok(())
+    }
+
+    #[cfg(feature = "span")]
+    fn check_spans_for_doc(doc: &KdlDocument, source: &impl miette::SourceCode) {
+        for node in doc.nodes() {
+            check_spans_for_node(node, source);
+        }
+    }
+
+    #[cfg(feature = "span")]
+    fn check_spans_for_node(node: &KdlNode, source: &impl miette::SourceCode) {
+        use crate::KdlEntryFormat;
+
+        check_span_for_ident(node.name(), source);
+        if let Some(ty) = node.ty() {
+            check_span_for_ident(ty, source);
+        }
+
+        for entry in node.entries() {
+            if let Some(name) = entry.name() {
+                check_span_for_ident(name, source);
+            }
+            if let Some(ty) = entry.ty() {
+                check_span_for_ident(ty, source);
+            }
+            if let Some(KdlEntryFormat { value_repr, .. }) = entry.format() {
+                if entry.name().is_none() && entry.ty().is_none() {
+                    check_span(value_repr, entry.span(), source);
+                }
+            }
+        }
+        if let Some(children) = node.children() {
+            check_spans_for_doc(children, source);
+        }
+    }
+
+    #[cfg(feature = "span")]
+    #[track_caller]
+    fn check_span_for_ident(ident: &KdlIdentifier, source: &impl miette::SourceCode) {
+        if let Some(repr) = ident.repr() {
+            check_span(repr, ident.span(), source);
+        } else {
+            check_span(ident.value(), ident.span(), source);
+        }
+    }
+
+    #[cfg(feature = "span")]
+    #[track_caller]
+    fn check_span(expected: &str, span: SourceSpan, source: &impl miette::SourceCode) {
+        let span = source.read_span(&span, 0, 0).unwrap();
+        let span = std::str::from_utf8(span.data()).unwrap();
+        assert_eq!(span, expected);
+    }
+
+    #[cfg(feature = "span")]
+    #[test]
+    fn span_test() -> miette::Result<()> {
+        let input = r####"
+this {
+    is (a)"cool" document="to" read=(int)5 10.1 (u32)0x45
+    and x="" {
+        "it" /*shh*/ "has"="💯" ##"the"##
+        Best🎊est
+        "syntax ever"
+    }
+    "yknow?" 0x10
 }
+// that's
+nice
+inline { time; to; live "our" "dreams"; "y;all" }
+
+"####;
+
+        let doc: KdlDocument = input.parse()?;
+
+        // First check that all the identity-spans are correct
+        check_spans_for_doc(&doc, &input);
+
+        // Now check some more interesting concrete spans
+
+        // The whole document should be everything from the first node until the
+        // last before_terminator whitespace.
+        check_span(&input[1..(input.len() - 2)], doc.span(), &input);
+
+        // This one-liner node should be the whole line without leading whitespace
+        let is_node = doc
+            .get("this")
+            .unwrap()
+            .children()
+            .unwrap()
+            .get("is")
+            .unwrap();
+        check_span(
+            r##"is (a)"cool" document="to" read=(int)5 10.1 (u32)0x45"##,
+            is_node.span(),
+            &input,
+        );
+
+        // Some simple with/without type hints
+        check_span(r#"(a)"cool""#, is_node.entry(0).unwrap().span(), &input);
+        check_span(
+            r#"read=(int)5"#,
+            is_node.entry("read").unwrap().span(),
+            &input,
+        );
+        check_span(r#"10.1"#, is_node.entry(1).unwrap().span(), &input);
+        check_span(r#"(u32)0x45"#, is_node.entry(2).unwrap().span(), &input);
+
+        // Now let's look at some messed up parts of that "and" node
+        let and_node = doc
+            .get("this")
+            .unwrap()
+            .children()
+            .unwrap()
+            .get("and")
+            .unwrap();
 
-/// Applies heuristics to get an idea of whether the string might be intended to
-/// be v2.
-#[allow(unused)]
-pub(crate) fn detect_v1(input: &str) -> bool {
-    input
-        .lines()
-        .next()
-        .map(|l| l.contains("kdl-version 1"))
-        .unwrap_or(false)
-        || input.contains(" true")
-        || input.contains(" false")
-        || input.contains(" null")
-        || input.contains("r#\"")
-        || input.contains(" \"\n")
-        || input.contains(" \"\r\n")
-}
+        // The node is what you expect, the whole line and its two braces
+        check_span(
+            r####"and x="" {
+        "it" /*shh*/ "has"="💯" ##"the"##
+        Best🎊est
+        "syntax ever"
+    }"####,
+            and_node.span(),
+            &input,
+        );
 
-impl std::str::FromStr for KdlDocument {
-    type Err = KdlError;
+        // The child document is a little weird, it's the contents *inside* the braces
+        // without the surrounding whitespace/comments. Just the actual contents.
+        check_span(
+            r####""it" /*shh*/ "has"="💯" ##"the"##
+        Best🎊est
+        "syntax ever""####,
+            and_node.children().unwrap().span(),
+            &input,
+        );
 
-    fn from_str(s: &str) -> Result<Self, Self::Err> {
-        Self::parse(s)
-    }
-}
+        // Oh hey don't forget to check that "x" entry
+        check_span(r#"x="""#, and_node.entry("x").unwrap().span(), &input);
 
-impl Display for KdlDocument {
-    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
-        self.stringify(f, 0)
+        // Now the "it" node, more straightforward
+        let it_node = and_node.children().unwrap().get("it").unwrap();
+        check_span(
+            r####""it" /*shh*/ "has"="💯" ##"the"##"####,
+            it_node.span(),
+            &input,
+        );
+        check_span(
+            r#""has"="💯""#,
+            it_node.entry("has").unwrap().span(),
+            &input,
+        );
+        check_span(
+            r####"##"the"##"####,
+            it_node.entry(0).unwrap().span(),
+            &input,
+        );
+
+        // Make sure inline nodes work ok
+        let inline_node = doc.get("inline").unwrap();
+        check_span(
+            r#"inline { time; to; live "our" "dreams"; "y;all" }"#,
+            inline_node.span(),
+            &input,
+        );
+
+        let inline_children = inline_node.children().unwrap();
+        check_span(
+            r#"time; to; live "our" "dreams"; "y;all" "#,
+            inline_children.span(),
+            &input,
+        );
+
+        let inline_nodes = inline_children.nodes();
+        check_span("time", inline_nodes[0].span(), &input);
+        check_span("to", inline_nodes[1].span(), &input);
+        check_span(r#"live "our" "dreams""#, inline_nodes[2].span(), &input);
+        check_span(r#""y;all" "#, inline_nodes[3].span(), &input);
+
+        Ok(())
     }
-}
 
-impl KdlDocument {
-    pub(crate) fn stringify(
-        &self,
-        f: &mut std::fmt::Formatter<'_>,
-        indent: usize,
-    ) -> std::fmt::Result {
-        if let Some(KdlDocumentFormat { leading, .. }) = self.format() {
-            write!(f, "{leading}")?;
-        }
-        for node in &self.nodes {
-            node.stringify(f, indent)?;
-        }
-        if let Some(KdlDocumentFormat { trailing, .. }) = self.format() {
-            write!(f, "{trailing}")?;
-        }
+    #[test]
+    fn parse_examples() -> miette::Result<()> {
+        include_str!("../examples/kdl-schema.kdl").parse::<KdlDocument>()?;
+        include_str!("../examples/Cargo.kdl").parse::<KdlDocument>()?;
+        include_str!("../examples/ci.kdl").parse::<KdlDocument>()?;
+        include_str!("../examples/nuget.kdl").parse::<KdlDocument>()?;
+        include_str!("../examples/website.kdl").parse::<KdlDocument>()?;
+        include_str!("../examples/zellij.kdl").parse::<KdlDocument>()?;
+        include_str!("../examples/zellij-unquoted-bindings.kdl").parse::<KdlDocument>()?;
         Ok(())
     }
+
+    #[test]
+    fn from_structured_pairs() {
+        let mut flat = KdlDocument::from_structured_pairs(
+            [
+                ("name", KdlValue::from("kdl")),
+                ("jobs", KdlValue::from(4)),
+                ("verbose", KdlValue::from(true)),
+            ],
+            ".",
+        );
+        flat.autoformat();
+        assert_eq!(flat.to_string(), "name kdl\njobs 4\nverbose #true\n");
+
+        let mut nested = KdlDocument::from_structured_pairs(
+            [
+                ("server__host", KdlValue::from("localhost")),
+                ("server__tls__port", KdlValue::from(443)),
+                ("server__tls__enabled", KdlValue::from(true)),
+                ("debug", KdlValue::from(false)),
+            ],
+            "__",
+        );
+        assert_eq!(nested.nodes().len(), 2);
+        let server = nested.get("server").expect("server node");
+        let tls = server.children().and_then(|c| c.get("tls"));
+        assert_eq!(
+            tls.and_then(|n| n.children()).map(|c| c.nodes().len()),
+            Some(2)
+        );
+        nested.autoformat();
+        assert_eq!(
+            nested.to_string(),
+            r#"server {
+    host localhost
+    tls {
+        port 443
+        enabled #true
+    }
 }
+debug #false
+"#
+        );
 
-impl IntoIterator for KdlDocument {
-    type Item = KdlNode;
-    type IntoIter = std::vec::IntoIter<Self::Item>;
+        let repeated = KdlDocument::from_structured_pairs(
+            vec![
+                ("include".to_string(), "a.kdl"),
+                ("include".to_string(), "b.kdl"),
+                ("other".to_string(), "c.kdl"),
+            ],
+            ".",
+        );
+        assert_eq!(repeated.nodes().len(), 2);
+        assert_eq!(
+            repeated.iter_args("include").collect::<Vec<_>>(),
+            vec![&"a.kdl".into(), &"b.kdl".into()]
+        );
 
-    fn into_iter(self) -> Self::IntoIter {
-        self.nodes.into_iter()
+        let unnested = KdlDocument::from_structured_pairs([("a.b", KdlValue::from(1))], "");
+        assert_eq!(unnested.get_arg("a.b"), Some(&1.into()));
     }
-}
 
-/// Formatting details for [`KdlDocument`]s.
-#[derive(Debug, Clone, Default, Hash, Eq, PartialEq)]
-pub struct KdlDocumentFormat {
-    /// Whitespace and comments preceding the document's first node.
-    pub leading: String,
-    /// Whitespace and comments following the document's last node.
-    pub trailing: String,
+    #[test]
+    fn node_path_to() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+top 1
+server {
+    tls {
+        cert "foo.pem"
+    }
 }
+"#
+        .parse()?;
 
-#[cfg(test)]
-mod test {
-    #[cfg(feature = "span")]
-    use crate::KdlIdentifier;
-    use crate::{KdlEntry, KdlValue};
+        let top = doc.get("top").unwrap();
+        let path = doc.node_path_to(top).expect("top should be found");
+        assert_eq!(path.len(), 1);
+        assert!(std::ptr::eq(path[0], top));
 
-    use super::*;
+        let cert = doc
+            .get("server")
+            .and_then(|n| n.children())
+            .and_then(|c| c.get("tls"))
+            .and_then(|n| n.children())
+            .and_then(|c| c.get("cert"))
+            .unwrap();
+        let path = doc.node_path_to(cert).expect("cert should be found");
+        assert_eq!(
+            path.iter()
+                .map(|n| n.name().value())
+                .collect::<Vec<_>>()
+                .join(" > "),
+            "server > tls > cert"
+        );
+
+        // Equal by value, but not the same node.
+        let lookalike = top.clone();
+        assert_eq!(doc.node_path_to(&lookalike), None);
+        assert_eq!(doc.node_path_to(&KdlNode::new("nope")), None);
+        Ok(())
+    }
 
     #[test]
-    fn canonical_clear_fmt() -> miette::Result<()> {
-        let left_src = r#"
-// There is a node here
-first_node /*with cool comments, too */ param=1.03e2 /-"commented" "argument" {
-    // With nested nodes too
-    nested 1 2 3
-    nested_2 "hi" "world" // this one is cool
+    fn deduplicate_children_recursive() -> miette::Result<()> {
+        let src = r#"
+a 1 x=1
+b
+a 2 x=2 y=3 {
+    c 1
+    c 2
 }
-second_node param=153 { nested one=1 two=2; }"#;
-        let right_src = r#"
-first_node param=103.0       "argument" {
-        // Different indentation, because
-        // Why not
-        nested 1 2 3
-        nested_2 "hi" /* actually, "hello" */ "world"
+a 3 {
+    c 3
 }
-// There is a node here
-second_node /* This time, the comment is here */ param=153 {
-        nested one=1 two=2
-}"#;
-        let mut left_doc: KdlDocument = left_src.parse()?;
-        let mut right_doc: KdlDocument = right_src.parse()?;
-        assert_ne!(left_doc, right_doc);
-        left_doc.clear_format_recursive();
-        right_doc.clear_format_recursive();
-        assert_eq!(left_doc, right_doc);
+"#;
+        let mut last_wins: KdlDocument = src.parse()?;
+        assert_eq!(last_wins.deduplicate_children_recursive(false), 2);
+        assert_eq!(last_wins.nodes().len(), 2);
+        assert_eq!(last_wins.nodes()[0].name().value(), "b");
+        assert_eq!(last_wins.get_arg("a"), Some(&3.into()));
+
+        let mut merged: KdlDocument = src.parse()?;
+        // Two extra `a` nodes, plus two extra `c`s once the children are concatenated.
+        assert_eq!(merged.deduplicate_children_recursive(true), 4);
+        merged.autoformat();
+        assert_eq!(merged.to_string(), "a 1 x=2 2 y=3 3 {\n    c 1 2 3\n}\nb\n");
         Ok(())
     }
 
     #[test]
-    fn basic_parsing() -> miette::Result<()> {
+    fn apply_to_values_at_path() -> miette::Result<()> {
         let src = r#"
-            // Hello, world!
-            node 1
-            node two
-            node item="three";
-            node {
-                nested 1 2 3
-                nested_2 hi "world"
+server port=80 backup=8080 {
+    admin port=9000
+}
+client port=80
+"#;
+        let bump = |v: &mut KdlValue| {
+            if let KdlValue::Integer(i) = v {
+                *i = *i * 3 / 2;
             }
-            (type)node ("type")what?
-            +false #true
-            null_id null_prop=#null
-                    foo indented
-            // normal comment?
-            /- comment
-            /* block comment */
-            inline /*comment*/ here
-            another /-comment there
+        };
 
+        let mut doc: KdlDocument = src.parse()?;
+        doc.apply_to_values_at_path(&["server"], bump);
+        assert_eq!(
+            doc.to_string(),
+            r#"
+server port=120 backup=12120 {
+    admin port=9000
+}
+client port=80
+"#
+        );
 
-            after some whitespace
-            trailing /* multiline */
-            trailing // single line
-            "#;
-        let _doc: KdlDocument = src.parse()?;
+        let mut doc: KdlDocument = src.parse()?;
+        doc.apply_to_all_values_at_path(&["server"], bump);
+        assert_eq!(doc.get("server").unwrap()["port"], 120.into());
+        assert_eq!(
+            doc.node_at_path_mut(&["server", "admin"]).unwrap()["port"],
+            13500.into()
+        );
+        assert_eq!(doc.get("client").unwrap()["port"], 80.into());
+
+        // Missing paths are a no-op.
+        let mut doc: KdlDocument = src.parse()?;
+        doc.apply_to_all_values_at_path(&["server", "nope"], bump);
+        doc.apply_to_all_values_at_path(&[], bump);
+        assert_eq!(doc.to_string(), src);
+        Ok(())
+    }
+
+    #[test]
+    fn collect_and_drain_nodes() -> miette::Result<()> {
+        let src = "// leading\na 1\nb 2\nc 3\n// trailing\n";
+        let doc: KdlDocument = src.parse()?;
+        let nodes = doc.collect_nodes();
+        assert_eq!(
+            nodes.iter().map(|n| n.name().value()).collect::<Vec<_>>(),
+            vec!["a", "b", "c"]
+        );
+
+        let rebuilt = KdlDocument::from_nodes(nodes);
+        assert_eq!(rebuilt.nodes().len(), 3);
+        assert_eq!(rebuilt.format(), None);
+        assert_eq!(rebuilt.to_string(), "a 1\nb 2\nc 3\n");
+
+        let mut doc: KdlDocument = src.parse()?;
+        let mut drain = doc.drain_nodes();
+        assert_eq!(
+            drain.next().map(|n| n.name().value().to_string()),
+            Some("a".into())
+        );
+        drop(drain);
+        assert!(doc.nodes().is_empty());
+        assert_eq!(doc.to_string(), "// leading\n// trailing\n");
+        Ok(())
+    }
+
+    #[test]
+    fn partition_nodes() -> miette::Result<()> {
+        let doc: KdlDocument = "import a\nfoo 1\nimport b\nbar 2\nbaz 3".parse()?;
+        let total = doc.nodes().len();
+        let (imports, rest) = doc.partition_nodes(|n| n.name().value() == "import");
+        assert_eq!(imports.nodes().len() + rest.nodes().len(), total);
+        assert_eq!(
+            imports.nodes().iter().map(|n| &n[0]).collect::<Vec<_>>(),
+            vec![&"a".into(), &"b".into()]
+        );
+        assert_eq!(
+            rest.nodes()
+                .iter()
+                .map(|n| n.name().value())
+                .collect::<Vec<_>>(),
+            vec!["foo", "bar", "baz"]
+        );
+
+        let (all, none) = KdlDocument::new().partition_nodes(|_| true);
+        assert!(all.nodes().is_empty() && none.nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn parse_fragment() -> miette::Result<()> {
+        let src = "  foo 1 /* hi */ bar=2\n  baz { qux }\n";
+        let fragment = KdlDocument::parse_fragment(src)?;
+        let wrapped: KdlDocument = format!("parent {{{src}}}").parse()?;
+        assert_eq!(&fragment, wrapped.nodes()[0].children().unwrap());
+        assert_eq!(fragment.to_string(), src);
+
+        #[cfg(feature = "span")]
+        {
+            assert_eq!(fragment.nodes()[0].span(), SourceSpan::from((2, 20)));
+            check_spans_for_doc(&fragment, &src);
+        }
+
+        assert!(KdlDocument::parse_fragment("foo }").is_err());
+        assert!(KdlDocument::parse_fragment("\u{FEFF}foo").is_err());
+        assert!(KdlDocument::parse_fragment("")?.nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn checksum_node_paths() -> miette::Result<()> {
+        let old: KdlDocument = r#"
+server {
+    tls cert=a.pem
+    port 80
+}
+item 1
+item 2 { nested }
+"#
+        .parse()?;
+        let new: KdlDocument = r#"
+server {
+    tls cert=b.pem
+    port 80
+}
+item 1
+item 2 { nested }
+"#
+        .parse()?;
+        let old = old.checksum_node_paths();
+        let new = new.checksum_node_paths();
+        let mut paths: Vec<_> = old.keys().map(|k| k.as_str()).collect();
+        paths.sort();
+        assert_eq!(
+            paths,
+            vec![
+                "item",
+                "item[1]",
+                "item[1]/nested",
+                "server",
+                "server/port",
+                "server/tls"
+            ]
+        );
+        let mut changed: Vec<_> = old
+            .iter()
+            .filter(|(path, hash)| new[*path] != **hash)
+            .map(|(path, _)| path.as_str())
+            .collect();
+        changed.sort();
+        assert_eq!(changed, vec!["server", "server/tls"]);
+        assert!(KdlDocument::new().checksum_node_paths().is_empty());
         Ok(())
     }
 
     #[test]
-    fn parsing() -> miette::Result<()> {
-        let src = "
-// This is the first node
-foo 1 2 three #null #true bar=\"baz\" {
-    - 1
-    - 2
-    - three
-    (mytype)something (\"name\")else\r
-}
-
-null_id null_prop=#null
-true_id true_prop=#null
-+false #true
+    fn into_iter() -> miette::Result<()> {
+        let doc: KdlDocument = "a 1\nb 2\nc 3\nd 4".parse()?;
+        let expected = doc.nodes().to_vec();
+
+        let mut iter = doc.clone().into_iter();
+        assert_eq!(iter.len(), 4);
+        assert_eq!(iter.next().as_ref(), Some(&expected[0]));
+        assert_eq!(iter.next_back().as_ref(), Some(&expected[3]));
+        assert_eq!(iter.len(), 2);
+        assert_eq!(
+            iter.rev().collect::<Vec<_>>(),
+            vec![expected[2].clone(), expected[1].clone()]
+        );
 
-         bar \"indented\" // trailing whitespace after this\t
-/*
-Some random comment
- */
+        let mut names = Vec::new();
+        for node in doc {
+            let node: KdlNode = node;
+            names.push(node.name().value().to_string());
+        }
+        assert_eq!(names, vec!["a", "b", "c", "d"]);
+        assert_eq!(KdlDocument::new().into_iter().len(), 0);
+        Ok(())
+    }
 
-a; b; c;
-/-commented \"node\"
+    #[test]
+    fn replace_node_at() -> miette::Result<()> {
+        let mut doc: KdlDocument = "// leading\na 1\nb 2\nc 3\n".parse()?;
+        let before = doc.nodes().to_vec();
+        let old = doc.replace_node_at(1, KdlNode::new("z").with_appended_arg(26));
+        assert_eq!(old, before[1]);
+        assert_eq!(doc.nodes().len(), before.len());
+        assert_eq!(doc.nodes()[0], before[0]);
+        assert_eq!(doc.nodes()[2], before[2]);
+        assert_eq!(doc.nodes()[1].name().value(), "z");
+        assert!(doc.format().is_none());
+        Ok(())
+    }
 
-another /*foo*/ \"node\" /-1 /*bar*/ #null;
-final;";
-        let mut doc: KdlDocument = src.parse()?;
+    #[test]
+    #[should_panic]
+    fn replace_node_at_out_of_bounds() {
+        KdlDocument::new().replace_node_at(0, KdlNode::new("a"));
+    }
 
-        assert_eq!(doc.get_arg("foo"), Some(&1.into()));
+    #[test]
+    fn subtree_index() -> miette::Result<()> {
+        let mut doc: KdlDocument = "a 1\nb 2\na 3\nc 4".parse()?;
+        doc.build_subtree_index();
+        assert_eq!(doc.name_index.as_ref().map(|i| i.len()), Some(3));
+        assert_eq!(doc.get_node_by_name_indexed("a"), Some(&doc.nodes()[0]));
+        assert_eq!(doc.get_node_by_name_indexed("c"), Some(&doc.nodes()[3]));
+        assert_eq!(doc.get_node_by_name_indexed("d"), None);
+
+        // Mutable access invalidates the index, and lookups fall back to a
+        // linear scan until it's rebuilt.
+        doc.nodes_mut().insert(0, KdlNode::new("c"));
+        assert!(doc.name_index.is_none());
+        assert_eq!(doc.get_node_by_name_indexed("c"), Some(&doc.nodes()[0]));
+
+        doc.build_subtree_index();
+        doc.get_mut("b").unwrap().set_name("d");
+        assert!(doc.name_index.is_none());
+        assert_eq!(doc.get_node_by_name_indexed("d"), Some(&doc.nodes()[2]));
+        assert_eq!(doc.get_node_by_name_indexed("b"), None);
+
+        let mut parent: KdlNode = "parent { x; y }".parse()?;
+        parent.ensure_children().build_subtree_index();
+        parent.iter_children_mut().next().unwrap().set_name("z");
+        let children = parent.children().unwrap();
         assert_eq!(
-            doc.iter_dash_args("foo").collect::<Vec<&KdlValue>>(),
-            vec![&1.into(), &2.into(), &"three".into()]
+            children.get_node_by_name_indexed("z"),
+            Some(&children.nodes()[0])
         );
+
+        // The index is ignored for equality.
+        let mut indexed = doc.clone();
+        indexed.build_subtree_index();
+        assert_eq!(indexed, doc);
+        Ok(())
+    }
+
+    #[test]
+    fn leaf_and_branch_nodes() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+server {
+    listen 80
+    tls {
+        cert "a.pem"
+    }
+    empty {}
+}
+debug
+"#
+        .parse()?;
+        let names = |iter: &mut dyn Iterator<Item = &KdlNode>| {
+            iter.map(|n| n.name().value().to_string())
+                .collect::<Vec<_>>()
+        };
         assert_eq!(
-            doc.format().map(|f| &f.leading[..]),
-            Some("\n// This is the first node\n")
+            names(&mut doc.leaf_nodes()),
+            vec!["listen", "cert", "empty", "debug"]
         );
-
-        let foo = doc.get("foo").expect("expected a foo node");
-        assert_eq!(foo.format().map(|f| &f.terminator[..]), Some("\n"));
-        assert_eq!(&foo[2], &"three".into());
-        assert_eq!(&foo["bar"], &"baz".into());
+        assert_eq!(names(&mut doc.branch_nodes()), vec!["server", "tls"]);
+        assert_eq!(names(&mut doc.top_level_leaf_nodes()), vec!["debug"]);
+        assert_eq!(names(&mut doc.top_level_branch_nodes()), vec!["server"]);
         assert_eq!(
-            foo.children().unwrap().get_arg("something"),
-            Some(&"else".into())
+            doc.leaf_nodes().count() + doc.branch_nodes().count(),
+            doc.descendants().count()
         );
-        assert_eq!(doc.get_arg("another"), Some(&"node".into()));
-
-        let null = doc.get("null_id").expect("expected a null_id node");
-        assert_eq!(&null["null_prop"], &KdlValue::Null);
-
-        let tru = doc.get("true_id").expect("expected a true_id node");
-        assert_eq!(&tru["true_prop"], &KdlValue::Null);
-
-        let plusfalse = doc.get("+false").expect("expected a +false node");
-        assert_eq!(&plusfalse[0], &KdlValue::Bool(true));
+        assert_eq!(KdlDocument::new().leaf_nodes().count(), 0);
+        Ok(())
+    }
 
-        let bar = doc.get("bar").expect("expected a bar node");
+    #[test]
+    fn collect_property_values() -> miette::Result<()> {
+        let mut doc: KdlDocument = r#"
+http timeout=30 {
+    route "/" timeout=5 retries=3
+    route "/slow" timeout=60 timeout=120
+}
+db "timeout"=10 {
+    - timeout
+}
+"#
+        .parse()?;
         assert_eq!(
-            format!("{}", bar),
-            "\n         bar \"indented\" // trailing whitespace after this\t\n"
+            doc.collect_property_values("timeout"),
+            vec![&30.into(), &5.into(), &60.into(), &120.into(), &10.into()]
         );
+        assert!(doc.collect_property_values("missing").is_empty());
 
-        let a = doc.get("a").expect("expected a node");
+        for value in doc.collect_property_values_mut("timeout") {
+            *value = (value.as_integer().unwrap() * 2).into();
+        }
         assert_eq!(
-            format!("{}", a),
-            "/*\nSome random comment\n */\n\na;".to_string()
+            doc.collect_property_values("timeout"),
+            vec![&60.into(), &10.into(), &120.into(), &240.into(), &20.into()]
         );
+        Ok(())
+    }
 
-        let b = doc.get("b").expect("expected a node");
-        assert_eq!(format!("{}", b), " b;".to_string());
-
-        // Round-tripping works.
-        assert_eq!(format!("{}", doc), src);
+    #[test]
+    fn split_at_node() -> miette::Result<()> {
+        let doc: KdlDocument = "// leading\na\nb\nc\n".parse()?;
+        let names = |doc: &KdlDocument| {
+            doc.nodes()
+                .iter()
+                .map(|n| n.name().value().to_string())
+                .collect::<Vec<_>>()
+        };
 
-        // Programmatic manipulation works.
-        let mut node: KdlNode = "new\n".parse()?;
-        // Manual entry parsing preserves formatting/reprs. Note that
-        // if you're making KdlEntries this way, you need to inject
-        // your own whitespace (or format the node)
-        node.push(" \"blah\"=0xDEADbeef".parse::<KdlEntry>()?);
-        doc.nodes_mut().push(node);
+        let (left, right) = doc.clone().split_at_node(1);
+        assert_eq!(names(&left), vec!["a"]);
+        assert_eq!(names(&right), vec!["b", "c"]);
+        assert!(left.format().is_none() && right.format().is_none());
 
-        assert_eq!(
-            format!("{}", doc),
-            format!("{}new \"blah\"=0xDEADbeef\n", src)
-        );
+        let (left, right) = doc.clone().split_at_node(0);
+        assert!(left.nodes().is_empty());
+        assert_eq!(right.nodes(), doc.nodes());
 
+        let (left, right) = doc.clone().split_at_node(3);
+        assert_eq!(left.nodes(), doc.nodes());
+        assert!(right.nodes().is_empty());
         Ok(())
     }
 
     #[test]
-    fn construction() {
-        let mut doc = KdlDocument::new();
-        doc.nodes_mut().push(KdlNode::new("foo"));
-
-        let mut bar = KdlNode::new("bar");
-        bar.insert("prop", "value");
-        bar.push(1);
-        bar.push(2);
-        bar.push(false);
-        bar.push(KdlValue::Null);
+    #[should_panic]
+    fn split_at_node_out_of_bounds() {
+        let doc: KdlDocument = "a\nb".parse().unwrap();
+        doc.split_at_node(3);
+    }
 
-        let subdoc = bar.ensure_children();
-        subdoc.nodes_mut().push(KdlNode::new("barchild"));
-        doc.nodes_mut().push(bar);
-        doc.nodes_mut().push(KdlNode::new("baz"));
+    #[test]
+    fn inject_at_path() -> miette::Result<()> {
+        let mut doc: KdlDocument = "http {\n    server\n}\nserver\n".parse()?;
+        doc.inject_at_path(
+            &["http", "server"],
+            KdlNode::new("listen").with_appended_arg(80),
+            false,
+        )?;
+        doc.inject_at_path(
+            &["http", "server"],
+            KdlNode::new("listen").with_appended_arg(443),
+            false,
+        )?;
+        doc.inject_at_path(&[], KdlNode::new("log"), false)?;
+
+        let err = doc
+            .inject_at_path(&["http", "proxy", "upstream"], KdlNode::new("x"), false)
+            .unwrap_err();
+        assert_eq!(
+            err.diagnostics[0].message.as_deref(),
+            Some("No node at path `http/proxy`")
+        );
+        assert!(err.input.is_empty());
+        assert_eq!(err.diagnostics[0].span, (0, 0).into());
+        let err = doc
+            .inject_at_path(&["server", "tls", "cert"], KdlNode::new("x"), false)
+            .unwrap_err();
+        assert_eq!(
+            err.diagnostics[0].message.as_deref(),
+            Some("No node at path `server/tls`")
+        );
 
+        doc.inject_at_path(
+            &["http", "proxy", "upstream"],
+            KdlNode::new("backend"),
+            true,
+        )?;
         doc.autoformat();
-
         assert_eq!(
-            r#"foo
-bar prop=value 1 2 #false #null {
-    barchild
+            doc.to_string(),
+            r#"http {
+    server {
+        listen 80
+        listen 443
+    }
+    proxy {
+        upstream {
+            backend
+        }
+    }
 }
-baz
-"#,
-            format!("{}", doc)
+server
+log
+"#
         );
+        Ok(())
     }
 
-    #[ignore = "There's still issues around formatting comments and esclines."]
     #[test]
-    fn autoformat() -> miette::Result<()> {
-        let mut doc: KdlDocument = r##"
+    fn topological_sort() -> miette::Result<()> {
+        fn deps(node: &KdlNode) -> Vec<&str> {
+            node.iter()
+                .filter(|e| e.name().is_none())
+                .filter_map(|e| e.value().as_string())
+                .collect()
+        }
+        fn names<'a>(nodes: &[&'a KdlNode]) -> Vec<&'a str> {
+            nodes.iter().map(|n| n.name().value()).collect()
+        }
 
-        /* x */ foo    1 "bar"=0xDEADbeef {
-    child1     1  ;
+        // Two roots (`docs` and `test`), shared dependencies, and a dependency
+        // on something that isn't in the document.
+        let doc: KdlDocument = r#"
+test build fixtures
+docs
+build codegen "external"
+fixtures
+codegen
+"#
+        .parse()?;
+        assert_eq!(
+            names(&doc.topological_sort(deps).unwrap()),
+            vec!["docs", "fixtures", "codegen", "build", "test"]
+        );
 
- // child 2 comment
+        // A document that's already sorted stays as it is.
+        let doc: KdlDocument =
+            "codegen\nbuild codegen\nfixtures\ntest build fixtures\ndocs".parse()?;
+        assert_eq!(
+            names(&doc.topological_sort(deps).unwrap()),
+            vec!["codegen", "build", "fixtures", "test", "docs"]
+        );
 
-        child2 2 /-3 // comment
+        let doc: KdlDocument = "a b\nb c\nc a\nd".parse()?;
+        let err = doc.topological_sort(deps).unwrap_err();
+        assert_eq!(err.cycle, vec!["a", "b", "c", "a"]);
+        assert_eq!(
+            err.to_string(),
+            "Dependency cycle detected: a -> b -> c -> a"
+        );
 
-               child3    "
+        let doc: KdlDocument = "a a".parse()?;
+        assert_eq!(
+            doc.topological_sort(deps).unwrap_err().cycle,
+            vec!["a", "a"]
+        );
 
-   string\t
-   " \
-{
-       /*
+        assert!(KdlDocument::new()
+            .topological_sort(deps)
+            .unwrap()
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn pipe() -> miette::Result<()> {
+        fn rename(mut doc: KdlDocument) -> KdlDocument {
+            for node in doc.nodes_mut() {
+                node.set_name(node.name().value().to_uppercase());
+            }
+            doc
+        }
+        fn require_port(doc: KdlDocument) -> Result<KdlDocument, KdlError> {
+            if doc.get("PORT").is_some() {
+                Ok(doc)
+            } else {
+                Err(KdlError::single(
+                    &doc.to_string(),
+                    (0, 0),
+                    "Missing port",
+                    None,
+                ))
+            }
+        }
 
+        let doc: KdlDocument = "port 8080\nhost localhost".parse()?;
+        let piped = doc.clone().pipe(rename).pipe(|d| d);
+        assert_eq!(piped.to_string(), "PORT 8080\nHOST localhost");
 
-       multiline*/
-                                    inner1    \
-                    #"value"# \
-                    ;
+        let piped = doc.clone().pipe(rename).try_pipe(require_port)?;
+        assert_eq!(piped.to_string(), "PORT 8080\nHOST localhost");
 
-        inner2      \ //comment
-        {
-            inner3
-        }
+        let err = doc.try_pipe(require_port).unwrap_err();
+        assert_eq!(err.diagnostics[0].message.as_deref(), Some("Missing port"));
+        Ok(())
     }
-               }
-
-        // trailing comment here
 
-        "##
+    #[test]
+    fn annotate_with_defaults() -> miette::Result<()> {
+        let defaults: KdlDocument = r#"
+server host=localhost port=8080 {
+    tls enabled=#false
+}
+log level=info
+"#
         .parse()?;
 
-        KdlDocument::autoformat(&mut doc);
+        // Nodes that already have every property are unchanged.
+        let input = "server host=example.com port=443 {\n    tls enabled=#true\n}\nlog level=debug";
+        let mut doc: KdlDocument = input.parse()?;
+        doc.annotate_with_defaults(&defaults);
+        assert_eq!(doc.to_string(), input);
 
+        // Missing properties are filled in, at every level.
+        let mut doc: KdlDocument = "server port=9090 {\n    tls cert=a.pem\n}\nlog".parse()?;
+        doc.annotate_with_defaults(&defaults);
         assert_eq!(
             doc.to_string(),
-            r#"/* x */
-foo 1 bar=0xdeadbeef {
-    child1 1
-    // child 2 comment
-    child2 2 /-3 // comment
-    child3 "\nstring\t" {
-        /*
+            "server port=9090 host=localhost {\n    tls cert=a.pem enabled=#false\n}\nlog level=info"
+        );
 
+        // Nodes without a matching default are untouched, even if their
+        // children have the same names as other defaults.
+        let input = "cache size=10 {\n    log\n}\nserver";
+        let mut doc: KdlDocument = input.parse()?;
+        doc.annotate_with_defaults(&defaults);
+        assert_eq!(
+            doc.to_string(),
+            "cache size=10 {\n    log\n}\nserver host=localhost port=8080"
+        );
+        Ok(())
+    }
 
-       multiline*/
-        inner1 value
-        inner2 {
-            inner3
+    #[test]
+    fn to_adaptive_string() -> miette::Result<()> {
+        let input = r#"
+// comment
+(t)name   "my app"
+"weird name" key=(u8)0xff
+enabled #true
+"#;
+        let doc: KdlDocument = input.parse()?;
+        assert!(doc.fits_on_one_line(80));
+        assert!(!doc.fits_on_one_line(20));
+        let line = doc.to_adaptive_string();
+        assert_eq!(
+            line,
+            r#"(t)name "my app"; "weird name" key=(u8)255; enabled #true"#
+        );
+        let mut reparsed: KdlDocument = line.parse()?;
+        let mut original = doc.clone();
+        reparsed.autoformat_no_comments();
+        original.autoformat_no_comments();
+        assert_eq!(reparsed.to_string(), original.to_string());
+
+        // Children and multiple entries always need multiple lines.
+        for input in ["a 1 2", "a {\n}", "a\nb {\n    c\n}"] {
+            let doc: KdlDocument = input.parse()?;
+            assert!(!doc.fits_on_one_line(usize::MAX), "{input:?}");
+            let mut expected = doc.clone();
+            expected.autoformat_no_comments();
+            assert_eq!(doc.to_adaptive_string(), expected.to_string());
         }
+
+        // Too long for a single line.
+        let doc: KdlDocument = (0..20)
+            .map(|i| format!("node{i} {i}\n"))
+            .collect::<String>()
+            .parse()?;
+        assert!(doc.fits_on_one_line(usize::MAX));
+        assert!(doc.to_adaptive_string().contains("\nnode19 19\n"));
+
+        // Widths are counted in characters, not bytes.
+        let doc: KdlDocument = format!("name \"{}\"", "é".repeat(70)).parse()?;
+        let line = doc.single_line_string().unwrap();
+        let width = line.chars().count();
+        assert!(line.len() > ADAPTIVE_LINE_WIDTH && width <= ADAPTIVE_LINE_WIDTH);
+        assert!(doc.fits_on_one_line(width));
+        assert!(!doc.fits_on_one_line(width - 1));
+        assert_eq!(doc.to_adaptive_string(), line);
+
+        assert!(KdlDocument::new().fits_on_one_line(0));
+        assert_eq!(KdlDocument::new().to_adaptive_string(), "");
+        Ok(())
     }
+
+    #[test]
+    fn count_by_type_annotation() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+(timestamp)created 1700000000
+event at=(timestamp)1700000001 id=(uuid)"6f1c" {
+    (timestamp)seen (u8)3
+    plain
 }
-// trailing comment here"#
+"#
+        .parse()?;
+        assert_eq!(
+            doc.count_by_type_annotation(),
+            HashMap::from([("timestamp", 2), ("uuid", 1), ("(none)", 2)])
+        );
+        assert_eq!(
+            doc.count_by_type_annotation_recursive(),
+            HashMap::from([("timestamp", 3), ("uuid", 1), ("u8", 1), ("(none)", 3)])
         );
+        assert!(KdlDocument::new().count_by_type_annotation().is_empty());
         Ok(())
     }
 
     #[test]
-    fn simple_autoformat() -> miette::Result<()> {
-        let mut doc: KdlDocument = "a { b { c { }; }; }".parse().unwrap();
-        KdlDocument::autoformat(&mut doc);
-        assert_eq!(
-            doc.to_string(),
-            r#"a {
-    b {
-        c {
-
-        }
+    fn collect_all_type_annotations() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+(timestamp)created 1700000000
+event at=(timestamp)1700000001 id=(uuid)"6f1c" {
+    (timestamp)seen (u8)3
+    plain {
+        ("(none)")deep (u8)1 (u8)2
     }
 }
 "#
+        .parse()?;
+        assert_eq!(
+            doc.collect_all_type_annotations(),
+            HashSet::from(["timestamp", "uuid", "u8", "(none)"])
         );
+        assert_eq!(
+            doc.collect_type_annotations_with_counts(),
+            HashMap::from([("timestamp", 3), ("uuid", 1), ("u8", 3), ("(none)", 1)])
+        );
+        let untyped: KdlDocument = "a 1 { b x=2 }".parse()?;
+        assert!(untyped.collect_all_type_annotations().is_empty());
+        assert!(untyped.collect_type_annotations_with_counts().is_empty());
         Ok(())
     }
 
     #[test]
-    fn simple_autoformat_two_spaces() -> miette::Result<()> {
-        let mut doc: KdlDocument = "a { b { c { }; }; }".parse().unwrap();
-        KdlDocument::autoformat_config(
-            &mut doc,
-            &FormatConfig {
-                indent: "  ",
-                ..Default::default()
-            },
-        );
+    fn sprint_node_tree() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+server host=localhost port=8080 {
+    route "/" {
+        handler index
+    }
+    route "/api"
+    tls {
+    }
+}
+"quoted name" 1 2 3
+"#
+        .parse()?;
         assert_eq!(
-            doc.to_string(),
-            r#"a {
-  b {
-    c {
+            doc.sprint_node_tree(),
+            r#"[0] server (2 entries, 3 children)
+  [1] route (1 entry, 1 child)
+    [2] handler (1 entry, 0 children)
+  [1] route (1 entry, 0 children)
+  [1] tls (0 entries, 0 children)
+[0] quoted name (3 entries, 0 children)
+"#
+        );
+        assert_eq!(KdlDocument::new().sprint_node_tree(), "");
+        Ok(())
+    }
 
+    #[test]
+    fn merge_recursive() -> miette::Result<()> {
+        let base: KdlDocument = r#"
+app "old" debug=#false {
+    server port=8080 host=localhost {
+        tls enabled=#false cert=old.pem
+        timeout 30
+    }
+    log level=info
+}
+cache size=10
+"#
+        .parse()?;
+        let overlay: KdlDocument = r#"
+app {
+    server port=9090 {
+        tls cert=new.pem
+        routes {
+            route "/"
+        }
+    }
+    metrics
+}
+worker 4
+"#
+        .parse()?;
+        let mut merged = KdlDocument::merge_recursive(&base, &overlay);
+        let mut expected: KdlDocument = r#"
+app "old" debug=#false {
+    server port=9090 host=localhost {
+        tls enabled=#false cert=new.pem
+        timeout 30
+        routes {
+            route "/"
+        }
     }
-  }
+    log level=info
+    metrics
 }
+cache size=10
+worker 4
 "#
+        .parse()?;
+        merged.clear_format_recursive();
+        expected.clear_format_recursive();
+        assert_eq!(merged, expected);
+
+        // Arguments are replaced wholesale, not merged.
+        let base: KdlDocument = "tags a b c key=1".parse()?;
+        let overlay: KdlDocument = "tags d".parse()?;
+        assert_eq!(
+            KdlDocument::merge_recursive(&base, &overlay).to_string(),
+            "tags d key=1"
+        );
+
+        // Merging with an empty document on either side is a copy.
+        assert_eq!(
+            KdlDocument::merge_recursive(&base, &KdlDocument::new()),
+            base
+        );
+        assert_eq!(
+            KdlDocument::merge_recursive(&KdlDocument::new(), &overlay).to_string(),
+            "tags d"
         );
         Ok(())
     }
 
     #[test]
-    fn simple_autoformat_single_tabs() -> miette::Result<()> {
-        let mut doc: KdlDocument = "a { b { c { }; }; }".parse().unwrap();
-        KdlDocument::autoformat_config(
-            &mut doc,
-            &FormatConfig {
-                indent: "\t",
-                ..Default::default()
-            },
+    fn verify_referential_integrity() -> miette::Result<()> {
+        let input = r#"
+db main
+db replica
+service web main {
+    cache redis
+}
+service api missing
+service worker
+queue jobs {
+    consumer web
+    consumer gone
+}
+"#;
+        let doc: KdlDocument = input.parse()?;
+        assert!(doc
+            .verify_referential_integrity(&[("unused", 0, "db")])
+            .is_empty());
+        let valid: KdlDocument = "db main\nservice web main".parse()?;
+        assert!(valid
+            .verify_referential_integrity(&[("service", 1, "db")])
+            .is_empty());
+
+        let errors = doc.verify_referential_integrity(&[
+            ("service", 1, "db"),
+            ("consumer", 0, "service"),
+            ("cache", 0, "cache"),
+        ]);
+        let broken: Vec<_> = errors
+            .iter()
+            .map(|e| (e.node_name.as_str(), e.reference.clone()))
+            .collect();
+        assert_eq!(
+            broken,
+            vec![
+                ("service", Some("missing".into())),
+                ("service", None),
+                ("consumer", Some("gone".into())),
+                // A node can't refer to itself.
+                ("cache", Some("redis".into())),
+            ]
         );
-        assert_eq!(doc.to_string(), "a {\n\tb {\n\t\tc {\n\n\t\t}\n\t}\n}\n");
+        assert_eq!(errors[0].arg_index, 1);
+        assert_eq!(errors[0].target_node_name, "db");
+        #[cfg(feature = "span")]
+        {
+            assert_eq!(&input[errors[0].span.offset()..][..7], "missing");
+            assert!(input[errors[1].span.offset()..].starts_with("service worker"));
+        }
         Ok(())
     }
 
     #[test]
-    fn simple_autoformat_no_comments() -> miette::Result<()> {
-        let mut doc: KdlDocument =
-            "// a comment\na {\n// another comment\n b { c { // another comment\n }; }; }"
-                .parse()
-                .unwrap();
-        KdlDocument::autoformat_no_comments(&mut doc);
+    fn to_flattened_properties() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+name kdl
+server "main" 8080 debug=#false {
+    tls port=443 cert=(path)"a.pem"
+    routes {
+        route "/" handler=index
+    }
+}
+empty
+"#
+        .parse()?;
+        let props = doc.to_flattened_properties()?;
         assert_eq!(
-            doc.to_string(),
-            r#"a {
-    b {
-        c {
+            props,
+            HashMap::from([
+                ("name.[0]".into(), "kdl".into()),
+                ("server.[0]".into(), "main".into()),
+                ("server.[1]".into(), 8080.into()),
+                ("server.debug".into(), false.into()),
+                ("server.tls.port".into(), 443.into()),
+                ("server.tls.cert".into(), "a.pem".into()),
+                ("server.routes.route.[0]".into(), "/".into()),
+                ("server.routes.route.handler".into(), "index".into()),
+            ])
+        );
 
+        // Sibling nodes with the same name are fine as long as their entries
+        // don't clash.
+        let doc: KdlDocument = "server port=80\nserver host=localhost".parse()?;
+        assert_eq!(doc.to_flattened_properties()?.len(), 2);
+
+        for ambiguous in [
+            "server port=80\nserver port=443",
+            "server 1\nserver 2",
+            "node a=1 a=2",
+            "a.b c=1\na {\n    b c=2\n}",
+        ] {
+            let doc: KdlDocument = ambiguous.parse()?;
+            assert!(
+                doc.to_flattened_properties().is_err(),
+                "{ambiguous:?} should be ambiguous"
+            );
         }
+        Ok(())
     }
+
+    #[test]
+    fn mirror() -> miette::Result<()> {
+        let input = r#"
+// The app's name.
+(app)name "kdl" version=2
+server "main" port=(u16)8080 ratio=0.75 tls=#true proxy=#null {
+    route "/" handler=index
+    empty
+}
+"#;
+        let doc: KdlDocument = input.parse()?;
+        let mirrored = doc.mirror();
+        assert_eq!(
+            mirrored.to_string(),
+            r#"// The app's name.
+(app)name (string)"" version=(integer)0
+server (string)"" port=(integer)0 ratio=(float)0.0 tls=(boolean)#false proxy=#null {
+    route (string)"" handler=(string)""
+    empty
 }
 "#
         );
-        Ok(())
-    }
 
-    #[cfg(feature = "span")]
-    fn check_spans_for_doc(doc: &KdlDocument, source: &impl miette::SourceCode) {
-        for node in doc.nodes() {
-            check_spans_for_node(node, source);
+        // The output is valid KDL with the same shape as the input.
+        let reparsed: KdlDocument = mirrored.to_string().parse()?;
+        fn shape(doc: &KdlDocument) -> Vec<(String, Vec<Option<String>>)> {
+            doc.descendants()
+                .map(|node| {
+                    let names = node.iter().map(|e| e.name().map(|n| n.to_string()));
+                    (node.name().to_string(), names.collect())
+                })
+                .collect()
         }
+        assert_eq!(shape(&reparsed), shape(&doc));
+        assert_eq!(reparsed.mirror().to_string(), mirrored.to_string());
+        Ok(())
     }
 
-    #[cfg(feature = "span")]
-    fn check_spans_for_node(node: &KdlNode, source: &impl miette::SourceCode) {
-        use crate::KdlEntryFormat;
-
-        check_span_for_ident(node.name(), source);
-        if let Some(ty) = node.ty() {
-            check_span_for_ident(ty, source);
+    #[test]
+    fn collect_nodes_by_depth() -> miette::Result<()> {
+        fn names(doc: &KdlDocument) -> Vec<Vec<&str>> {
+            doc.collect_nodes_by_depth()
+                .iter()
+                .map(|level| level.iter().map(|n| n.name().value()).collect())
+                .collect()
         }
 
-        for entry in node.entries() {
-            if let Some(name) = entry.name() {
-                check_span_for_ident(name, source);
-            }
-            if let Some(ty) = entry.ty() {
-                check_span_for_ident(ty, source);
-            }
-            if let Some(KdlEntryFormat { value_repr, .. }) = entry.format() {
-                if entry.name().is_none() && entry.ty().is_none() {
-                    check_span(value_repr, entry.span(), source);
-                }
-            }
-        }
-        if let Some(children) = node.children() {
-            check_spans_for_doc(children, source);
-        }
+        let doc: KdlDocument = r#"
+server {
+    tls {
+        cert "a.pem"
+        key "b.pem"
     }
+    port 80
+}
+client {
+    retries 3
+}
+log {}
+"#
+        .parse()?;
+        assert_eq!(
+            names(&doc),
+            vec![
+                vec!["server", "client", "log"],
+                vec!["tls", "port", "retries"],
+                vec!["cert", "key"],
+            ]
+        );
+        assert_eq!(doc.depth(), 2);
+        assert_eq!(doc.collect_nodes_by_depth().len(), doc.depth() + 1);
 
-    #[cfg(feature = "span")]
-    #[track_caller]
-    fn check_span_for_ident(ident: &KdlIdentifier, source: &impl miette::SourceCode) {
-        if let Some(repr) = ident.repr() {
-            check_span(repr, ident.span(), source);
-        } else {
-            check_span(ident.value(), ident.span(), source);
-        }
-    }
+        let flat: KdlDocument = "a 1\nb 2\nc {}".parse()?;
+        assert_eq!(names(&flat), vec![vec!["a", "b", "c"]]);
+        assert_eq!(flat.depth(), 0);
 
-    #[cfg(feature = "span")]
-    #[track_caller]
-    fn check_span(expected: &str, span: SourceSpan, source: &impl miette::SourceCode) {
-        let span = source.read_span(&span, 0, 0).unwrap();
-        let span = std::str::from_utf8(span.data()).unwrap();
-        assert_eq!(span, expected);
+        let empty = KdlDocument::new();
+        assert_eq!(names(&empty), vec![Vec::<&str>::new()]);
+        assert_eq!(empty.depth(), 0);
+        Ok(())
     }
 
-    #[cfg(feature = "span")]
     #[test]
-    fn span_test() -> miette::Result<()> {
-        let input = r####"
-this {
-    is (a)"cool" document="to" read=(int)5 10.1 (u32)0x45
-    and x="" {
-        "it" /*shh*/ "has"="💯" ##"the"##
-        Best🎊est
-        "syntax ever"
+    fn iter_entries() -> miette::Result<()> {
+        let mut doc: KdlDocument = r#"
+server "main" port=80 {
+    tls cert="a.pem" {
+        cipher "A" "B"
     }
-    "yknow?" 0x10
+    listen "::1"
 }
-// that's
-nice
-inline { time; to; live "our" "dreams"; "y;all" }
-
-"####;
-
-        let doc: KdlDocument = input.parse()?;
-
-        // First check that all the identity-spans are correct
-        check_spans_for_doc(&doc, &input);
-
-        // Now check some more interesting concrete spans
-
-        // The whole document should be everything from the first node until the
-        // last before_terminator whitespace.
-        check_span(&input[1..(input.len() - 2)], doc.span(), &input);
-
-        // This one-liner node should be the whole line without leading whitespace
-        let is_node = doc
-            .get("this")
-            .unwrap()
-            .children()
-            .unwrap()
-            .get("is")
-            .unwrap();
-        check_span(
-            r##"is (a)"cool" document="to" read=(int)5 10.1 (u32)0x45"##,
-            is_node.span(),
-            &input,
-        );
-
-        // Some simple with/without type hints
-        check_span(r#"(a)"cool""#, is_node.entry(0).unwrap().span(), &input);
-        check_span(
-            r#"read=(int)5"#,
-            is_node.entry("read").unwrap().span(),
-            &input,
-        );
-        check_span(r#"10.1"#, is_node.entry(1).unwrap().span(), &input);
-        check_span(r#"(u32)0x45"#, is_node.entry(2).unwrap().span(), &input);
-
-        // Now let's look at some messed up parts of that "and" node
-        let and_node = doc
-            .get("this")
-            .unwrap()
-            .children()
-            .unwrap()
-            .get("and")
-            .unwrap();
-
-        // The node is what you expect, the whole line and its two braces
-        check_span(
-            r####"and x="" {
-        "it" /*shh*/ "has"="💯" ##"the"##
-        Best🎊est
-        "syntax ever"
-    }"####,
-            and_node.span(),
-            &input,
+empty
+log level=debug
+"#
+        .parse()?;
+        let entries: Vec<(String, String)> = doc
+            .iter_entries()
+            .map(|(path, entry)| (path.join("/"), entry.to_string().trim().to_string()))
+            .collect();
+        assert_eq!(
+            entries,
+            [
+                ("server", "\"main\""),
+                ("server", "port=80"),
+                ("server/tls", "cert=\"a.pem\""),
+                ("server/tls/cipher", "\"A\""),
+                ("server/tls/cipher", "\"B\""),
+                ("server/listen", "\"::1\""),
+                ("log", "level=debug"),
+            ]
+            .map(|(path, entry)| (path.to_string(), entry.to_string()))
         );
 
-        // The child document is a little weird, it's the contents *inside* the braces
-        // without the surrounding whitespace/comments. Just the actual contents.
-        check_span(
-            r####""it" /*shh*/ "has"="💯" ##"the"##
-        Best🎊est
-        "syntax ever""####,
-            and_node.children().unwrap().span(),
-            &input,
+        for (path, entry) in doc.iter_entries_mut() {
+            if path.len() > 1 {
+                entry.set_value(path.len() as i128);
+                entry.clear_format();
+            }
+        }
+        let values: Vec<_> = doc
+            .iter_entries()
+            .map(|(_, entry)| entry.value().clone())
+            .collect();
+        assert_eq!(
+            values,
+            vec![
+                "main".into(),
+                80.into(),
+                2.into(),
+                3.into(),
+                3.into(),
+                2.into(),
+                "debug".into(),
+            ]
         );
+        assert_eq!(KdlDocument::new().iter_entries().count(), 0);
+        Ok(())
+    }
 
-        // Oh hey don't forget to check that "x" entry
-        check_span(r#"x="""#, and_node.entry("x").unwrap().span(), &input);
-
-        // Now the "it" node, more straightforward
-        let it_node = and_node.children().unwrap().get("it").unwrap();
-        check_span(
-            r####""it" /*shh*/ "has"="💯" ##"the"##"####,
-            it_node.span(),
-            &input,
-        );
-        check_span(
-            r#""has"="💯""#,
-            it_node.entry("has").unwrap().span(),
-            &input,
-        );
-        check_span(
-            r####"##"the"##"####,
-            it_node.entry(0).unwrap().span(),
-            &input,
+    #[test]
+    fn assert_no_unknown_properties() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+server host=localhost port=80
+server hostname=example.com prot=443 timout=30 x=1
+group {
+    server host=a PORT=1
+}
+client prot=1
+"#
+        .parse()?;
+        let allowed = ["host", "port", "timeout"];
+        let errors = doc
+            .assert_no_unknown_properties("server", &allowed)
+            .unwrap_err();
+        let found: Vec<_> = errors
+            .iter()
+            .map(|e| (e.name.as_str(), e.suggestion.as_deref()))
+            .collect();
+        assert_eq!(
+            found,
+            vec![
+                ("hostname", None),
+                ("prot", Some("port")),
+                ("timout", Some("timeout")),
+                ("x", None),
+                ("PORT", None),
+            ]
         );
-
-        // Make sure inline nodes work ok
-        let inline_node = doc.get("inline").unwrap();
-        check_span(
-            r#"inline { time; to; live "our" "dreams"; "y;all" }"#,
-            inline_node.span(),
-            &input,
+        assert!(errors.iter().all(|e| e.node_name == "server"));
+        assert_eq!(
+            errors[1].to_string(),
+            "Unknown property `prot` on node `server`"
         );
 
-        let inline_children = inline_node.children().unwrap();
-        check_span(
-            r#"time; to; live "our" "dreams"; "y;all" "#,
-            inline_children.span(),
-            &input,
+        assert!(doc
+            .assert_no_unknown_properties("client", &["prot"])
+            .is_ok());
+        assert!(doc.assert_no_unknown_properties("missing", &[]).is_ok());
+        assert_eq!(
+            doc.assert_no_unknown_properties("client", &[]).unwrap_err()[0].suggestion,
+            None
         );
+        Ok(())
+    }
 
-        let inline_nodes = inline_children.nodes();
-        check_span("time", inline_nodes[0].span(), &input);
-        check_span("to", inline_nodes[1].span(), &input);
-        check_span(r#"live "our" "dreams""#, inline_nodes[2].span(), &input);
-        check_span(r#""y;all" "#, inline_nodes[3].span(), &input);
+    #[test]
+    fn levenshtein() {
+        assert_eq!(super::levenshtein("", ""), 0);
+        assert_eq!(super::levenshtein("port", ""), 4);
+        assert_eq!(super::levenshtein("kitten", "sitting"), 3);
+        assert_eq!(super::levenshtein("prot", "port"), 2);
+        assert_eq!(super::levenshtein("naïve", "naive"), 1);
+    }
 
+    #[test]
+    fn apply_overrides_from() -> miette::Result<()> {
+        let mut doc: KdlDocument = r#"
+server "main" host=localhost port=8080 {
+    tls enabled=#false
+}
+log level=info
+cache
+"#
+        .parse()?;
+        let overrides: KdlDocument = r#"
+log level=debug file="app.log"
+server "replica" port=9090 {
+    tls cert="a.pem"
+}
+metrics port=9100
+cache size=10
+tracing
+"#
+        .parse()?;
+        doc.apply_overrides_from(&overrides);
+        let mut expected: KdlDocument = r#"
+server "replica" host=localhost port=9090 {
+    tls cert="a.pem"
+}
+log level=debug file="app.log"
+cache size=10
+metrics port=9100
+tracing
+"#
+        .parse()?;
+        doc.clear_format_recursive();
+        expected.clear_format_recursive();
+        assert_eq!(doc, expected);
+
+        // Overrides without arguments or children keep the original ones.
+        let mut doc: KdlDocument = "server \"main\" port=8080 {\n    tls\n}\n".parse()?;
+        doc.apply_overrides_from(&"server port=1".parse()?);
+        assert_eq!(doc.to_string(), "server \"main\" port=1 {\n    tls\n}\n");
+        doc.apply_overrides_from(&KdlDocument::new());
+        assert_eq!(doc.to_string(), "server \"main\" port=1 {\n    tls\n}\n");
         Ok(())
     }
 
     #[test]
-    fn parse_examples() -> miette::Result<()> {
-        include_str!("../examples/kdl-schema.kdl").parse::<KdlDocument>()?;
-        include_str!("../examples/Cargo.kdl").parse::<KdlDocument>()?;
-        include_str!("../examples/ci.kdl").parse::<KdlDocument>()?;
-        include_str!("../examples/nuget.kdl").parse::<KdlDocument>()?;
-        include_str!("../examples/website.kdl").parse::<KdlDocument>()?;
-        include_str!("../examples/zellij.kdl").parse::<KdlDocument>()?;
-        include_str!("../examples/zellij-unquoted-bindings.kdl").parse::<KdlDocument>()?;
+    fn comments_round_trip() -> miette::Result<()> {
+        let input = r#"// Leading comment.
+/* Block comment
+   over several lines */
+server /* before arg */ "main" port=8080 /-disabled=#true { // trailing
+    // Child comment.
+    tls /* nested /* block */ comment */ cert="a.pem"
+    /-listen 80
+}
+/-(old)node 1 {
+    child
+}
+log level=debug /* end */
+// Final comment.
+"#;
+        let doc: KdlDocument = input.parse()?;
+        assert_eq!(doc.to_string(), input);
+        let reparsed: KdlDocument = doc.to_string().parse()?;
+        assert_eq!(reparsed.to_string(), input);
+
+        let output = doc.to_string_with_comments();
+        assert_eq!(output, input);
+        let reparsed: KdlDocument = output.parse()?;
+        assert_eq!(reparsed, doc);
+        assert_eq!(reparsed.to_string_with_comments(), input);
         Ok(())
     }
 
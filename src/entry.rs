@@ -151,6 +151,49 @@ impl KdlEntry {
         }
     }
 
+    /// Returns the KDL literal for this entry's value, using the textual
+    /// representation it was parsed from (for example, `0xff` or `r#"foo"#`)
+    /// if it's still available, and [`KdlValue::to_kdl_literal`] otherwise.
+    pub fn to_kdl_literal_with_repr(&self) -> String {
+        match &self.format {
+            Some(fmt) if !fmt.value_repr.is_empty() => fmt.value_repr.clone(),
+            _ => self.value.to_kdl_literal(),
+        }
+    }
+
+    /// Returns this entry in its most compact form, as it would appear inside
+    /// a node: `42`, `key="some value"`, `(u8)255`, and so on.
+    ///
+    /// Unlike [`Display`], this ignores all formatting, including the value's
+    /// original representation, so the result is always valid KDL v2 and can
+    /// be pasted straight into a node.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::{KdlEntry, KdlNode};
+    /// let entry: KdlEntry = r#"  key = (ty) 0xff "#.parse().unwrap();
+    /// assert_eq!(entry.to_string(), r#"  key = (ty) 0xff "#);
+    /// assert_eq!(entry.to_inline_string(), "key=(ty)255");
+    /// let node: KdlNode = format!("node {}", entry.to_inline_string()).parse().unwrap();
+    /// assert_eq!(node["key"], 255.into());
+    /// ```
+    pub fn to_inline_string(&self) -> String {
+        let ident = |ident: &KdlIdentifier| KdlValue::String(ident.value().into()).to_string();
+        let mut out = String::new();
+        if let Some(name) = &self.name {
+            out.push_str(&ident(name));
+            out.push('=');
+        }
+        if let Some(ty) = &self.ty {
+            out.push('(');
+            out.push_str(&ident(ty));
+            out.push(')');
+        }
+        out.push_str(&self.value.to_kdl_literal());
+        out
+    }
+
     /// Length of this entry when rendered as a string.
     pub fn len(&self) -> usize {
         format!("{self}").len()
@@ -503,6 +546,7 @@ pub struct KdlEntryFormat {
 #[cfg(test)]
 mod test {
     use super::*;
+    use crate::KdlNode;
 
     #[test]
     fn reset_value_repr() -> miette::Result<()> {
@@ -515,6 +559,64 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn to_kdl_literal_with_repr() -> miette::Result<()> {
+        let mut entry: KdlEntry = " (u8)0xff ".parse()?;
+        assert_eq!(entry.to_kdl_literal_with_repr(), "0xff");
+        assert_eq!(entry.value().to_kdl_literal(), "255");
+        entry.clear_format();
+        assert_eq!(entry.to_kdl_literal_with_repr(), "255");
+
+        let entry = KdlEntry::new_prop("name", "hello world");
+        assert_eq!(entry.to_kdl_literal_with_repr(), r#""hello world""#);
+        Ok(())
+    }
+
+    #[test]
+    fn to_inline_string() -> miette::Result<()> {
+        let cases = [
+            (KdlEntry::new("plain"), "plain"),
+            (KdlEntry::new("needs quotes\n"), r#""needs quotes\n""#),
+            (KdlEntry::new("true"), r#""true""#),
+            (KdlEntry::new(""), r#""""#),
+            (KdlEntry::new(-42), "-42"),
+            (KdlEntry::new(1.5), "1.5"),
+            (KdlEntry::new(f64::INFINITY), "#inf"),
+            (KdlEntry::new(f64::NAN), "#nan"),
+            (KdlEntry::new(true), "#true"),
+            (KdlEntry::new(KdlValue::Null), "#null"),
+            (KdlEntry::new_prop("key", "value"), "key=value"),
+            (KdlEntry::new_prop("a key", 1), r#""a key"=1"#),
+            (KdlEntry::new_prop("null", false), r#""null"=#false"#),
+            (
+                " name = (\"my type\")\"hi\" ".parse()?,
+                r#"name=("my type")hi"#,
+            ),
+            (" 0b1010 ".parse()?, "10"),
+            ("#\"raw\"# ".parse()?, "raw"),
+        ];
+        for (entry, expected) in cases {
+            let inline = entry.to_inline_string();
+            assert_eq!(inline, expected);
+            let node: KdlNode = format!("node {inline}").parse()?;
+            let parsed = &node.entries()[0];
+            assert_eq!(
+                parsed.name().map(|n| n.value()),
+                entry.name().map(|n| n.value())
+            );
+            assert_eq!(
+                parsed.ty().map(|t| t.value()),
+                entry.ty().map(|t| t.value())
+            );
+            if entry.value().as_float().is_some_and(f64::is_nan) {
+                assert!(parsed.value().as_float().unwrap().is_nan());
+            } else {
+                assert_eq!(parsed.value(), entry.value());
+            }
+        }
+        Ok(())
+    }
+
     #[test]
     fn new() {
         let entry = KdlEntry::new(42);
@@ -2,11 +2,10 @@ use std::{error::Error, fmt::Display, iter, sync::Arc};
 
 use miette::{Diagnostic, LabeledSpan, Severity, SourceSpan};
 
+use crate::{KdlDocument, KdlEntry, KdlIdentifier, KdlNode, KdlValue};
+
 #[cfg(doc)]
-use {
-    crate::KdlNode,
-    std::convert::{TryFrom, TryInto},
-};
+use std::convert::{TryFrom, TryInto};
 
 /// The toplevel `Error` type for KDL: this is returned when a KDL document
 /// failed to parse entirely.
@@ -42,6 +41,83 @@ pub struct KdlError {
     pub diagnostics: Vec<KdlDiagnostic>,
 }
 
+impl KdlError {
+    /// Creates an error with a single diagnostic pointing at `span` in
+    /// `input`. Used for failures that don't come out of the KDL parser
+    /// itself, such as when converting from or to other formats.
+    #[allow(unused)]
+    pub(crate) fn single(
+        input: &str,
+        span: impl Into<SourceSpan>,
+        message: impl Into<String>,
+        help: Option<&str>,
+    ) -> Self {
+        let input = Arc::new(String::from(input));
+        Self {
+            input: input.clone(),
+            diagnostics: vec![KdlDiagnostic {
+                input,
+                span: span.into(),
+                message: Some(message.into()),
+                label: None,
+                help: help.map(String::from),
+                severity: Severity::Error,
+            }],
+        }
+    }
+
+    /// Creates an error with a single diagnostic pointing at `span` in
+    /// `doc`'s text. Used for failures found while converting an
+    /// already-parsed document to another format.
+    #[allow(unused)]
+    pub(crate) fn in_document(
+        doc: &KdlDocument,
+        span: impl Into<SourceSpan>,
+        message: impl Into<String>,
+    ) -> Self {
+        Self::single(&doc.to_string(), span, message, None)
+    }
+}
+
+/// Document components that carry a [`SourceSpan`] when the `span` feature
+/// is enabled.
+pub(crate) trait Spanned {
+    /// Returns this component's span, or an empty span at the start of the
+    /// document if the `span` feature is disabled.
+    fn span_or_default(&self) -> SourceSpan;
+}
+
+macro_rules! impl_spanned {
+    ($($ty:ty),*) => {$(
+        impl Spanned for $ty {
+            #[cfg(feature = "span")]
+            fn span_or_default(&self) -> SourceSpan {
+                self.span()
+            }
+
+            #[cfg(not(feature = "span"))]
+            fn span_or_default(&self) -> SourceSpan {
+                (0..0).into()
+            }
+        }
+    )*};
+}
+
+impl_spanned!(KdlDocument, KdlNode, KdlEntry, KdlIdentifier);
+
+impl<T: Spanned> Spanned for &T {
+    fn span_or_default(&self) -> SourceSpan {
+        (*self).span_or_default()
+    }
+}
+
+/// Returns `item`'s span, or an empty span at the start of the document if
+/// the `span` feature is disabled.
+#[allow(unused)]
+pub(crate) fn span_or_default(item: &impl Spanned) -> SourceSpan {
+    item.span_or_default()
+}
+
 impl Display for KdlError {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         write!(f, "Failed to parse KDL document")
@@ -117,6 +193,158 @@ impl Diagnostic for KdlDiagnostic {
     }
 }
 
+/// Error returned by [`KdlDocument::topological_sort`](crate::KdlDocument::topological_sort)
+/// when the nodes' dependencies form a cycle.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KdlCycleError {
+    /// Names of the nodes in the cycle, in dependency order. The first name is
+    /// repeated at the end, so `["a", "b", "a"]` means `a` depends on `b`,
+    /// which depends on `a`.
+    pub cycle: Vec<String>,
+}
+
+impl Display for KdlCycleError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "Dependency cycle detected: {}", self.cycle.join(" -> "))
+    }
+}
+impl Error for KdlCycleError {}
+
+impl Diagnostic for KdlCycleError {
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new("Remove one of the dependencies in the cycle."))
+    }
+}
+
+/// A broken cross-reference found by
+/// [`KdlDocument::verify_referential_integrity`](crate::KdlDocument::verify_referential_integrity).
+#[derive(Debug, Clone, PartialEq)]
+pub struct KdlRefError {
+    /// Name of the node containing the reference.
+    pub node_name: String,
+    /// Index of the argument that should hold the reference.
+    pub arg_index: usize,
+    /// Name of the nodes the reference should point to.
+    pub target_node_name: String,
+    /// The referenced value, or `None` if the node has no argument at
+    /// `arg_index`.
+    pub reference: Option<KdlValue>,
+    /// Span of the offending argument, or of the whole node if the argument
+    /// is missing.
+    pub span: SourceSpan,
+}
+
+impl Display for KdlRefError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.reference {
+            Some(reference) => write!(
+                f,
+                "`{}` refers to {reference}, but there's no `{}` node with that name",
+                self.node_name, self.target_node_name
+            ),
+            None => write!(
+                f,
+                "`{}` is missing its reference to a `{}` node (argument {})",
+                self.node_name, self.target_node_name, self.arg_index
+            ),
+        }
+    }
+}
+impl Error for KdlRefError {}
+
+impl Diagnostic for KdlRefError {
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        let label = match self.reference {
+            Some(_) => "broken reference",
+            None => "missing reference",
+        };
+        Some(Box::new(iter::once(LabeledSpan::new_with_span(
+            Some(label.into()),
+            self.span,
+        ))))
+    }
+}
+
+/// A property name that doesn't follow the naming convention checked by
+/// [`KdlDocument::lint_property_casing`](crate::KdlDocument::lint_property_casing).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KdlLintWarning {
+    /// The offending property name.
+    pub name: String,
+    /// The property name converted to the expected convention.
+    pub suggestion: String,
+    /// Span of the offending property name.
+    pub span: SourceSpan,
+}
+
+impl Display for KdlLintWarning {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Property `{}` doesn't follow the naming convention",
+            self.name
+        )
+    }
+}
+impl Error for KdlLintWarning {}
+
+impl Diagnostic for KdlLintWarning {
+    fn severity(&self) -> Option<Severity> {
+        Some(Severity::Warning)
+    }
+
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        Some(Box::new(format!("Rename it to `{}`.", self.suggestion)))
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        Some(Box::new(iter::once(LabeledSpan::new_with_span(
+            Some("here".into()),
+            self.span,
+        ))))
+    }
+}
+
+/// A property that isn't allowed on its node, found by
+/// [`KdlDocument::assert_no_unknown_properties`](crate::KdlDocument::assert_no_unknown_properties).
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KdlUnknownPropError {
+    /// Name of the node with the unknown property.
+    pub node_name: String,
+    /// The unknown property's name.
+    pub name: String,
+    /// The closest allowed property name, if any were close enough.
+    pub suggestion: Option<String>,
+    /// Span of the node with the unknown property.
+    pub span: SourceSpan,
+}
+
+impl Display for KdlUnknownPropError {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(
+            f,
+            "Unknown property `{}` on node `{}`",
+            self.name, self.node_name
+        )
+    }
+}
+impl Error for KdlUnknownPropError {}
+
+impl Diagnostic for KdlUnknownPropError {
+    fn help<'a>(&'a self) -> Option<Box<dyn Display + 'a>> {
+        self.suggestion
+            .as_ref()
+            .map(|suggestion| Box::new(format!("Did you mean `{suggestion}`?")) as Box<dyn Display>)
+    }
+
+    fn labels(&self) -> Option<Box<dyn Iterator<Item = miette::LabeledSpan> + '_>> {
+        Some(Box::new(iter::once(LabeledSpan::new_with_span(
+            Some("unknown property".into()),
+            self.span,
+        ))))
+    }
+}
+
 #[cfg(feature = "v1")]
 impl From<kdlv1::KdlError> for KdlError {
     fn from(value: kdlv1::KdlError) -> Self {
@@ -0,0 +1,100 @@
+use crate::{KdlDocument, KdlError, KdlNode};
+
+/// Types that can be extracted out of a [`KdlDocument`].
+///
+/// This is a lightweight alternative to a full-blown (de)serialization
+/// framework: you implement it for your own configuration types, and then use
+/// [`KdlDocument::extract`] to pull them out of a parsed document.
+///
+/// # Examples
+///
+/// ```rust
+/// use std::sync::Arc;
+///
+/// use kdl::{KdlDiagnostic, KdlDocument, KdlError, KdlExtract};
+/// use miette::Severity;
+///
+/// struct Config {
+///     port: i128,
+/// }
+///
+/// impl KdlExtract<'_> for Config {
+///     fn extract(doc: &KdlDocument) -> Result<Self, KdlError> {
+///         let port = doc.get_arg("port").and_then(|v| v.as_integer());
+///         port.map(|port| Config { port }).ok_or_else(|| {
+///             let input = Arc::new(doc.to_string());
+///             KdlError {
+///                 input: input.clone(),
+///                 diagnostics: vec![KdlDiagnostic {
+///                     input,
+///                     span: (0..0).into(),
+///                     message: Some("Missing integer `port` node".into()),
+///                     label: None,
+///                     help: None,
+///                     severity: Severity::Error,
+///                 }],
+///             }
+///         })
+///     }
+/// }
+///
+/// let doc: KdlDocument = "port 8080".parse().unwrap();
+/// let config: Config = doc.extract().unwrap();
+/// assert_eq!(config.port, 8080);
+/// ```
+pub trait KdlExtract<'a>: Sized {
+    /// Extracts `Self` out of `doc`.
+    fn extract(doc: &'a KdlDocument) -> Result<Self, KdlError>;
+}
+
+/// Extracts a copy of the whole document.
+impl KdlExtract<'_> for KdlDocument {
+    fn extract(doc: &KdlDocument) -> Result<Self, KdlError> {
+        Ok(doc.clone())
+    }
+}
+
+/// Extracts a copy of the document's toplevel nodes.
+impl KdlExtract<'_> for Vec<KdlNode> {
+    fn extract(doc: &KdlDocument) -> Result<Self, KdlError> {
+        Ok(doc.nodes().to_vec())
+    }
+}
+
+/// Extracts the document's first toplevel node, if any.
+impl<'a> KdlExtract<'a> for Option<&'a KdlNode> {
+    fn extract(doc: &'a KdlDocument) -> Result<Self, KdlError> {
+        Ok(doc.nodes().first())
+    }
+}
+
+impl KdlDocument {
+    /// Extracts a `T` out of this document. See [`KdlExtract`] for details.
+    pub fn extract<'a, T: KdlExtract<'a>>(&'a self) -> Result<T, KdlError> {
+        T::extract(self)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn builtin_impls() -> miette::Result<()> {
+        let doc: KdlDocument = "foo 1\nbar 2".parse()?;
+
+        let whole: KdlDocument = doc.extract()?;
+        assert_eq!(whole, doc);
+
+        let nodes: Vec<KdlNode> = doc.extract()?;
+        assert_eq!(nodes, doc.nodes());
+
+        let first: Option<&KdlNode> = doc.extract()?;
+        assert!(std::ptr::eq(first.unwrap(), &doc.nodes()[0]));
+
+        let empty = KdlDocument::new();
+        let first: Option<&KdlNode> = empty.extract()?;
+        assert!(first.is_none());
+        Ok(())
+    }
+}
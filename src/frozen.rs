@@ -0,0 +1,100 @@
+use std::{fmt::Display, ops::Deref};
+
+use crate::KdlDocument;
+
+/// A read-only [`KdlDocument`], created with [`KdlDocument::freeze`].
+///
+/// All of `KdlDocument`'s read APIs are available through [`Deref`], but none
+/// of the mutating ones are, so a frozen document can be handed out (or shared
+/// across threads behind a plain [`Arc`](std::sync::Arc)) without anyone being
+/// able to change it. Use [`FrozenKdlDocument::thaw`] to get a mutable
+/// document back.
+///
+/// # Examples
+///
+/// ```rust
+/// # use kdl::KdlDocument;
+/// let doc: KdlDocument = "port 8080".parse().unwrap();
+/// let frozen = doc.freeze();
+/// assert_eq!(frozen.get_arg("port"), Some(&8080.into()));
+///
+/// let mut doc = frozen.thaw();
+/// doc.nodes_mut().clear();
+/// ```
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub struct FrozenKdlDocument(KdlDocument);
+
+impl FrozenKdlDocument {
+    /// Unfreezes this document, making it mutable again.
+    pub fn thaw(self) -> KdlDocument {
+        self.0
+    }
+}
+
+impl KdlDocument {
+    /// Freezes this document, preventing any further changes to it. See
+    /// [`FrozenKdlDocument`].
+    pub fn freeze(self) -> FrozenKdlDocument {
+        FrozenKdlDocument(self)
+    }
+}
+
+impl Deref for FrozenKdlDocument {
+    type Target = KdlDocument;
+
+    fn deref(&self) -> &Self::Target {
+        &self.0
+    }
+}
+
+impl AsRef<KdlDocument> for FrozenKdlDocument {
+    fn as_ref(&self) -> &KdlDocument {
+        &self.0
+    }
+}
+
+impl From<KdlDocument> for FrozenKdlDocument {
+    fn from(doc: KdlDocument) -> Self {
+        doc.freeze()
+    }
+}
+
+impl From<FrozenKdlDocument> for KdlDocument {
+    fn from(frozen: FrozenKdlDocument) -> Self {
+        frozen.thaw()
+    }
+}
+
+impl Display for FrozenKdlDocument {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        self.0.fmt(f)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use std::sync::Arc;
+
+    use super::*;
+
+    #[test]
+    fn freeze_and_thaw() -> miette::Result<()> {
+        let src = "server host=localhost {\n    port 8080\n}\n";
+        let doc: KdlDocument = src.parse()?;
+        let frozen = Arc::new(doc.clone().freeze());
+
+        let handle = {
+            let frozen = Arc::clone(&frozen);
+            std::thread::spawn(move || frozen.get("server").unwrap()["host"].clone())
+        };
+        assert_eq!(handle.join().unwrap(), "localhost".into());
+        assert_eq!(frozen.to_string(), src);
+        assert_eq!(frozen.nodes().len(), 1);
+
+        let mut thawed = Arc::try_unwrap(frozen).unwrap().thaw();
+        assert_eq!(thawed, doc);
+        thawed.nodes_mut().clear();
+        assert!(thawed.nodes().is_empty());
+        Ok(())
+    }
+}
@@ -0,0 +1,315 @@
+use crate::{error::span_or_default, lines::lines, KdlDocument, KdlError, KdlNode, KdlValue};
+
+/// The fields of an `/etc/fstab` line, in order.
+const FIELDS: [&str; 6] = ["device", "mountpoint", "fstype", "options", "dump", "pass"];
+
+impl KdlDocument {
+    /// Parses a Linux [`/etc/fstab`](https://man7.org/linux/man-pages/man5/fstab.5.html)
+    /// file into a document.
+    ///
+    /// Each `device mountpoint fstype options dump pass` line becomes a
+    /// `mount` node with `device`, `mountpoint`, `fstype`, `options`, `dump`
+    /// and `pass` properties. `options` is kept as a single comma-separated
+    /// string, like `defaults,noatime`, and `dump` and `pass` become
+    /// integers:
+    ///
+    /// ```kdl
+    /// mount device="UUID=1234-abcd" mountpoint="/" fstype=ext4 options="defaults,noatime" dump=0 pass=1
+    /// ```
+    ///
+    /// `dump` and `pass` can be left out of a line, in which case they're
+    /// left out of the node too. Octal escapes like `\040` (for a space) are
+    /// decoded. Blank lines and lines starting with `#` are skipped.
+    ///
+    /// This is only available with the `fstab` feature.
+    ///
+    /// Fails if a line doesn't have between four and six fields, or if
+    /// `dump` or `pass` isn't a number.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_fstab("/dev/sda2 none swap sw 0 0\n").unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "mount device=\"/dev/sda2\" mountpoint=none fstype=swap options=sw dump=0 pass=0\n"
+    /// );
+    /// assert_eq!(doc.to_fstab().unwrap(), "/dev/sda2 none swap sw 0 0\n");
+    /// ```
+    pub fn from_fstab(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        for (start, line) in lines(s) {
+            let trimmed = line.trim_start();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let fields = split_fields(line);
+            if !(4..=FIELDS.len()).contains(&fields.len()) {
+                return Err(KdlError::single(
+                    s,
+                    (start, line.len()),
+                    format!(
+                        "Expected 4 to 6 whitespace-separated fields, found {}",
+                        fields.len()
+                    ),
+                    Some("Lines look like `device mountpoint fstype options dump pass`. Escape literal spaces as `\\040`."),
+                ));
+            }
+            let mut node = KdlNode::new("mount");
+            for (name, (field_start, field)) in FIELDS.iter().zip(fields) {
+                let value = if matches!(*name, "dump" | "pass") {
+                    match field.parse::<i128>() {
+                        Ok(value) => KdlValue::Integer(value),
+                        Err(_) => {
+                            return Err(KdlError::single(
+                                s,
+                                (start + field_start, field.len()),
+                                format!("Expected `{name}` to be a number"),
+                                None,
+                            ));
+                        }
+                    }
+                } else {
+                    KdlValue::String(unescape(field))
+                };
+                node.insert(*name, value);
+            }
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+
+    /// Converts a document into an `/etc/fstab` file, with one line per
+    /// `mount` node. This is the reverse of [`KdlDocument::from_fstab`].
+    ///
+    /// A missing `options` property is written as `defaults`, and missing
+    /// `dump` and `pass` properties as `0`. Spaces, tabs, newlines and
+    /// backslashes in values are written as octal escapes.
+    ///
+    /// Fails if there are nodes other than `mount` nodes, if a node has
+    /// arguments, children, or properties other than the six fstab fields,
+    /// if it doesn't have a `device`, `mountpoint` and `fstype`, or if a
+    /// value isn't a non-empty string (or an integer, for `dump` and
+    /// `pass`).
+    pub fn to_fstab(&self) -> Result<String, KdlError> {
+        let mut out = String::new();
+        for node in self.nodes() {
+            if node.name().value() != "mount" {
+                return Err(KdlError::in_document(
+                    self,
+                    span_or_default(node),
+                    "Only `mount` nodes can be written to an fstab file",
+                ));
+            }
+            if node.children().is_some_and(|c| !c.nodes().is_empty()) {
+                return Err(KdlError::in_document(
+                    self,
+                    span_or_default(node),
+                    "Mounts can't have children",
+                ));
+            }
+            for entry in node.iter() {
+                let known = entry
+                    .name()
+                    .is_some_and(|name| FIELDS.contains(&name.value()));
+                if !known {
+                    return Err(KdlError::in_document(
+                        self,
+                        span_or_default(entry),
+                        "Mounts can only have device, mountpoint, fstype, options, dump and pass properties",
+                    ));
+                }
+            }
+            let mut fields = Vec::with_capacity(FIELDS.len());
+            for name in FIELDS {
+                let numeric = matches!(name, "dump" | "pass");
+                let field = match node.entry(name) {
+                    Some(entry) => match entry.value() {
+                        KdlValue::Integer(i) if numeric => i.to_string(),
+                        KdlValue::String(s) if !numeric && !s.is_empty() => escape(s),
+                        _ => {
+                            let expected = if numeric {
+                                "an integer"
+                            } else {
+                                "a non-empty string"
+                            };
+                            return Err(KdlError::in_document(
+                                self,
+                                span_or_default(entry),
+                                format!("`{name}` must be {expected}"),
+                            ));
+                        }
+                    },
+                    None if name == "options" => "defaults".into(),
+                    None if numeric => "0".into(),
+                    None => {
+                        return Err(KdlError::in_document(
+                            self,
+                            span_or_default(node),
+                            format!("Mounts must have a `{name}`"),
+                        ))
+                    }
+                };
+                fields.push(field);
+            }
+            out.push_str(&fields.join(" "));
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+/// Splits a line on whitespace, along with each field's offset in the line.
+fn split_fields(line: &str) -> Vec<(usize, &str)> {
+    let mut fields = Vec::new();
+    let mut field_start = None;
+    for (idx, c) in line.char_indices() {
+        match (c.is_whitespace(), field_start) {
+            (true, Some(start)) => {
+                fields.push((start, &line[start..idx]));
+                field_start = None;
+            }
+            (false, None) => field_start = Some(idx),
+            _ => {}
+        }
+    }
+    if let Some(start) = field_start {
+        fields.push((start, &line[start..]));
+    }
+    fields
+}
+
+/// Decodes `\NNN` octal escapes, which fstab uses for whitespace and other
+/// special characters.
+fn unescape(field: &str) -> String {
+    let bytes = field.as_bytes();
+    let mut out = Vec::with_capacity(bytes.len());
+    let mut idx = 0;
+    while idx < bytes.len() {
+        let escape = bytes.get(idx + 1..idx + 4).filter(|digits| {
+            bytes[idx] == b'\\' && digits.iter().all(|d| (b'0'..=b'7').contains(d))
+        });
+        match escape
+            .and_then(|digits| u8::from_str_radix(std::str::from_utf8(digits).unwrap(), 8).ok())
+        {
+            Some(byte) => {
+                out.push(byte);
+                idx += 4;
+            }
+            None => {
+                out.push(bytes[idx]);
+                idx += 1;
+            }
+        }
+    }
+    String::from_utf8_lossy(&out).into_owned()
+}
+
+fn escape(field: &str) -> String {
+    let mut out = String::with_capacity(field.len());
+    for c in field.chars() {
+        match c {
+            ' ' | '\t' | '\n' | '\\' => out.push_str(&format!("\\{:03o}", c as u32)),
+            c => out.push(c),
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    const FSTAB: &str = "# <file system> <mount point> <type> <options> <dump> <pass>
+UUID=1234-abcd  /               ext4    defaults,noatime  0  1
+/dev/sda2       none            swap    sw                0  0
+
+  # NFS share.
+nas.local:/export/media /mnt/My\\040Media nfs rw,hard,timeo=600,_netdev 0 0
+/srv/data\t/var/www/data\tnone\tbind
+";
+
+    #[test]
+    fn from_fstab() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_fstab,
+            FSTAB,
+            r#"
+mount device="UUID=1234-abcd" mountpoint="/" fstype=ext4 options="defaults,noatime" dump=0 pass=1
+mount device="/dev/sda2" mountpoint=none fstype=swap options=sw dump=0 pass=0
+mount device="nas.local:/export/media" mountpoint="/mnt/My Media" fstype=nfs options="rw,hard,timeo=600,_netdev" dump=0 pass=0
+mount device="/srv/data" mountpoint="/var/www/data" fstype=none options=bind
+"#,
+        )?;
+        assert!(KdlDocument::from_fstab("").unwrap().nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn to_fstab() -> miette::Result<()> {
+        let doc = KdlDocument::from_fstab(FSTAB)?;
+        assert_eq!(
+            doc.to_fstab()?,
+            "UUID=1234-abcd / ext4 defaults,noatime 0 1
+/dev/sda2 none swap sw 0 0
+nas.local:/export/media /mnt/My\\040Media nfs rw,hard,timeo=600,_netdev 0 0
+/srv/data /var/www/data none bind 0 0
+"
+        );
+        let fstab = doc.to_fstab()?;
+        assert_eq!(
+            KdlDocument::from_fstab(&fstab)?.to_fstab()?,
+            fstab,
+            "round trip"
+        );
+
+        let doc: KdlDocument =
+            r#"mount device="/dev/sdb1" mountpoint="/mnt/back\\slash" fstype=vfat"#.parse()?;
+        assert_eq!(
+            doc.to_fstab()?,
+            "/dev/sdb1 /mnt/back\\134slash vfat defaults 0 0\n"
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> miette::Result<()> {
+        let err = KdlDocument::from_fstab("/dev/sda1 /\n").unwrap_err();
+        assert_eq!(
+            err.diagnostics[0].message.as_deref(),
+            Some("Expected 4 to 6 whitespace-separated fields, found 2")
+        );
+        assert!(KdlDocument::from_fstab("/dev/sda1 / ext4 defaults 0 1 extra\n").is_err());
+        let input = "/dev/sda1 / ext4 defaults zero 1\n";
+        let err = KdlDocument::from_fstab(input).unwrap_err();
+        assert_eq!(err.diagnostics[0].span.offset(), 26);
+
+        for bad in [
+            r#"swap device="/dev/sda2""#,
+            r#"mount mountpoint="/" fstype=ext4"#,
+            r#"mount device="/dev/sda1" fstype=ext4"#,
+            r#"mount device="/dev/sda1" mountpoint="/""#,
+            r#"mount device="" mountpoint="/" fstype=ext4"#,
+            r#"mount device="/dev/sda1" mountpoint="/" fstype=ext4 dump="0""#,
+            r#"mount device="/dev/sda1" mountpoint="/" fstype=ext4 options=#true"#,
+            r#"mount "/dev/sda1" mountpoint="/" fstype=ext4"#,
+            r#"mount device="/dev/sda1" mountpoint="/" fstype=ext4 extra=1"#,
+            "mount device=\"/dev/sda1\" mountpoint=\"/\" fstype=ext4 {\n    child\n}",
+        ] {
+            let doc: KdlDocument = bad.parse()?;
+            assert!(doc.to_fstab().is_err(), "{bad:?} should fail");
+        }
+        Ok(())
+    }
+
+    #[test]
+    fn escapes() {
+        assert_eq!(unescape("a\\040b\\011c\\134d"), "a b\tc\\d");
+        assert_eq!(unescape("\\04"), "\\04");
+        assert_eq!(unescape("\\x41\\"), "\\x41\\");
+        assert_eq!(escape("a b\tc\\d"), "a\\040b\\011c\\134d");
+    }
+}
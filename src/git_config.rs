@@ -0,0 +1,323 @@
+use crate::{KdlDocument, KdlEntry, KdlError, KdlNode, KdlValue};
+
+impl KdlDocument {
+    /// Parses a [Git configuration
+    /// file](https://git-scm.com/docs/git-config#_configuration_file) (such
+    /// as `.git/config` or `~/.gitconfig`) into a document.
+    ///
+    /// Each `[section]` becomes a toplevel node named after the section, and
+    /// a `[section "subsection"]` header (or the older `[section.subsection]`
+    /// form) gives the node the subsection as its only argument. Headers
+    /// that repeat an earlier section add to that section's node.
+    ///
+    /// Variables become properties on their section's node. Variables that
+    /// appear more than once, like a remote's `fetch` refspecs, become one
+    /// property per value, in order, so the last one wins just like it does
+    /// for `git config --get`. A variable with no `= value` at all is
+    /// `#true`.
+    ///
+    /// Values are unquoted and unescaped the way Git does it, including
+    /// lines continued with a trailing backslash. Unquoted `true` and `false`
+    /// become booleans, and unquoted decimal integers become integers, with
+    /// `k`, `m` and `g` suffixes multiplied out (so `1k` is `1024`).
+    /// Everything else is a string. Comments starting with `#` or `;` are
+    /// skipped, both on their own lines and after a header or value.
+    ///
+    /// Fails if a header or variable is malformed, if a variable comes before
+    /// the first section, or if a value has an unterminated quote or an
+    /// unknown escape sequence.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_git_config(
+    ///     "[core]\n\tbare = false\n[remote \"origin\"]\n\turl = https://example.com/repo.git\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "core bare=#false\nremote origin url=\"https://example.com/repo.git\"\n"
+    /// );
+    /// ```
+    pub fn from_git_config(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        let mut section: Option<usize> = None;
+        let mut pos = 0;
+        while pos < s.len() {
+            let line_end = s[pos..].find('\n').map_or(s.len(), |i| pos + i);
+            let line = &s[pos..line_end];
+            let trimmed = line.trim();
+            let start = pos + (line.len() - line.trim_start().len());
+            let error = |message: &str| KdlError::single(s, (start, trimmed.len()), message, None);
+            pos = line_end + 1;
+            if trimmed.is_empty() || trimmed.starts_with(['#', ';']) {
+                continue;
+            }
+
+            if trimmed.starts_with('[') {
+                let (name, subsection) = parse_header(trimmed)
+                    .ok_or_else(|| error("Invalid Git config section header"))?;
+                let existing = doc.nodes().iter().position(|node| {
+                    node.name().value() == name
+                        && node.get(0).and_then(|arg| arg.as_string()) == subsection.as_deref()
+                });
+                section = Some(existing.unwrap_or_else(|| {
+                    let mut node = KdlNode::new(name);
+                    if let Some(subsection) = subsection {
+                        node.push(subsection);
+                    }
+                    doc.nodes_mut().push(node);
+                    doc.nodes().len() - 1
+                }));
+                continue;
+            }
+
+            let key_len = trimmed
+                .find(|c: char| !(c.is_ascii_alphanumeric() || c == '-'))
+                .unwrap_or(trimmed.len());
+            let key = &trimmed[..key_len];
+            if !key.starts_with(|c: char| c.is_ascii_alphabetic()) {
+                return Err(error("Invalid Git config variable name"));
+            }
+            let section =
+                section.ok_or_else(|| error("Git config variables must be inside a [section]"))?;
+            let rest = trimmed[key_len..].trim_start();
+            let value = if rest.is_empty() || rest.starts_with(['#', ';']) {
+                KdlValue::Bool(true)
+            } else if rest.starts_with('=') {
+                let value_start = start + (trimmed.len() - rest.len()) + 1;
+                let (value, quoted, end) = parse_value(s, value_start)
+                    .map_err(|(offset, message)| KdlError::single(s, (offset, 1), message, None))?;
+                pos = end;
+                if quoted {
+                    KdlValue::String(value)
+                } else if value.eq_ignore_ascii_case("true") {
+                    KdlValue::Bool(true)
+                } else if value.eq_ignore_ascii_case("false") {
+                    KdlValue::Bool(false)
+                } else {
+                    parse_integer(&value).map_or(KdlValue::String(value), KdlValue::Integer)
+                }
+            } else {
+                return Err(error("Expected a `key = value` variable"));
+            };
+            doc.nodes_mut()[section].push(KdlEntry::new_prop(key, value));
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Parses a `[section]`, `[section "subsection"]` or `[section.subsection]`
+/// header line, which may be followed by a comment.
+fn parse_header(line: &str) -> Option<(&str, Option<String>)> {
+    let header = line.strip_prefix('[')?;
+    let name_len = header
+        .find(|c: char| !(c.is_ascii_alphanumeric() || matches!(c, '-' | '.')))
+        .unwrap_or(header.len());
+    let name = &header[..name_len];
+    if name.is_empty() {
+        return None;
+    }
+    let mut rest = header[name_len..].chars();
+    let mut subsection = None;
+    match rest.next()? {
+        ']' => {
+            if let Some((name, subsection)) = name.split_once('.') {
+                return (!name.is_empty() && !subsection.is_empty())
+                    .then(|| (name, Some(subsection.into())));
+            }
+        }
+        c if c.is_whitespace() && !name.contains('.') => {
+            let mut rest_str = rest.as_str().trim_start().chars();
+            if rest_str.next()? != '"' {
+                return None;
+            }
+            let mut value = String::new();
+            loop {
+                match rest_str.next()? {
+                    '"' => break,
+                    // Git drops the backslash from any other escape here.
+                    '\\' => value.push(rest_str.next()?),
+                    '\n' => return None,
+                    c => value.push(c),
+                }
+            }
+            if rest_str.next()? != ']' {
+                return None;
+            }
+            subsection = Some(value);
+            rest = rest_str;
+        }
+        _ => return None,
+    }
+    let trailing = rest.as_str().trim_start();
+    (trailing.is_empty() || trailing.starts_with(['#', ';'])).then_some((name, subsection))
+}
+
+/// Parses the value starting at `start` (right after the `=`), returning it
+/// along with whether any of it was quoted and the offset right after the
+/// line it ended on. Errors are the offset and message to report.
+fn parse_value(s: &str, start: usize) -> Result<(String, bool, usize), (usize, &'static str)> {
+    let mut value = String::new();
+    // Length of `value` without any trailing, unquoted whitespace.
+    let mut len = 0;
+    let mut in_quotes = false;
+    let mut quoted = false;
+    let mut chars = s[start..].char_indices().map(|(i, c)| (start + i, c));
+    let mut end = s.len();
+    while let Some((idx, c)) = chars.next() {
+        match c {
+            '\n' if in_quotes => return Err((idx, "Unterminated quote in Git config value")),
+            '\n' => {
+                end = idx + 1;
+                break;
+            }
+            '#' | ';' if !in_quotes => {
+                end = s[idx..].find('\n').map_or(s.len(), |i| idx + i + 1);
+                break;
+            }
+            '"' => {
+                in_quotes = !in_quotes;
+                quoted = true;
+            }
+            '\\' => {
+                match chars.next() {
+                    Some((_, '\n')) => {}
+                    Some((_, '\r')) if s[idx + 2..].starts_with('\n') => {
+                        chars.next();
+                    }
+                    Some((_, 'n')) => value.push('\n'),
+                    Some((_, 't')) => value.push('\t'),
+                    Some((_, 'b')) => value.push('\u{8}'),
+                    Some((_, c @ ('"' | '\\'))) => value.push(c),
+                    _ => return Err((idx, "Invalid escape sequence in Git config value")),
+                }
+                len = value.len();
+            }
+            c if c.is_whitespace() && !in_quotes => {
+                if !value.is_empty() {
+                    value.push(c);
+                }
+            }
+            c => {
+                value.push(c);
+                len = value.len();
+            }
+        }
+    }
+    if in_quotes {
+        return Err((s.len() - 1, "Unterminated quote in Git config value"));
+    }
+    value.truncate(len);
+    Ok((value, quoted, end))
+}
+
+/// Parses a decimal integer with an optional `k`, `m` or `g` suffix. Numbers
+/// with leading zeros are left alone, since Git would read them as octal.
+fn parse_integer(value: &str) -> Option<i128> {
+    let (digits, multiplier) = match value.as_bytes().last()?.to_ascii_lowercase() {
+        b'k' => (&value[..value.len() - 1], 1 << 10),
+        b'm' => (&value[..value.len() - 1], 1 << 20),
+        b'g' => (&value[..value.len() - 1], 1 << 30),
+        _ => (value, 1),
+    };
+    let unsigned = digits.strip_prefix(['-', '+']).unwrap_or(digits);
+    if unsigned.is_empty()
+        || !unsigned.bytes().all(|b| b.is_ascii_digit())
+        || (unsigned.len() > 1 && unsigned.starts_with('0'))
+    {
+        return None;
+    }
+    digits.parse::<i128>().ok()?.checked_mul(multiplier)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn core() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_git_config,
+            r#"# This is a comment
+; and so is this
+[core]
+	repositoryformatversion = 0
+	filemode = true
+	bare = FALSE ; trailing comment
+	logallrefupdates
+	packedGitLimit = 256m
+	bigFileThreshold = -2k
+	sharedRepository = 0660
+	editor = "vim -c \"set ft=gitcommit\""  # quoted
+	pager = less \
+		-R
+	whitespace = "  spaced  "  trailing-tab,space
+[core]
+	autocrlf = input
+"#,
+            r##"
+core repositoryformatversion=0 filemode=#true bare=#false logallrefupdates=#true packedGitLimit=268435456 bigFileThreshold=-2048 sharedRepository="0660" editor="vim -c \"set ft=gitcommit\"" pager="less \t\t-R" whitespace="  spaced    trailing-tab,space" autocrlf=input
+"##,
+        )
+    }
+
+    #[test]
+    fn remote() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_git_config,
+            r#"[remote "origin"]
+	url = git@github.com:kdl-org/kdl-rs.git
+	fetch = +refs/heads/*:refs/remotes/origin/*
+	fetch = +refs/tags/*:refs/tags/*
+[remote "weird \"name\" \x"]
+	url = "/srv/repo.git"
+[url "https://example.com/"]
+	insteadOf = ex:
+"#,
+            r#"
+remote origin url="git@github.com:kdl-org/kdl-rs.git" fetch="+refs/heads/*:refs/remotes/origin/*" fetch="+refs/tags/*:refs/tags/*"
+remote "weird \"name\" x" url="/srv/repo.git"
+url "https://example.com/" insteadOf="ex:"
+"#,
+        )
+    }
+
+    #[test]
+    fn branch() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_git_config,
+            "[branch \"main\"]  # the default branch\r\n\tremote = origin\r\n\tmerge = refs/heads/main\r\n\trebase = \"true\"\r\n[branch \"feature/kdl\"]\n\tremote = origin\n[branch.legacy]\n\tremote = upstream\n",
+            r#"
+branch main remote=origin merge="refs/heads/main" rebase="true"
+branch "feature/kdl" remote=origin
+branch legacy remote=upstream
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "name = value\n",
+            "[core\n",
+            "[]\n",
+            "[remote origin]\n",
+            "[remote \"origin\"\n",
+            "[core] junk\n",
+            "[core]\n\t1key = value\n",
+            "[core]\n\tkey: value\n",
+            "[core]\n\tkey = \"unterminated\n",
+            "[core]\n\tkey = \"unterminated",
+            "[core]\n\tkey = bad \\q escape\n",
+        ] {
+            assert!(
+                KdlDocument::from_git_config(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+    }
+}
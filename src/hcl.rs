@@ -0,0 +1,575 @@
+use std::fmt::Write as _;
+
+use hcl_edit::{
+    expr::{Expression, ObjectKey},
+    structure::{Block, Body, Structure},
+    Ident,
+};
+use miette::SourceSpan;
+
+use crate::{error::span_or_default, KdlDocument, KdlEntry, KdlError, KdlNode, KdlValue};
+
+const INDENT: &str = "  ";
+
+impl KdlDocument {
+    /// Writes this document out as
+    /// [HCL](https://github.com/hashicorp/hcl/blob/main/hclsyntax/spec.md),
+    /// as used by Terraform and other HashiCorp tools.
+    ///
+    /// * Nodes with children, or with properties, become blocks. Their
+    ///   arguments become the block's labels, their properties become
+    ///   attributes inside the block, and their children become the rest of
+    ///   the block's body.
+    /// * Nodes with only arguments become attributes: `region "us-east-1"`
+    ///   becomes `region = "us-east-1"`, and nodes with more than one argument
+    ///   become lists.
+    /// * Nodes annotated with `(object)` or `(array)` become attributes with
+    ///   an object or list value, built from their properties, arguments, and
+    ///   children.
+    /// * String values annotated with `(hcl)` are written out as raw HCL
+    ///   expressions, so `ami=(hcl)"var.ami"` becomes `ami = var.ami`.
+    ///
+    /// Fails if a node or property name isn't a valid HCL identifier, if a
+    /// block label isn't a string, if a value is a non-finite float, or if an
+    /// `(hcl)` expression is invalid.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = r#"
+    /// resource aws_instance web ami=(hcl)"var.ami" instance_type="t3.micro" {
+    ///     (object)tags Name=web
+    /// }
+    /// "#.parse().unwrap();
+    /// assert_eq!(doc.to_hcl().unwrap(), r#"resource "aws_instance" "web" {
+    ///   ami = var.ami
+    ///   instance_type = "t3.micro"
+    ///   tags = {
+    ///     Name = "web"
+    ///   }
+    /// }
+    /// "#);
+    /// ```
+    pub fn to_hcl(&self) -> Result<String, KdlError> {
+        let mut out = String::new();
+        write_body(&mut out, self, self.nodes(), 0)?;
+        Ok(out)
+    }
+
+    /// Parses [HCL](https://github.com/hashicorp/hcl/blob/main/hclsyntax/spec.md)
+    /// into a document. This is the reverse of [`KdlDocument::to_hcl`]:
+    ///
+    /// * Blocks become nodes with children, with the block labels as string
+    ///   arguments.
+    /// * Attributes inside blocks become properties on the block's node, and
+    ///   toplevel attributes become nodes with a single argument.
+    /// * Attributes with object or list values become child nodes annotated
+    ///   with `(object)` or `(array)`.
+    /// * Any other expression, like `var.region` or `"${local.prefix}-web"`,
+    ///   is kept as a string annotated with `(hcl)`.
+    ///
+    /// Comments are not preserved.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_hcl(r#"
+    /// provider "aws" {
+    ///   region = var.region
+    /// }
+    /// "#).unwrap();
+    /// let provider = doc.get("provider").unwrap();
+    /// assert_eq!(provider.get(0), Some(&"aws".into()));
+    /// let region = provider.entry("region").unwrap();
+    /// assert_eq!(region.ty().unwrap().value(), "hcl");
+    /// assert_eq!(region.value(), &"var.region".into());
+    /// ```
+    pub fn from_hcl(s: &str) -> Result<KdlDocument, KdlError> {
+        let body = hcl_edit::parser::parse_body(s).map_err(|e| {
+            KdlError::single(
+                s,
+                (e.location().offset(), 0),
+                "Invalid HCL",
+                Some(e.message()),
+            )
+        })?;
+        let mut doc = KdlDocument::new();
+        for structure in body.iter() {
+            let node = match structure {
+                Structure::Block(block) => block_to_node(block),
+                Structure::Attribute(attr) => value_to_node(attr.key.as_str(), &attr.value),
+            };
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+fn block_to_node(block: &Block) -> KdlNode {
+    let mut node = KdlNode::new(block.ident.as_str());
+    for label in &block.labels {
+        node.push(label.as_str());
+    }
+    let children = body_to_props(&mut node, &block.body);
+    *node.ensure_children() = children;
+    node
+}
+
+/// Adds the attributes in `body` to `node` as properties, and returns the
+/// rest of the body as a document.
+fn body_to_props(node: &mut KdlNode, body: &Body) -> KdlDocument {
+    let mut children = KdlDocument::new();
+    for structure in body.iter() {
+        match structure {
+            Structure::Block(block) => children.nodes_mut().push(block_to_node(block)),
+            Structure::Attribute(attr) => match literal(&attr.value) {
+                Some(mut entry) => {
+                    entry.set_name(Some(attr.key.as_str()));
+                    node.push(entry);
+                }
+                None => children
+                    .nodes_mut()
+                    .push(value_to_node(attr.key.as_str(), &attr.value)),
+            },
+        }
+    }
+    children
+}
+
+/// Converts a non-object, non-array expression into an entry.
+fn literal(expr: &Expression) -> Option<KdlEntry> {
+    let value = match expr {
+        Expression::Array(_) | Expression::Object(_) => return None,
+        Expression::Null(_) => KdlValue::Null,
+        Expression::Bool(b) => KdlValue::Bool(*b.value()),
+        Expression::String(s) => KdlValue::String(s.value().clone()),
+        Expression::Number(n) => {
+            let n = n.value();
+            match (n.as_i64(), n.as_u64(), n.as_f64()) {
+                (Some(i), _, _) => KdlValue::Integer(i.into()),
+                (_, Some(u), _) => KdlValue::Integer(u.into()),
+                (_, _, Some(f)) => KdlValue::Float(f),
+                // Keep numbers that don't fit in any of those as written.
+                _ => KdlValue::String(n.to_string()),
+            }
+        }
+        expr => {
+            let mut entry = KdlEntry::new(expr.to_string().trim());
+            entry.set_ty("hcl");
+            return Some(entry);
+        }
+    };
+    Some(KdlEntry::new(value))
+}
+
+/// Converts an object or array expression into an `(object)` or `(array)`
+/// node. Anything else becomes a node with a single argument.
+fn value_to_node(name: &str, expr: &Expression) -> KdlNode {
+    let mut node = KdlNode::new(name);
+    match expr {
+        Expression::Object(object) => {
+            node.set_ty("object");
+            for (key, value) in object.iter() {
+                let key = match key {
+                    ObjectKey::Ident(ident) => ident.as_str().to_string(),
+                    ObjectKey::Expression(Expression::String(s)) => s.value().clone(),
+                    ObjectKey::Expression(expr) => expr.to_string().trim().to_string(),
+                };
+                match literal(value.expr()) {
+                    Some(mut entry) => {
+                        entry.set_name(Some(key));
+                        node.push(entry);
+                    }
+                    None => node
+                        .ensure_children()
+                        .nodes_mut()
+                        .push(value_to_node(&key, value.expr())),
+                }
+            }
+        }
+        Expression::Array(array) => {
+            node.set_ty("array");
+            let nested = array.iter().any(|item| literal(item).is_none());
+            for item in array.iter() {
+                if nested {
+                    node.ensure_children()
+                        .nodes_mut()
+                        .push(value_to_node("-", item));
+                } else {
+                    node.push(literal(item).unwrap());
+                }
+            }
+        }
+        expr => node.push(literal(expr).unwrap()),
+    }
+    node
+}
+
+fn write_body(
+    out: &mut String,
+    doc: &KdlDocument,
+    nodes: &[KdlNode],
+    depth: usize,
+) -> Result<(), KdlError> {
+    let indent = INDENT.repeat(depth);
+    let mut prev_was_block = false;
+    for (idx, node) in nodes.iter().enumerate() {
+        let name = ident(doc, node.name().value(), span_or_default(node))?;
+        let ty = node.ty().map(|ty| ty.value());
+        let has_children = node.children().is_some();
+        let has_props = node.iter().any(|e| e.name().is_some());
+        let is_block = !matches!(ty, Some("object" | "array")) && (has_children || has_props);
+        if idx > 0 && depth == 0 && (is_block || prev_was_block) {
+            out.push('\n');
+        }
+        prev_was_block = is_block;
+        if !is_block {
+            let value = node_value(doc, node, depth, true)?;
+            writeln!(out, "{indent}{name} = {value}").unwrap();
+            continue;
+        }
+        write!(out, "{indent}{name}").unwrap();
+        for arg in node.iter().filter(|e| e.name().is_none()) {
+            match arg.value() {
+                KdlValue::String(label) => write!(out, " {}", quote(label)).unwrap(),
+                _ => {
+                    return Err(KdlError::in_document(
+                        doc,
+                        span_or_default(arg),
+                        "HCL block labels must be strings",
+                    ))
+                }
+            }
+        }
+        let children = node.children().map(|c| c.nodes()).unwrap_or_default();
+        if !has_props && children.is_empty() {
+            out.push_str(" {}\n");
+            continue;
+        }
+        out.push_str(" {\n");
+        write_props(out, doc, node, depth + 1)?;
+        write_body(out, doc, children, depth + 1)?;
+        writeln!(out, "{indent}}}").unwrap();
+    }
+    Ok(())
+}
+
+fn write_props(
+    out: &mut String,
+    doc: &KdlDocument,
+    node: &KdlNode,
+    depth: usize,
+) -> Result<(), KdlError> {
+    let indent = INDENT.repeat(depth);
+    for prop in node.iter() {
+        if let Some(name) = prop.name() {
+            let key = ident(doc, name.value(), span_or_default(prop))?;
+            let value = entry_value(doc, prop)?;
+            writeln!(out, "{indent}{key} = {value}").unwrap();
+        }
+    }
+    Ok(())
+}
+
+/// Writes `node` out as an HCL value. With `lists` set, plain nodes with more
+/// than one argument are written as lists.
+fn node_value(
+    doc: &KdlDocument,
+    node: &KdlNode,
+    depth: usize,
+    lists: bool,
+) -> Result<String, KdlError> {
+    let indent = INDENT.repeat(depth);
+    let args = node
+        .iter()
+        .filter(|e| e.name().is_none())
+        .collect::<Vec<_>>();
+    let children = node.children().map(|c| c.nodes()).unwrap_or_default();
+    match node.ty().map(|ty| ty.value()) {
+        Some("object") => {
+            if let Some(arg) = args.first() {
+                return Err(KdlError::in_document(
+                    doc,
+                    span_or_default(arg),
+                    "HCL objects can't have arguments",
+                ));
+            }
+            let mut out = String::from("{\n");
+            for prop in node.iter() {
+                if let Some(name) = prop.name() {
+                    let key = object_key(name.value());
+                    let value = entry_value(doc, prop)?;
+                    writeln!(out, "{indent}{INDENT}{key} = {value}").unwrap();
+                }
+            }
+            for child in children {
+                let key = object_key(child.name().value());
+                let value = node_value(doc, child, depth + 1, false)?;
+                writeln!(out, "{indent}{INDENT}{key} = {value}").unwrap();
+            }
+            if out == "{\n" {
+                return Ok("{}".into());
+            }
+            write!(out, "{indent}}}").unwrap();
+            Ok(out)
+        }
+        Some("array") => {
+            if let Some(prop) = node.iter().find(|e| e.name().is_some()) {
+                return Err(KdlError::in_document(
+                    doc,
+                    span_or_default(prop),
+                    "HCL lists can't have properties",
+                ));
+            }
+            let mut items = args
+                .iter()
+                .map(|arg| entry_value(doc, arg))
+                .collect::<Result<Vec<_>, _>>()?;
+            if children.is_empty() {
+                return Ok(format!("[{}]", items.join(", ")));
+            }
+            for child in children {
+                items.push(node_value(doc, child, depth + 1, false)?);
+            }
+            let mut out = String::from("[\n");
+            for item in items {
+                writeln!(out, "{indent}{INDENT}{item},").unwrap();
+            }
+            write!(out, "{indent}]").unwrap();
+            Ok(out)
+        }
+        _ if node.iter().any(|e| e.name().is_some()) || !children.is_empty() => {
+            Err(KdlError::in_document(
+                doc,
+                span_or_default(node),
+                "Only `(object)` and `(array)` nodes, or nodes with a single argument, can be used as HCL values",
+            ))
+        }
+        _ => match args.as_slice() {
+            [arg] => entry_value(doc, arg),
+            args if lists => Ok(format!(
+                "[{}]",
+                args.iter()
+                    .map(|arg| entry_value(doc, arg))
+                    .collect::<Result<Vec<_>, _>>()?
+                    .join(", ")
+            )),
+            _ => Err(KdlError::in_document(
+                doc,
+                span_or_default(node),
+                "Use an `(array)` node for lists inside HCL objects and lists",
+            )),
+        },
+    }
+}
+
+fn entry_value(doc: &KdlDocument, entry: &KdlEntry) -> Result<String, KdlError> {
+    if entry.ty().map(|ty| ty.value()) == Some("hcl") {
+        let Some(expr) = entry.value().as_string() else {
+            return Err(KdlError::in_document(
+                doc,
+                span_or_default(entry),
+                "`(hcl)` values must be strings",
+            ));
+        };
+        return match expr.parse::<Expression>() {
+            Ok(_) => Ok(expr.trim().into()),
+            Err(e) => Err(KdlError::single(
+                &doc.to_string(),
+                span_or_default(entry),
+                "Invalid HCL expression",
+                Some(e.message()),
+            )),
+        };
+    }
+    Ok(match entry.value() {
+        KdlValue::String(s) => quote(s),
+        KdlValue::Integer(i) => i.to_string(),
+        KdlValue::Float(f) if f.is_finite() => format!("{f:?}"),
+        KdlValue::Float(_) => {
+            return Err(KdlError::in_document(
+                doc,
+                span_or_default(entry),
+                "HCL can't represent non-finite floats",
+            ))
+        }
+        KdlValue::Bool(b) => b.to_string(),
+        KdlValue::Null => "null".into(),
+    })
+}
+
+fn ident<'a>(doc: &KdlDocument, name: &'a str, span: SourceSpan) -> Result<&'a str, KdlError> {
+    match Ident::try_new(name) {
+        Ok(_) => Ok(name),
+        Err(_) => Err(KdlError::in_document(
+            doc,
+            span,
+            format!("`{name}` is not a valid HCL identifier"),
+        )),
+    }
+}
+
+fn object_key(name: &str) -> String {
+    match Ident::try_new(name) {
+        Ok(_) => name.into(),
+        Err(_) => quote(name),
+    }
+}
+
+fn quote(s: &str) -> String {
+    let mut out = String::from('"');
+    let mut chars = s.chars().peekable();
+    while let Some(c) = chars.next() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            // Escape template sequences, so they're not interpolated.
+            '$' | '%' if chars.peek() == Some(&'{') => {
+                out.push(c);
+                out.push(c);
+            }
+            c if c.is_control() => write!(out, "\\u{:04X}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const HCL: &str = r#"terraform_version = "1.6"
+
+provider "aws" {
+  region = var.region
+  default_tags {
+    tags = {
+      Team = "infra"
+      "cost center" = 42
+    }
+  }
+}
+
+variable "region" {
+  type = string
+  default = "us-east-1"
+  sensitive = false
+}
+
+variable "empty" {}
+
+resource "aws_instance" "web" {
+  ami = data.aws_ami.ubuntu.id
+  instance_type = "t3.micro"
+  count = 2
+  monitoring = true
+  cpu_credits = null
+  name = "${local.prefix}-web"
+  security_groups = ["default", "web"]
+  root_block_device {
+    volume_size = 8.5
+  }
+  ebs_block_device = [
+    {
+      device_name = "/dev/sdb"
+    },
+  ]
+}
+"#;
+
+    const KDL: &str = r#"
+terraform_version "1.6"
+provider aws region=(hcl)"var.region" {
+    default_tags {
+        (object)tags Team=infra "cost center"=42
+    }
+}
+variable region type=(hcl)string default=us-east-1 sensitive=#false {}
+variable empty {}
+resource aws_instance web ami=(hcl)"data.aws_ami.ubuntu.id" instance_type=t3.micro count=2 monitoring=#true cpu_credits=#null name=(hcl)"\"${local.prefix}-web\"" {
+    (array)security_groups default web
+    root_block_device volume_size=8.5 {}
+    (array)ebs_block_device {
+        (object)- device_name="/dev/sdb"
+    }
+}
+"#;
+
+    #[test]
+    fn from_hcl() -> miette::Result<()> {
+        let doc = KdlDocument::from_hcl(&format!("# comment\n{HCL}"))?;
+        pretty_assertions::assert_eq!(
+            doc.to_string(),
+            r#"terraform_version "1.6"
+provider aws region=(hcl)var.region {
+    default_tags {
+        (object)tags Team=infra "cost center"=42
+    }
+}
+variable region type=(hcl)string default=us-east-1 sensitive=#false {
+}
+variable empty {
+}
+resource aws_instance web ami=(hcl)data.aws_ami.ubuntu.id instance_type=t3.micro count=2 monitoring=#true cpu_credits=#null name=(hcl)"\"${local.prefix}-web\"" {
+    (array)security_groups default web
+    root_block_device volume_size=8.5 {
+    }
+    (array)ebs_block_device {
+        (object)- device_name="/dev/sdb"
+    }
+}
+"#
+        );
+
+        let err = KdlDocument::from_hcl("a = \n").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (4, 0).into());
+        Ok(())
+    }
+
+    #[test]
+    fn to_hcl() -> miette::Result<()> {
+        let doc: KdlDocument = KDL.parse()?;
+        pretty_assertions::assert_eq!(doc.to_hcl()?, HCL);
+        pretty_assertions::assert_eq!(KdlDocument::from_hcl(HCL)?.to_hcl()?, HCL);
+
+        let doc: KdlDocument = r#"
+locals {
+    cidrs "10.0.0.0/16" "10.1.0.0/16"
+    template "${not interpolated}"
+    (object)empty
+}
+"#
+        .parse()?;
+        assert_eq!(
+            doc.to_hcl()?,
+            r#"locals {
+  cidrs = ["10.0.0.0/16", "10.1.0.0/16"]
+  template = "$${not interpolated}"
+  empty = {}
+}
+"#
+        );
+
+        for bad in [
+            "resource 1 {}",
+            "\"not an ident\" 1",
+            "resource x=#inf",
+            "resource x=(hcl)\"1 +\"",
+            "(object)tags 1",
+            "(array)tags a=1",
+            "(object)tags { nested a=1 }",
+        ] {
+            let doc: KdlDocument = bad.parse()?;
+            assert!(doc.to_hcl().is_err(), "{bad:?} should fail");
+        }
+        Ok(())
+    }
+}
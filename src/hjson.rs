@@ -0,0 +1,399 @@
+use std::fmt::Write as _;
+
+use crate::{
+    error::span_or_default, KdlDocument, KdlEntry, KdlError, KdlIdentifier, KdlNode, KdlValue,
+};
+
+impl KdlDocument {
+    /// Serializes this document as [HJSON](https://hjson.github.io/).
+    ///
+    /// The document becomes an HJSON object, keyed by node name:
+    ///
+    /// * Nodes with a single argument and nothing else (no properties, no
+    ///   children) map to `"name": value`.
+    /// * Any other node maps to an object with an `"args"` array, its
+    ///   properties as members, and a `"children"` object, each of them only
+    ///   present if non-empty.
+    /// * If a node name shows up more than once at the same level, all of
+    ///   those nodes are collected, in order, into an array under that name.
+    /// * Type annotations are written as `/* (type) */` comments right before
+    ///   the annotated node's key, or the annotated entry's value.
+    ///
+    /// Fails if the document contains non-finite floats (`#inf`, `#-inf`, or
+    /// `#nan`), since HJSON has no way to represent them, or if a node that
+    /// maps to an object has a property named `args` or `children`, since
+    /// those would clash with its arguments and children.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "name kdl\nport (u16)8080".parse().unwrap();
+    /// assert_eq!(
+    ///     doc.to_hjson().unwrap(),
+    ///     "{\n  \"name\": \"kdl\"\n  \"port\": /* (u16) */ 8080\n}\n"
+    /// );
+    /// ```
+    pub fn to_hjson(&self) -> Result<String, KdlError> {
+        let mut out = String::new();
+        write_object(&mut out, self, 0, self)?;
+        out.push('\n');
+        Ok(out)
+    }
+}
+
+fn write_object(
+    out: &mut String,
+    doc: &KdlDocument,
+    indent: usize,
+    root: &KdlDocument,
+) -> Result<(), KdlError> {
+    let mut groups: Vec<(&str, Vec<&KdlNode>)> = Vec::new();
+    for node in doc.nodes() {
+        let name = node.name().value();
+        if let Some((_, group)) = groups.iter_mut().find(|(n, _)| *n == name) {
+            group.push(node);
+        } else {
+            groups.push((name, vec![node]));
+        }
+    }
+    out.push('{');
+    for (name, group) in groups {
+        newline(out, indent + 1);
+        if let [node] = &group[..] {
+            if let Some(ty) = node.ty() {
+                write_ty(out, ty);
+                out.push(' ');
+            }
+            write_string(out, name);
+            out.push_str(": ");
+            write_node(out, node, indent + 1, root)?;
+        } else {
+            write_string(out, name);
+            out.push_str(": [");
+            for node in group {
+                newline(out, indent + 2);
+                if let Some(ty) = node.ty() {
+                    write_ty(out, ty);
+                    out.push(' ');
+                }
+                write_node(out, node, indent + 2, root)?;
+            }
+            newline(out, indent + 1);
+            out.push(']');
+        }
+    }
+    if !doc.nodes().is_empty() {
+        newline(out, indent);
+    }
+    out.push('}');
+    Ok(())
+}
+
+fn write_node(
+    out: &mut String,
+    node: &KdlNode,
+    indent: usize,
+    root: &KdlDocument,
+) -> Result<(), KdlError> {
+    let children = node.children().filter(|c| !c.nodes().is_empty());
+    if let ([entry], None) = (node.entries(), children) {
+        if entry.name().is_none() {
+            return write_entry_value(out, entry, root);
+        }
+    }
+    let args: Vec<&KdlEntry> = node.iter().filter(|e| e.name().is_none()).collect();
+    out.push('{');
+    if !args.is_empty() {
+        newline(out, indent + 1);
+        out.push_str("\"args\": [");
+        for (idx, arg) in args.into_iter().enumerate() {
+            if idx > 0 {
+                out.push_str(", ");
+            }
+            write_entry_value(out, arg, root)?;
+        }
+        out.push(']');
+    }
+    for entry in node.iter() {
+        if let Some(name) = entry.name() {
+            if matches!(name.value(), "args" | "children") {
+                return Err(KdlError::single(
+                    &root.to_string(),
+                    span_or_default(entry),
+                    format!("HJSON cannot represent a property named `{}`", name.value()),
+                    Some(
+                        "`args` and `children` are reserved for the node's arguments and children.",
+                    ),
+                ));
+            }
+            newline(out, indent + 1);
+            write_string(out, name.value());
+            out.push_str(": ");
+            write_entry_value(out, entry, root)?;
+        }
+    }
+    if let Some(children) = children {
+        newline(out, indent + 1);
+        out.push_str("\"children\": ");
+        write_object(out, children, indent + 1, root)?;
+    }
+    if !node.entries().is_empty() || children.is_some() {
+        newline(out, indent);
+    }
+    out.push('}');
+    Ok(())
+}
+
+fn write_entry_value(
+    out: &mut String,
+    entry: &KdlEntry,
+    root: &KdlDocument,
+) -> Result<(), KdlError> {
+    if let Some(ty) = entry.ty() {
+        write_ty(out, ty);
+        out.push(' ');
+    }
+    match entry.value() {
+        KdlValue::String(s) => write_string(out, s),
+        KdlValue::Integer(i) => write!(out, "{i}").unwrap(),
+        KdlValue::Float(f) if f.is_finite() => write!(out, "{f:?}").unwrap(),
+        KdlValue::Float(_) => {
+            return Err(KdlError::single(
+                &root.to_string(),
+                span_or_default(entry),
+                "HJSON cannot represent non-finite floats",
+                Some("Replace #inf, #-inf, and #nan with a finite number or a string."),
+            ));
+        }
+        KdlValue::Bool(b) => write!(out, "{b}").unwrap(),
+        KdlValue::Null => out.push_str("null"),
+    }
+    Ok(())
+}
+
+fn write_ty(out: &mut String, ty: &KdlIdentifier) {
+    write!(out, "/* ({}) */", ty.value().replace("*/", "* /")).unwrap();
+}
+
+fn newline(out: &mut String, indent: usize) {
+    out.push('\n');
+    for _ in 0..indent {
+        out.push_str("  ");
+    }
+}
+
+fn write_string(out: &mut String, s: &str) {
+    out.push('"');
+    for c in s.chars() {
+        match c {
+            '"' => out.push_str("\\\""),
+            '\\' => out.push_str("\\\\"),
+            '\n' => out.push_str("\\n"),
+            '\r' => out.push_str("\\r"),
+            '\t' => out.push_str("\\t"),
+            '\u{08}' => out.push_str("\\b"),
+            '\u{0C}' => out.push_str("\\f"),
+            c if c.is_control() => write!(out, "\\u{:04x}", c as u32).unwrap(),
+            c => out.push(c),
+        }
+    }
+    out.push('"');
+}
+
+#[cfg(test)]
+mod test {
+    use serde_json::json;
+
+    use super::*;
+
+    fn roundtrip(doc: &KdlDocument) -> serde_json::Value {
+        let hjson = doc.to_hjson().expect("to_hjson failed");
+        deser_hjson::from_str(&hjson).expect("generated invalid HJSON")
+    }
+
+    /// The inverse of [`KdlDocument::to_hjson`]'s mapping, minus type
+    /// annotations, which HJSON parsers drop along with other comments.
+    fn from_json(value: &serde_json::Value) -> KdlDocument {
+        let mut doc = KdlDocument::new();
+        for (name, value) in value.as_object().expect("expected an object") {
+            match value {
+                serde_json::Value::Array(items) => {
+                    for item in items {
+                        doc.nodes_mut().push(node_from_json(name, item));
+                    }
+                }
+                value => doc.nodes_mut().push(node_from_json(name, value)),
+            }
+        }
+        doc
+    }
+
+    fn node_from_json(name: &str, value: &serde_json::Value) -> KdlNode {
+        let mut node = KdlNode::new(name);
+        let Some(members) = value.as_object() else {
+            node.push(value_from_json(value));
+            return node;
+        };
+        for (key, value) in members {
+            match (key.as_str(), value) {
+                ("args", serde_json::Value::Array(args)) => {
+                    for arg in args {
+                        node.push(value_from_json(arg));
+                    }
+                }
+                ("children", children) => node.set_children(from_json(children)),
+                (key, value) => node.push((key, value_from_json(value))),
+            }
+        }
+        node
+    }
+
+    fn value_from_json(value: &serde_json::Value) -> KdlValue {
+        match value {
+            serde_json::Value::String(s) => KdlValue::String(s.clone()),
+            serde_json::Value::Number(n) => match n.as_i64() {
+                Some(i) => KdlValue::Integer(i.into()),
+                None => KdlValue::Float(n.as_f64().unwrap()),
+            },
+            serde_json::Value::Bool(b) => KdlValue::Bool(*b),
+            serde_json::Value::Null => KdlValue::Null,
+            value => panic!("expected a scalar, got {value}"),
+        }
+    }
+
+    fn assert_roundtrips(kdl: &str) -> miette::Result<()> {
+        let mut doc: KdlDocument = kdl.parse()?;
+        let mut back = from_json(&roundtrip(&doc));
+        doc.clear_format_recursive();
+        back.clear_format_recursive();
+        pretty_assertions::assert_eq!(back, doc);
+        Ok(())
+    }
+
+    #[test]
+    fn simple_values() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+string "hello\n\"world\""
+int 42
+float 1.5
+bool #true
+nothing #null
+"#
+        .parse()?;
+        assert_eq!(
+            roundtrip(&doc),
+            json!({
+                "string": "hello\n\"world\"",
+                "int": 42,
+                "float": 1.5,
+                "bool": true,
+                "nothing": null,
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn complex_nodes() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+(server)server "main" 1 host=localhost port=(u16)8080 {
+    tls enabled=#true
+    empty
+}
+item 1
+item 2
+item a=b
+"#
+        .parse()?;
+        let hjson = doc.to_hjson()?;
+        assert_eq!(
+            hjson,
+            r#"{
+  /* (server) */ "server": {
+    "args": ["main", 1]
+    "host": "localhost"
+    "port": /* (u16) */ 8080
+    "children": {
+      "tls": {
+        "enabled": true
+      }
+      "empty": {}
+    }
+  }
+  "item": [
+    1
+    2
+    {
+      "a": "b"
+    }
+  ]
+}
+"#
+        );
+        assert_eq!(
+            roundtrip(&doc),
+            json!({
+                "server": {
+                    "args": ["main", 1],
+                    "host": "localhost",
+                    "port": 8080,
+                    "children": {
+                        "tls": { "enabled": true },
+                        "empty": {},
+                    },
+                },
+                "item": [1, 2, { "a": "b" }],
+            })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn roundtrips() -> miette::Result<()> {
+        assert_roundtrips(
+            r#"
+name kdl
+version 6 3.5
+server "main" 1 host=localhost port=8080 {
+    tls enabled=#true cert=#null
+    empty
+    "needs \"quotes\"" "a\tb"
+}
+item 1
+item 2
+item a=b
+item {
+    nested x
+}
+"#,
+        )?;
+        assert_roundtrips("")
+    }
+
+    #[test]
+    fn reserved_property_names() -> miette::Result<()> {
+        for bad in [
+            "node children=1",
+            "node 1 args=2",
+            "a {\n    b args=#true\n}",
+        ] {
+            let doc: KdlDocument = bad.parse()?;
+            assert!(doc.to_hjson().is_err(), "{bad:?} should fail");
+        }
+        let doc: KdlDocument = "args 1\nchildren {\n    args 2\n}".parse()?;
+        assert_eq!(
+            roundtrip(&doc),
+            json!({ "args": 1, "children": { "children": { "args": 2 } } })
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn non_finite_floats() -> miette::Result<()> {
+        let doc: KdlDocument = "x #inf".parse()?;
+        assert!(doc.to_hjson().is_err());
+        assert_eq!(roundtrip(&KdlDocument::new()), json!({}));
+        Ok(())
+    }
+}
@@ -66,6 +66,31 @@ impl KdlIdentifier {
         self.repr = Some(repr.into());
     }
 
+    /// Returns this identifier's value if it can be written as a plain,
+    /// unquoted identifier, and `None` if it would need to be quoted.
+    ///
+    /// This is useful when generating code or configuration that only allows
+    /// bare identifiers.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlIdentifier;
+    /// let plain = KdlIdentifier::from("foo-bar");
+    /// assert_eq!(plain.try_as_plain(), Some("foo-bar"));
+    ///
+    /// let quoted = KdlIdentifier::from("foo bar");
+    /// assert_eq!(quoted.try_as_plain(), None);
+    ///
+    /// // Fall back to the raw value when a plain identifier isn't possible:
+    /// let name = quoted.try_as_plain().unwrap_or_else(|| quoted.value());
+    /// assert_eq!(name, "foo bar");
+    /// ```
+    pub fn try_as_plain(&self) -> Option<&str> {
+        let value = self.value();
+        (!value.is_empty() && crate::value::is_plain_ident(value)).then_some(value)
+    }
+
     /// Length of this identifier when rendered as a string.
     pub fn len(&self) -> usize {
         format!("{self}").len()
@@ -212,6 +237,34 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn try_as_plain() {
+        for plain in [
+            "foo",
+            "foo-bar",
+            "foo123~!@$%^&*.:'|?+<>,",
+            "-",
+            "_0",
+            "truest",
+        ] {
+            assert_eq!(KdlIdentifier::from(plain).try_as_plain(), Some(plain));
+        }
+        for quoted in [
+            "", "foo bar", "foo\"bar", "1foo", "-1", ".5", "(ty)", "a=b", "true", "false", "null",
+            "inf", "-inf", "nan",
+        ] {
+            assert_eq!(
+                KdlIdentifier::from(quoted).try_as_plain(),
+                None,
+                "{quoted:?}"
+            );
+        }
+
+        // Only the value matters, not how it was written.
+        let ident: KdlIdentifier = r#""foo""#.parse().unwrap();
+        assert_eq!(ident.try_as_plain(), Some("foo"));
+    }
+
     #[test]
     fn formatting() {
         let plain = KdlIdentifier::from("foo");
@@ -0,0 +1,212 @@
+use serde_json::{Deserializer, Value};
+
+use crate::{lines::lines, KdlDocument, KdlError, KdlNode, KdlValue};
+
+impl KdlDocument {
+    /// Parses the output of [`jq`](https://jqlang.org/), a sequence of JSON
+    /// values separated by whitespace (usually newlines), into a document
+    /// with one `result` node per value.
+    ///
+    /// Scalars become the `result` node's only argument, objects become its
+    /// children, and arrays of scalars become multiple arguments. Nested
+    /// values are converted the same way, with each object key becoming a
+    /// child node named after the key, and arrays containing objects or
+    /// arrays becoming children named `-`, one per item:
+    ///
+    /// ```kdl
+    /// result "a string"
+    /// result {
+    ///     name kdl
+    ///     tags config language
+    ///     versions {
+    ///         - {
+    ///             number 2
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// This works with both `jq -c` output and jq's default pretty-printed
+    /// output.
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// Fails if any of the values aren't valid JSON.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_jq_output("\"web\"\n{\"port\":8080,\"tls\":null}\n").unwrap();
+    /// assert_eq!(doc.to_string(), "result web\nresult {\n    port 8080\n    tls #null\n}\n");
+    /// ```
+    pub fn from_jq_output(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        let mut values = Deserializer::from_str(s).into_iter::<Value>();
+        loop {
+            let offset = values.byte_offset();
+            match values.next() {
+                Some(Ok(value)) => doc.nodes_mut().push(value_to_node("result", value)),
+                Some(Err(e)) => {
+                    let error_offset = error_offset(s, &e).clamp(offset, s.len());
+                    return Err(KdlError::single(
+                        s,
+                        (error_offset, 0),
+                        "Invalid JSON",
+                        Some(&e.to_string()),
+                    ));
+                }
+                None => break,
+            }
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Returns the byte offset of a JSON error in `s`. Errors report the line and
+/// column they happened at, rather than an offset.
+pub(crate) fn error_offset(s: &str, e: &serde_json::Error) -> usize {
+    lines(s)
+        .nth(e.line().saturating_sub(1))
+        .map_or(s.len(), |(offset, _)| offset)
+        + e.column().saturating_sub(1)
+}
+
+fn value_to_node(name: &str, value: Value) -> KdlNode {
+    let mut node = KdlNode::new(name);
+    match value {
+        Value::Object(object) => {
+            let children = node.ensure_children();
+            for (key, value) in object {
+                children.nodes_mut().push(value_to_node(&key, value));
+            }
+        }
+        Value::Array(items) if items.iter().all(|i| scalar(i).is_some()) => {
+            for item in &items {
+                node.push(scalar(item).unwrap());
+            }
+        }
+        Value::Array(items) => {
+            let children = node.ensure_children();
+            for item in items {
+                children.nodes_mut().push(value_to_node("-", item));
+            }
+        }
+        value => node.push(scalar(&value).unwrap()),
+    }
+    node
+}
+
+pub(crate) fn scalar(value: &Value) -> Option<KdlValue> {
+    Some(match value {
+        Value::Null => KdlValue::Null,
+        Value::Bool(b) => KdlValue::Bool(*b),
+        Value::Number(n) => match (n.as_i64(), n.as_u64()) {
+            (Some(i), _) => KdlValue::Integer(i.into()),
+            (_, Some(u)) => KdlValue::Integer(u.into()),
+            _ => KdlValue::Float(n.as_f64()?),
+        },
+        Value::String(s) => KdlValue::String(s.clone()),
+        Value::Array(_) | Value::Object(_) => return None,
+    })
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn compact_output() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_jq_output,
+            r#"1
+-2.5
+18446744073709551615
+"two words"
+true
+null
+[1,"a",false]
+[]
+{}
+[{"id":1},[2,3]]
+"#,
+            r#"
+result 1
+result -2.5
+result 18446744073709551615
+result "two words"
+result #true
+result #null
+result 1 a #false
+result
+result {
+}
+result {
+    - {
+        id 1
+    }
+    - 2 3
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn pretty_output() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_jq_output,
+            r#"{
+  "name": "kdl",
+  "zeta": 1,
+  "alpha": {
+    "tags": [
+      "config",
+      "language"
+    ],
+    "versions": [
+      {
+        "number": 2
+      }
+    ]
+  }
+}
+{
+  "name": "json"
+}
+"#,
+            r#"
+result {
+    name kdl
+    zeta 1
+    alpha {
+        tags config language
+        versions {
+            - {
+                number 2
+            }
+        }
+    }
+}
+result {
+    name json
+}
+"#,
+        )?;
+        assert!(KdlDocument::from_jq_output("").unwrap().nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        for bad in ["{\"a\": }", "1\n[1, 2\n", "{}\nnope\n"] {
+            assert!(
+                KdlDocument::from_jq_output(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        let err = KdlDocument::from_jq_output("1\n2\n{\"a\" 1}\n").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (9, 0).into());
+    }
+}
@@ -0,0 +1,402 @@
+use yaml_rust2::{yaml::Hash, Yaml, YamlEmitter};
+
+use crate::{
+    yaml::{convert_yaml, key_to_string, load_all, scalar, ListItems, YamlConversion},
+    KdlDocument, KdlError, KdlNode,
+};
+
+impl KdlDocument {
+    /// Parses a Kubernetes resource manifest into a document.
+    ///
+    /// Each resource becomes a toplevel `resource` node, with its
+    /// `metadata.name` as its argument and its `apiVersion` and `kind` as
+    /// properties. The contents of its `spec` become the node's children,
+    /// along with the rest of its `metadata` (as a `metadata` child) and any
+    /// other toplevel keys, like a ConfigMap's `data`:
+    ///
+    /// ```kdl
+    /// resource web apiVersion="apps/v1" kind=Deployment {
+    ///     metadata {
+    ///         labels app=web
+    ///     }
+    ///     replicas 3
+    /// }
+    /// ```
+    ///
+    /// Within a resource:
+    ///
+    /// * `labels`, `annotations`, `matchLabels` and `nodeSelector` become a
+    ///   single node with one property per entry.
+    /// * Lists of mappings become one child per item, named after the list
+    ///   without its trailing `s` (so `containers` items become `container`
+    ///   nodes), or `-` if the list name doesn't end in `s`. Items with a
+    ///   `name` get it as their argument.
+    /// * Everything else is converted generically: scalars become a node's
+    ///   only argument, mappings become children, and lists of scalars become
+    ///   multiple arguments.
+    ///
+    /// Manifests with several YAML documents separated by `---` produce one
+    /// `resource` node per document, in order. Empty documents are skipped.
+    ///
+    /// Fails if the YAML is invalid, if a resource isn't a YAML mapping, or if
+    /// it's missing its `apiVersion` or `kind`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_k8s_manifest(r#"
+    /// apiVersion: v1
+    /// kind: Service
+    /// metadata:
+    ///   name: web
+    /// spec:
+    ///   selector: {app: web}
+    ///   ports:
+    ///     - port: 80
+    /// "#).unwrap();
+    /// assert_eq!(doc.to_string(), r#"resource web apiVersion=v1 kind=Service {
+    ///     selector {
+    ///         app web
+    ///     }
+    ///     ports {
+    ///         port {
+    ///             port 80
+    ///         }
+    ///     }
+    /// }
+    /// "#);
+    /// ```
+    pub fn from_k8s_manifest(yaml: &str) -> Result<KdlDocument, KdlError> {
+        let error = |message: &str| KdlError::single(yaml, (0, 0), message, None);
+        let mut doc = KdlDocument::new();
+        for resource in load_all(yaml)? {
+            let hash = match resource {
+                Yaml::Null => continue,
+                Yaml::Hash(hash) => hash,
+                _ => {
+                    return Err(error(
+                        "Expected each Kubernetes resource to be a YAML mapping",
+                    ))
+                }
+            };
+            let mut node = KdlNode::new("resource");
+            let mut name = None;
+            let mut api_version = None;
+            let mut kind = None;
+            for (key, value) in hash {
+                let key = key_to_string(&key).ok_or_else(|| error("Unsupported YAML key"))?;
+                match (key.as_str(), value) {
+                    ("apiVersion", value) => api_version = scalar(&value),
+                    ("kind", value) => kind = scalar(&value),
+                    ("metadata", Yaml::Hash(metadata)) => {
+                        let mut child = KdlNode::new("metadata");
+                        for (key, value) in metadata {
+                            let key =
+                                key_to_string(&key).ok_or_else(|| error("Unsupported YAML key"))?;
+                            if key == "name" {
+                                name = scalar(&value);
+                                continue;
+                            }
+                            let grandchild = convert(&key, value).map_err(|msg| error(&msg))?;
+                            child.ensure_children().nodes_mut().push(grandchild);
+                        }
+                        if child.children().is_some() {
+                            node.ensure_children().nodes_mut().push(child);
+                        }
+                    }
+                    ("spec", Yaml::Hash(spec)) => {
+                        let children = node.ensure_children();
+                        for (key, value) in spec {
+                            let key =
+                                key_to_string(&key).ok_or_else(|| error("Unsupported YAML key"))?;
+                            children
+                                .nodes_mut()
+                                .push(convert(&key, value).map_err(|msg| error(&msg))?);
+                        }
+                    }
+                    (key, value) => {
+                        let child = convert(key, value).map_err(|msg| error(&msg))?;
+                        node.ensure_children().nodes_mut().push(child);
+                    }
+                }
+            }
+            let api_version = api_version
+                .ok_or_else(|| error("Every Kubernetes resource must have an `apiVersion`"))?;
+            let kind = kind.ok_or_else(|| error("Every Kubernetes resource must have a `kind`"))?;
+            node.insert("apiVersion", api_version);
+            node.insert("kind", kind);
+            if let Some(name) = name {
+                node.entries_mut().insert(0, name.into());
+            }
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+
+    /// Generates a Kubernetes `ConfigMap` manifest, as YAML, that stores this
+    /// document (as KDL text) under `data[key]`, with the given
+    /// `metadata.name` and `metadata.namespace`.
+    ///
+    /// The document is stored as it would be [displayed](std::fmt::Display),
+    /// as a YAML block literal when it spans multiple lines.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "port 8080\nhost localhost\n".parse().unwrap();
+    /// assert_eq!(doc.to_k8s_configmap("web-config", "prod", "config.kdl"), r#"---
+    /// apiVersion: v1
+    /// kind: ConfigMap
+    /// metadata:
+    ///   name: web-config
+    ///   namespace: prod
+    /// data:
+    ///   config.kdl: |
+    ///     port 8080
+    ///     host localhost
+    /// "#);
+    /// ```
+    pub fn to_k8s_configmap(&self, name: &str, namespace: &str, key: &str) -> String {
+        let string = |s: &str| Yaml::String(s.into());
+        let mut metadata = Hash::new();
+        metadata.insert(string("name"), string(name));
+        metadata.insert(string("namespace"), string(namespace));
+        let mut data = Hash::new();
+        data.insert(string(key), string(&self.to_string()));
+        let mut manifest = Hash::new();
+        manifest.insert(string("apiVersion"), string("v1"));
+        manifest.insert(string("kind"), string("ConfigMap"));
+        manifest.insert(string("metadata"), Yaml::Hash(metadata));
+        manifest.insert(string("data"), Yaml::Hash(data));
+
+        let mut out = String::new();
+        let mut emitter = YamlEmitter::new(&mut out);
+        emitter.multiline_strings(true);
+        emitter
+            .dump(&Yaml::Hash(manifest))
+            .expect("writing YAML to a String can't fail");
+        out.push('\n');
+        out
+    }
+}
+
+/// Kubernetes objects use flat label maps under a few well-known keys, and
+/// name the items of most lists with a `name` key.
+const K8S: YamlConversion = YamlConversion {
+    label_maps: &["labels", "annotations", "matchLabels", "nodeSelector"],
+    list_items: ListItems::NamedSingular,
+};
+
+fn convert(name: &str, value: Yaml) -> Result<KdlNode, String> {
+    convert_yaml(name, value, &K8S, convert)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn deployment() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_k8s_manifest,
+            r#"
+apiVersion: apps/v1
+kind: Deployment
+metadata:
+  name: web
+  namespace: prod
+  labels:
+    app: web
+    tier: frontend
+spec:
+  replicas: 3
+  selector:
+    matchLabels:
+      app: web
+  template:
+    metadata:
+      labels:
+        app: web
+    spec:
+      containers:
+        - name: nginx
+          image: nginx:1.25
+          args: ["--port", "8080"]
+          ports:
+            - containerPort: 8080
+              protocol: TCP
+          env:
+            - name: LOG_LEVEL
+              value: info
+"#,
+            r#"
+resource web apiVersion="apps/v1" kind=Deployment {
+    metadata {
+        namespace prod
+        labels app=web tier=frontend
+    }
+    replicas 3
+    selector {
+        matchLabels app=web
+    }
+    template {
+        metadata {
+            labels app=web
+        }
+        spec {
+            containers {
+                container nginx {
+                    image "nginx:1.25"
+                    args "--port" "8080"
+                    ports {
+                        port {
+                            containerPort 8080
+                            protocol TCP
+                        }
+                    }
+                    env {
+                        - LOG_LEVEL {
+                            value info
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn service() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_k8s_manifest,
+            r#"
+apiVersion: v1
+kind: Service
+metadata:
+  name: web
+  annotations:
+    prometheus.io/scrape: "true"
+spec:
+  type: ClusterIP
+  selector:
+    app: web
+  ports:
+    - name: http
+      port: 80
+      targetPort: 8080
+"#,
+            r#"
+resource web apiVersion=v1 kind=Service {
+    metadata {
+        annotations "prometheus.io/scrape"="true"
+    }
+    type ClusterIP
+    selector {
+        app web
+    }
+    ports {
+        port http {
+            port 80
+            targetPort 8080
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn config_map() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_k8s_manifest,
+            r#"
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  name: web-config
+data:
+  LOG_LEVEL: debug
+  app.properties: |
+    color=blue
+    size=large
+---
+---
+apiVersion: v1
+kind: ConfigMap
+metadata:
+  generateName: extra-
+immutable: true
+"#,
+            r#"
+resource web-config apiVersion=v1 kind=ConfigMap {
+    data {
+        LOG_LEVEL debug
+        app.properties "color=blue\nsize=large\n"
+    }
+}
+resource apiVersion=v1 kind=ConfigMap {
+    metadata {
+        generateName extra-
+    }
+    immutable #true
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn to_k8s_configmap() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+// Server settings
+server host="0.0.0.0" port=8080 {
+    tls cert="/etc/tls: cert.pem"
+}
+"#
+        .parse()?;
+        let yaml = doc.to_k8s_configmap("web: config", "prod", "app.kdl");
+        let manifest = crate::yaml::load_single(&yaml, "ConfigMap")?.unwrap();
+        assert_eq!(manifest["apiVersion"].as_str(), Some("v1"));
+        assert_eq!(manifest["kind"].as_str(), Some("ConfigMap"));
+        assert_eq!(manifest["metadata"]["name"].as_str(), Some("web: config"));
+        assert_eq!(manifest["metadata"]["namespace"].as_str(), Some("prod"));
+        let stored: KdlDocument = manifest["data"]["app.kdl"].as_str().unwrap().parse()?;
+        assert_eq!(stored, doc);
+
+        // It reads back as a ConfigMap resource, too.
+        let resource = KdlDocument::from_k8s_manifest(&yaml)?;
+        assert_eq!(resource.nodes()[0].get("kind"), Some(&"ConfigMap".into()));
+
+        let yaml = KdlDocument::new().to_k8s_configmap("empty", "default", "config.kdl");
+        let manifest = crate::yaml::load_single(&yaml, "ConfigMap")?.unwrap();
+        assert_eq!(manifest["data"]["config.kdl"].as_str(), Some(""));
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        assert!(KdlDocument::from_k8s_manifest("")
+            .unwrap()
+            .nodes()
+            .is_empty());
+        for bad in [
+            "- not a mapping\n",
+            "kind: Pod\nmetadata: {name: x}\n",
+            "apiVersion: v1\nmetadata: {name: x}\n",
+            "apiVersion: v1\nkind: Pod\n---\nkind: Pod\n",
+            "apiVersion: v1\nkind: Pod\nmetadata:\n  labels: {a: [1]}\n",
+            "kind: [\n",
+        ] {
+            assert!(
+                KdlDocument::from_k8s_manifest(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+    }
+}
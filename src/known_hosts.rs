@@ -0,0 +1,157 @@
+use crate::{lines::trimmed_lines, KdlDocument, KdlError, KdlNode};
+
+impl KdlDocument {
+    /// Parses an OpenSSH `known_hosts` file into a document.
+    ///
+    /// Each `hostnames keytype key [comment]` line becomes a `host` node with
+    /// `hostname`, `key-type`, `key` and (if there is one) `comment` string
+    /// properties:
+    ///
+    /// * Lines that list several hosts (like `a.example.com,10.0.0.1`) keep
+    ///   them comma-separated in `hostname`, as written.
+    /// * Hashed hostnames (`|1|salt|hash`) are kept as-is, and also get a
+    ///   `hashed=#true` property.
+    /// * Lines starting with a `@cert-authority` or `@revoked` marker get a
+    ///   `marker` property with the marker's name, without the `@`.
+    ///
+    /// Empty lines and lines starting with `#` are skipped.
+    ///
+    /// Fails if a line is missing its hostnames, key type or key, or if it
+    /// has an unknown marker.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_known_hosts(
+    ///     "@revoked github.com ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIOMq old key\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "host hostname=github.com key-type=ssh-ed25519 key=AAAAC3NzaC1lZDI1NTE5AAAAIOMq comment=\"old key\" marker=revoked\n"
+    /// );
+    /// ```
+    pub fn from_known_hosts(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        for (start, line) in trimmed_lines(s) {
+            let error = |message: &str| KdlError::single(s, (start, line.len()), message, None);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let mut rest = line;
+            let mut marker = None;
+            if let Some(marked) = line.strip_prefix('@') {
+                let (name, after) = split_field(marked);
+                if !matches!(name, "cert-authority" | "revoked") {
+                    return Err(error(&format!("Unknown known_hosts marker `@{name}`")));
+                }
+                marker = Some(name);
+                rest = after;
+            }
+            let (hostname, rest) = split_field(rest);
+            let (key_type, rest) = split_field(rest);
+            let (key, comment) = split_field(rest);
+            if key.is_empty() {
+                return Err(error(
+                    "Expected a known_hosts entry like `hostnames keytype key [comment]`",
+                ));
+            }
+            let mut node = KdlNode::new("host");
+            node.insert("hostname", hostname);
+            node.insert("key-type", key_type);
+            node.insert("key", key);
+            if !comment.is_empty() {
+                node.insert("comment", comment);
+            }
+            if let Some(marker) = marker {
+                node.insert("marker", marker);
+            }
+            if hostname.starts_with('|') {
+                node.insert("hashed", true);
+            }
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Splits off the first whitespace-separated field of `s`, returning it and
+/// the rest of `s` with its leading whitespace removed.
+fn split_field(s: &str) -> (&str, &str) {
+    match s.split_once(char::is_whitespace) {
+        Some((field, rest)) => (field, rest.trim_start()),
+        None => (s, ""),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn single_host() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_known_hosts,
+            "example.com ssh-rsa AAAAB3NzaC1yc2EAAAADAQABAAABAQC7\n",
+            "host hostname=example.com key-type=ssh-rsa key=AAAAB3NzaC1yc2EAAAADAQABAAABAQC7",
+        )
+    }
+
+    #[test]
+    fn multi_host() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_known_hosts,
+            r#"
+# Internal hosts
+a.example.com,b.example.com,10.0.0.1 ecdsa-sha2-nistp256 AAAAE2VjZHNh= ops@example.com
+[git.example.com]:2222,*.corp ssh-ed25519 AAAAC3NzaC1lZDI1NTE5
+
+@cert-authority *.example.com ssh-ed25519 AAAAC3NzaC1lZDI1NTE5AAAAIB= CA key for example.com
+"#,
+            r#"
+host hostname="a.example.com,b.example.com,10.0.0.1" key-type=ecdsa-sha2-nistp256 key="AAAAE2VjZHNh=" comment="ops@example.com"
+host hostname="[git.example.com]:2222,*.corp" key-type=ssh-ed25519 key=AAAAC3NzaC1lZDI1NTE5
+host hostname="*.example.com" key-type=ssh-ed25519 key="AAAAC3NzaC1lZDI1NTE5AAAAIB=" comment="CA key for example.com" marker=cert-authority
+"#,
+        )
+    }
+
+    #[test]
+    fn hashed() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_known_hosts,
+            "|1|JfKTdBh7rNbXkVAQCRp4OQoPfmI=|USECr3SWf1JUPsms5AqfD5QfxkM= ssh-ed25519 AAAAC3NzaC1lZDI1NTE5\n",
+            r#"host hostname="|1|JfKTdBh7rNbXkVAQCRp4OQoPfmI=|USECr3SWf1JUPsms5AqfD5QfxkM=" key-type=ssh-ed25519 key=AAAAC3NzaC1lZDI1NTE5 hashed=#true"#,
+        )
+    }
+
+    #[test]
+    fn revoked() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_known_hosts,
+            "@revoked * ssh-rsa AAAAB3NzaC1yc2EAAAADAQAB compromised 2024-01-01\n",
+            r#"host hostname="*" key-type=ssh-rsa key=AAAAB3NzaC1yc2EAAAADAQAB comment="compromised 2024-01-01" marker=revoked"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "example.com ssh-rsa\n",
+            "@revoked example.com ssh-rsa\n",
+            "@trusted example.com ssh-rsa AAAA\n",
+        ] {
+            assert!(
+                KdlDocument::from_known_hosts(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        assert!(KdlDocument::from_known_hosts("# nothing here\n\n")
+            .unwrap()
+            .nodes()
+            .is_empty());
+    }
+}
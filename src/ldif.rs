@@ -0,0 +1,231 @@
+use crate::{lines::lines, KdlDocument, KdlEntry, KdlError, KdlNode, KdlValue};
+
+impl KdlDocument {
+    /// Parses an [LDIF](https://www.rfc-editor.org/rfc/rfc2849) (LDAP Data
+    /// Interchange Format) file into a document.
+    ///
+    /// Each entry becomes a toplevel node named after its `dn`. Attributes
+    /// with a single value become string properties on the node, and
+    /// attributes with several values, like `objectClass`, become a child
+    /// node with one argument per value, in order:
+    ///
+    /// ```kdl
+    /// "uid=jdoe,ou=people,dc=example,dc=com" uid=jdoe cn="John Doe" {
+    ///     objectClass top person inetOrgPerson
+    /// }
+    /// ```
+    ///
+    /// Attribute names are case-insensitive, so `objectClass` and
+    /// `objectclass` are the same attribute, named after its first
+    /// spelling. Base64-encoded values (`attribute:: dGV4dA==`) are kept
+    /// encoded, as `(base64)` annotated strings, and URL references
+    /// (`attribute:< file:///photo.jpg`) as `(url)` annotated strings. A
+    /// base64-encoded `dn` gives the node a `(base64)` type annotation.
+    ///
+    /// Folded lines (continued on the next line after a single space) are
+    /// unfolded, and comments and the `version:` line are skipped.
+    ///
+    /// This is only available with the `ldif` feature.
+    ///
+    /// Fails if a line isn't an `attribute: value` pair, if an entry doesn't
+    /// start with a `dn`, or if the file contains change records (with a
+    /// `changetype`), which aren't supported.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_ldif(
+    ///     "dn: cn=admins,dc=example,dc=com\ncn: admins\nmember: uid=a\nmember: uid=b\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "\"cn=admins,dc=example,dc=com\" cn=admins {\n    member \"uid=a\" \"uid=b\"\n}\n"
+    /// );
+    /// ```
+    pub fn from_ldif(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        let mut entry: Option<Entry> = None;
+        let mut first = true;
+        for (start, line) in unfold(s) {
+            let error = |message: &str| KdlError::single(s, (start, line.len()), message, None);
+            if line.is_empty() {
+                if let Some(entry) = entry.take() {
+                    doc.nodes_mut().push(entry.into_node());
+                }
+                continue;
+            }
+            if line.starts_with('#') {
+                continue;
+            }
+            let (name, value) =
+                parse_line(&line).ok_or_else(|| error("Expected an `attribute: value` line"))?;
+            let is_version = first && name.eq_ignore_ascii_case("version");
+            first = false;
+            if is_version {
+                continue;
+            }
+            match &mut entry {
+                None if name.eq_ignore_ascii_case("dn") => {
+                    entry = Some(Entry {
+                        dn: value,
+                        attributes: Vec::new(),
+                    });
+                }
+                None => return Err(error("LDIF entries must start with a `dn`")),
+                Some(_) if name.eq_ignore_ascii_case("changetype") => {
+                    return Err(error("LDIF change records aren't supported"));
+                }
+                Some(entry) => {
+                    match entry
+                        .attributes
+                        .iter_mut()
+                        .find(|(n, _)| n.eq_ignore_ascii_case(name))
+                    {
+                        Some((_, values)) => values.push(value),
+                        None => entry.attributes.push((name.into(), vec![value])),
+                    }
+                }
+            }
+        }
+        if let Some(entry) = entry {
+            doc.nodes_mut().push(entry.into_node());
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+struct Entry {
+    dn: KdlEntry,
+    attributes: Vec<(String, Vec<KdlEntry>)>,
+}
+
+impl Entry {
+    fn into_node(self) -> KdlNode {
+        let KdlValue::String(dn) = self.dn.value else {
+            unreachable!("LDIF values are always strings");
+        };
+        let mut node = KdlNode::new(dn);
+        if let Some(ty) = self.dn.ty {
+            node.set_ty(ty);
+        }
+        for (name, mut values) in self.attributes {
+            if values.len() == 1 {
+                let mut value = values.pop().unwrap();
+                value.set_name(Some(name));
+                node.push(value);
+            } else {
+                let mut child = KdlNode::new(name);
+                child.entries_mut().extend(values);
+                node.ensure_children().nodes_mut().push(child);
+            }
+        }
+        node
+    }
+}
+
+/// Splits `s` into lines, joining folded lines (ones starting with a single
+/// space) onto the line before them, along with their starting offsets.
+fn unfold(s: &str) -> Vec<(usize, String)> {
+    let mut unfolded: Vec<(usize, String)> = Vec::new();
+    for (start, line) in lines(s) {
+        match (line.strip_prefix(' '), unfolded.last_mut()) {
+            (Some(continued), Some((_, last))) if !last.is_empty() => last.push_str(continued),
+            _ => unfolded.push((start, line.into())),
+        }
+    }
+    unfolded
+}
+
+/// Parses an `attribute: value`, `attribute:: base64` or `attribute:< url`
+/// line.
+fn parse_line(line: &str) -> Option<(&str, KdlEntry)> {
+    let (name, rest) = line.split_once(':')?;
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '-' | ';' | '.'))
+    {
+        return None;
+    }
+    let (ty, value) = if let Some(value) = rest.strip_prefix(':') {
+        (Some("base64"), value)
+    } else if let Some(value) = rest.strip_prefix('<') {
+        (Some("url"), value)
+    } else {
+        (None, rest)
+    };
+    let mut entry = KdlEntry::new(value.trim_start_matches(' '));
+    if let Some(ty) = ty {
+        entry.set_ty(ty);
+    }
+    Some((name, entry))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts_formatted;
+
+    #[test]
+    fn user_entries() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_ldif,
+            r#"version: 1
+
+# John's entry.
+dn: uid=jdoe,ou=people,dc=example,dc=com
+objectClass: top
+objectClass: person
+objectclass: inetOrgPerson
+uid: jdoe
+cn: John Doe
+description: A very long description that has been folded
+  onto a second line
+userPassword:: e1NTSEF9c2VjcmV0
+jpegPhoto:< file:///tmp/jdoe.jpg
+mail:
+
+dn:: dWlkPWrDtnJnLG91PXBlb3BsZQ==
+uid: jörg
+"#,
+            r#"
+"uid=jdoe,ou=people,dc=example,dc=com" uid=jdoe cn="John Doe" description="A very long description that has been folded onto a second line" userPassword=(base64)e1NTSEF9c2VjcmV0 jpegPhoto=(url)"file:///tmp/jdoe.jpg" mail="" {
+    objectClass top person inetOrgPerson
+}
+(base64)"dWlkPWrDtnJnLG91PXBlb3BsZQ==" uid=jörg
+"#,
+        )
+    }
+
+    #[test]
+    fn group_entries() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_ldif,
+            "dn: cn=admins,ou=groups,dc=example,dc=com\r\nobjectClass: groupOfNames\r\ncn: admins\r\nmember: uid=jdoe,ou=people,dc=example,dc=com\r\nmember: uid=asmith,ou=people,dc=example,dc=com\r\n\r\n\r\ndn: cn=empty,ou=groups,dc=example,dc=com\r\nobjectClass: groupOfNames\r\ndescription::\r\n",
+            r#"
+"cn=admins,ou=groups,dc=example,dc=com" objectClass=groupOfNames cn=admins {
+    member "uid=jdoe,ou=people,dc=example,dc=com" "uid=asmith,ou=people,dc=example,dc=com"
+}
+"cn=empty,ou=groups,dc=example,dc=com" objectClass=groupOfNames description=(base64)""
+"#,
+        )?;
+        assert!(KdlDocument::from_ldif("").unwrap().nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "cn: no dn\n",
+            "dn: cn=a\nnot an attribute\n",
+            "dn: cn=a\n: value\n",
+            "dn: cn=a\nchangetype: modify\nreplace: cn\ncn: b\n-\n",
+            "version: 1\nversion: 1\n",
+        ] {
+            assert!(KdlDocument::from_ldif(bad).is_err(), "{bad:?} should fail");
+        }
+    }
+}
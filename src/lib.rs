@@ -113,6 +113,75 @@
 //!   fails, try again with `v1`. For `KdlDocument`, a heuristic will be applied
 //!   if both `v1` and `v2` parsers fail, to pick which error(s) to return. For
 //!   other types, only the `v2` parser's errors will be returned.
+//! * `hjson` - Adds `KdlDocument::to_hjson`, for writing documents out as
+//!   [HJSON](https://hjson.github.io/).
+//! * `redis-config` - Adds `KdlDocument::from_redis_config`, for reading
+//!   [Redis](https://redis.io/) `redis.conf` configuration files.
+//! * `aws-config` - Adds `KdlDocument::from_aws_config` and
+//!   `KdlDocument::to_aws_config`, for converting from and to AWS CLI/SDK
+//!   configuration files such as `~/.aws/config`.
+//! * `apache` - Adds `KdlDocument::from_apache_config`, for reading [Apache HTTP
+//!   Server](https://httpd.apache.org/) configuration files.
+//! * `yaml` - Adds `KdlDocument::from_kubeconfig`,
+//!   `KdlDocument::from_k8s_manifest` and
+//!   `KdlDocument::from_prometheus_config`, for reading Kubernetes
+//!   [kubeconfig](https://kubernetes.io/docs/concepts/configuration/organize-cluster-access-kubeconfig/)
+//!   files and resource manifests, and [Prometheus](https://prometheus.io/)
+//!   YAML configuration files, and `KdlDocument::from_flat_yaml` for flat
+//!   `key: value` YAML files. Also adds `KdlDocument::to_k8s_configmap`, for
+//!   embedding a document in a Kubernetes ConfigMap, and
+//!   `KdlDocument::from_openapi`, for reading YAML
+//!   [OpenAPI](https://www.openapis.org/) 3.x specifications, and
+//!   `KdlDocument::from_netplan`, for reading [Netplan](https://netplan.io/)
+//!   network configuration files.
+//! * `systemd` - Adds `KdlDocument::from_systemd_unit`, for reading
+//!   [systemd](https://systemd.io/) unit files.
+//! * `nginx` - Adds `KdlDocument::from_nginx_config`, for reading
+//!   [nginx](https://nginx.org/) configuration files.
+//! * `testing` - Adds `KdlDocument::assert_structure`, for checking the shape of
+//!   a document in tests.
+//! * `hcl` - Adds `KdlDocument::to_hcl` and `KdlDocument::from_hcl`, for
+//!   converting to and from [HCL](https://github.com/hashicorp/hcl), as used by
+//!   Terraform.
+//! * `cargo` - Enables `KdlDocument::from_cargo_workspace`, which converts a
+//!   Cargo workspace manifest into a KDL document.
+//! * `ssh-config` - Enables `KdlDocument::from_ssh_config`, which converts an
+//!   OpenSSH client configuration file into a KDL document.
+//! * `debug-print` - Enables `KdlDocument::debug_print` and
+//!   `KdlNode::debug_print`, which print a detailed description of the AST to
+//!   stderr, along with `debug_string` methods that return it as a string.
+//!   Also enables `KdlDocument::print_node_tree`, which prints
+//!   `KdlDocument::sprint_node_tree` to stdout.
+//! * `pgpass` - Enables `KdlDocument::from_pgpass` and `KdlDocument::to_pgpass`,
+//!   which convert between PostgreSQL password files and KDL documents.
+//! * `known-hosts` - Enables `KdlDocument::from_known_hosts`, which converts an
+//!   OpenSSH `known_hosts` file into a KDL document.
+//! * `regex` - Enables `KdlDocument::nodes_matching` and
+//!   `KdlDocument::nodes_matching_recursive`, which find nodes whose names
+//!   match a regular expression.
+//! * `ron` - Enables `KdlDocument::to_ron`, which converts a KDL document into
+//!   [RON](https://github.com/ron-rs/ron).
+//! * `json` - Enables `KdlDocument::from_jq_output`, which converts a sequence of
+//!   JSON values, as printed by [jq](https://jqlang.org/), into a KDL document.
+//!   Also adds `KdlDocument::from_openapi`, for reading JSON
+//!   [OpenAPI](https://www.openapis.org/) 3.x specifications, and
+//!   `KdlDocument::from_vscode_settings` and `KdlDocument::to_vscode_settings`,
+//!   for converting from and to VS Code `settings.json` files.
+//! * `git-config` - Adds `KdlDocument::from_git_config`, for converting Git
+//!   configuration files such as `.git/config` and `~/.gitconfig` into KDL.
+//! * `dhall` - Adds `KdlDocument::from_dhall_type`, for generating schema
+//!   documents from [Dhall](https://dhall-lang.org/) record and union types.
+//! * `ldif` - Adds `KdlDocument::from_ldif`, for converting
+//!   [LDIF](https://www.rfc-editor.org/rfc/rfc2849) LDAP directory entries into
+//!   KDL.
+//! * `sftp` - Enables `KdlDocument::from_sftp_batch`, for converting `sftp` batch
+//!   files into KDL documents.
+//! * `fstab` - Enables `KdlDocument::from_fstab` and `KdlDocument::to_fstab`, for
+//!   converting between Linux `/etc/fstab` files and KDL documents.
+//! * `cron` - Enables `KdlDocument::from_crontab`, for converting crontab files
+//!   into KDL documents.
+//! * `postfix` - Enables `KdlDocument::from_postfix_main_cf`, for converting
+//!   Postfix `main.cf` files into KDL documents.
 //!
 //! ## Quirks
 //!
@@ -187,25 +256,116 @@
 #![doc(html_favicon_url = "https://kdl.dev/favicon.ico")]
 #![doc(html_logo_url = "https://kdl.dev/logo.svg")]
 
+pub use casing::*;
 pub use document::*;
 pub use entry::*;
 pub use error::*;
+pub use extract::*;
 pub use fmt::*;
+pub use frozen::*;
 pub use identifier::*;
 pub use node::*;
+pub use structured_log::*;
 // pub use query::*;
+pub use transform::*;
 pub use value::*;
+pub use value_map::*;
 
+#[cfg(feature = "apache")]
+mod apache;
+#[cfg(feature = "aws-config")]
+mod aws_config;
+#[cfg(feature = "cargo")]
+mod cargo_workspace;
+mod casing;
+#[cfg(feature = "cron")]
+mod crontab;
+#[cfg(feature = "debug-print")]
+mod debug_print;
+#[cfg(feature = "dhall")]
+mod dhall;
 mod document;
 mod entry;
 mod error;
+mod extract;
 mod fmt;
+mod frozen;
+#[cfg(feature = "fstab")]
+mod fstab;
+#[cfg(feature = "git-config")]
+mod git_config;
+#[cfg(feature = "hcl")]
+mod hcl;
+#[cfg(feature = "hjson")]
+mod hjson;
 mod identifier;
+#[cfg(feature = "json")]
+mod json;
+#[cfg(feature = "yaml")]
+mod k8s_manifest;
+#[cfg(feature = "known-hosts")]
+mod known_hosts;
+#[cfg(feature = "ldif")]
+mod ldif;
+#[cfg(any(
+    feature = "apache",
+    feature = "aws-config",
+    feature = "cron",
+    feature = "fstab",
+    feature = "json",
+    feature = "known-hosts",
+    feature = "ldif",
+    feature = "pgpass",
+    feature = "postfix",
+    feature = "redis-config",
+    feature = "sftp",
+    feature = "ssh-config",
+    feature = "systemd",
+))]
+mod lines;
+#[cfg(any(feature = "apache", feature = "nginx"))]
+mod nesting;
+#[cfg(feature = "yaml")]
+mod netplan;
+#[cfg(feature = "nginx")]
+mod nginx;
 mod node;
+#[cfg(feature = "regex")]
+mod nodes_matching;
+#[cfg(any(feature = "json", feature = "yaml"))]
+mod openapi;
+#[cfg(feature = "pgpass")]
+mod pgpass;
+#[cfg(feature = "postfix")]
+mod postfix;
+#[cfg(feature = "yaml")]
+mod prometheus;
 // mod nom_compat;
 // mod query;
 // mod query_parser;
+mod query_string;
 // mod v1_parser;
+#[cfg(feature = "redis-config")]
+mod redis_config;
+#[cfg(feature = "ron")]
+mod ron;
+#[cfg(feature = "sftp")]
+mod sftp;
+#[cfg(feature = "ssh-config")]
+mod ssh_config;
+mod structured_log;
+#[cfg(feature = "systemd")]
+mod systemd;
+#[cfg(test)]
+mod test_util;
+#[cfg(any(test, feature = "testing"))]
+mod testing;
+mod transform;
 mod value;
+mod value_map;
+#[cfg(feature = "json")]
+mod vscode_settings;
+#[cfg(feature = "yaml")]
+mod yaml;
 
 mod v2_parser;
@@ -0,0 +1,56 @@
+//! Line splitting shared by the parsers for line-based config formats.
+
+/// Iterates over the lines in `s`, without their `\n` or `\r\n` endings,
+/// along with the byte offset each line starts at.
+pub(crate) fn lines(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    let mut offset = 0;
+    s.split_inclusive('\n').map(move |raw| {
+        let start = offset;
+        offset += raw.len();
+        (start, raw.trim_end_matches(['\n', '\r']))
+    })
+}
+
+/// Like [`lines`], but with leading and trailing whitespace trimmed off each
+/// line, and each offset pointing at the first character that's left.
+#[allow(unused)]
+pub(crate) fn trimmed_lines(s: &str) -> impl Iterator<Item = (usize, &str)> {
+    lines(s).map(|(start, line)| {
+        let trimmed = line.trim_start();
+        (start + line.len() - trimmed.len(), trimmed.trim_end())
+    })
+}
+
+/// Splits `s` into trimmed logical lines, joining lines that end in `\`, and
+/// returns each of them with the span they cover in `s`.
+#[cfg(any(feature = "apache", feature = "systemd"))]
+pub(crate) fn logical_lines(s: &str) -> Vec<(String, (usize, usize))> {
+    let mut lines = Vec::new();
+    let mut current: Option<(String, usize)> = None;
+    for (start, trimmed) in trimmed_lines(s) {
+        let (text, continues) = match trimmed.strip_suffix('\\') {
+            Some(text) => (text.trim_end(), true),
+            None => (trimmed, false),
+        };
+        let (line, line_start) = match current.take() {
+            Some((mut line, line_start)) => {
+                if !line.is_empty() && !text.is_empty() {
+                    line.push(' ');
+                }
+                line.push_str(text);
+                (line, line_start)
+            }
+            None => (text.to_string(), start),
+        };
+        if continues {
+            current = Some((line, line_start));
+        } else {
+            let end = start + trimmed.len();
+            lines.push((line, (line_start, end - line_start)));
+        }
+    }
+    if let Some((line, line_start)) = current {
+        lines.push((line, (line_start, s.len() - line_start)));
+    }
+    lines
+}
@@ -0,0 +1,12 @@
+//! Helpers for parsers of config formats with nested blocks or sections.
+
+use crate::{KdlDocument, KdlNode};
+
+/// Adds `node` to the innermost block that's still open in `stack`, or to
+/// `doc` if there isn't one.
+pub(crate) fn push_node<T>(doc: &mut KdlDocument, stack: &mut [(KdlNode, T)], node: KdlNode) {
+    match stack.last_mut() {
+        Some((parent, _)) => parent.ensure_children().nodes_mut().push(node),
+        None => doc.nodes_mut().push(node),
+    }
+}
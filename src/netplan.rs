@@ -0,0 +1,294 @@
+use yaml_rust2::{yaml::Hash, Yaml};
+
+use crate::{
+    yaml::{key_to_string, load_single, scalar},
+    KdlDocument, KdlError, KdlNode,
+};
+
+impl KdlDocument {
+    /// Parses a [Netplan](https://netplan.io/) network configuration file
+    /// into a document.
+    ///
+    /// Each section under `network`, like `ethernets`, `wifis` or `bridges`,
+    /// becomes a toplevel node, with one child per interface. Interfaces, and
+    /// any mappings inside them, get their scalar settings as properties and
+    /// everything else as children: lists of scalars become a child with one
+    /// argument per item, and each item in `routes` becomes a `route` child:
+    ///
+    /// ```kdl
+    /// version 2
+    /// ethernets {
+    ///     enp3s0 dhcp4=#false {
+    ///         addresses "192.168.1.10/24"
+    ///         route to=default via="192.168.1.1"
+    ///         nameservers {
+    ///             addresses "1.1.1.1" "8.8.8.8"
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Scalar settings directly under `network`, like `version` and
+    /// `renderer`, become toplevel nodes with the value as their argument.
+    /// Other lists of mappings, like `routing-policy`, become one child named
+    /// `-` per item.
+    ///
+    /// Fails if the YAML is invalid, if it has more than one YAML document in
+    /// it, if it isn't a mapping with a single `network` mapping in it, or if
+    /// an item in `routes` isn't a mapping.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_netplan(r#"
+    /// network:
+    ///   version: 2
+    ///   ethernets:
+    ///     eth0:
+    ///       dhcp4: true
+    /// "#).unwrap();
+    /// assert_eq!(doc.to_string(), "version 2\nethernets {\n    eth0 dhcp4=#true\n}\n");
+    /// ```
+    pub fn from_netplan(yaml: &str) -> Result<KdlDocument, KdlError> {
+        let error = |message: &str| KdlError::single(yaml, (0, 0), message, None);
+        let mut doc = KdlDocument::new();
+        let hash = match load_single(yaml, "Netplan")? {
+            None | Some(Yaml::Null) => return Ok(doc),
+            Some(Yaml::Hash(hash)) => hash,
+            Some(_) => return Err(error("Expected Netplan config to be a YAML mapping")),
+        };
+        let mut network = None;
+        for (key, value) in hash {
+            match (key_to_string(&key).as_deref(), value) {
+                (Some("network"), Yaml::Hash(hash)) => network = Some(hash),
+                (Some("network"), _) => return Err(error("Expected `network` to be a mapping")),
+                _ => {
+                    return Err(error(
+                        "Netplan configs can only have a `network` key at the toplevel",
+                    ))
+                }
+            }
+        }
+        let network = network.ok_or_else(|| error("Expected a `network` mapping"))?;
+        for (key, value) in network {
+            let key = key_to_string(&key).ok_or_else(|| error("Unsupported YAML key"))?;
+            doc.nodes_mut()
+                .push(setting_node(&key, value).map_err(|msg| error(&msg))?);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Converts a Netplan setting into a node called `name`. Scalars and lists
+/// of scalars become arguments, and mappings are converted with
+/// [`mapping_node`].
+fn setting_node(name: &str, value: Yaml) -> Result<KdlNode, String> {
+    match value {
+        Yaml::Hash(hash) => mapping_node(name, hash),
+        Yaml::Array(items) if items.iter().all(|i| scalar(i).is_some()) => {
+            let mut node = KdlNode::new(name);
+            for item in &items {
+                node.push(scalar(item).unwrap());
+            }
+            Ok(node)
+        }
+        Yaml::Array(items) => {
+            let mut node = KdlNode::new(name);
+            let children = node.ensure_children();
+            for item in items {
+                children.nodes_mut().push(setting_node("-", item)?);
+            }
+            Ok(node)
+        }
+        value => {
+            let value = scalar(&value).ok_or("Unsupported YAML value")?;
+            Ok(KdlNode::new(name).with_appended_arg(value))
+        }
+    }
+}
+
+/// Converts a mapping, like an interface's settings, into a node called
+/// `name`, with scalar values as properties and everything else as children.
+fn mapping_node(name: &str, hash: Hash) -> Result<KdlNode, String> {
+    let mut node = KdlNode::new(name);
+    for (key, value) in hash {
+        let key = key_to_string(&key).ok_or("Unsupported YAML key")?;
+        if let Some(value) = scalar(&value) {
+            node.insert(key, value);
+            continue;
+        }
+        match (key.as_str(), value) {
+            ("routes", Yaml::Array(routes)) => {
+                for route in routes {
+                    let Yaml::Hash(route) = route else {
+                        return Err("Expected each route to be a mapping".into());
+                    };
+                    let route = mapping_node("route", route)?;
+                    node.ensure_children().nodes_mut().push(route);
+                }
+            }
+            (key, value) => {
+                let child = setting_node(key, value)?;
+                node.ensure_children().nodes_mut().push(child);
+            }
+        }
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn ethernet() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_netplan,
+            r#"
+# Static address with a default route.
+network:
+  version: 2
+  renderer: networkd
+  ethernets:
+    enp3s0:
+      dhcp4: false
+      mtu: 1500
+      addresses:
+        - 192.168.1.10/24
+        - "2001:db8::10/64"
+      routes:
+        - to: default
+          via: 192.168.1.1
+        - to: 10.0.0.0/8
+          via: 192.168.1.254
+          metric: 100
+      nameservers:
+        search: [example.com]
+        addresses: [1.1.1.1, 8.8.8.8]
+      routing-policy:
+        - from: 192.168.1.0/24
+          table: 101
+    enp4s0: {}
+"#,
+            r#"
+version 2
+renderer networkd
+ethernets {
+    enp3s0 dhcp4=#false mtu=1500 {
+        addresses "192.168.1.10/24" "2001:db8::10/64"
+        route to=default via="192.168.1.1"
+        route to="10.0.0.0/8" via="192.168.1.254" metric=100
+        nameservers {
+            search example.com
+            addresses "1.1.1.1" "8.8.8.8"
+        }
+        routing-policy {
+            - from="192.168.1.0/24" table=101
+        }
+    }
+    enp4s0
+}
+"#,
+        )?;
+        assert!(KdlDocument::from_netplan("").unwrap().nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn wifi() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_netplan,
+            r#"
+network:
+  version: 2
+  wifis:
+    wlp2s0:
+      dhcp4: true
+      access-points:
+        "Home Network":
+          password: "correct horse battery staple"
+        corp:
+          auth:
+            key-management: eap
+            method: peap
+            identity: jdoe@example.com
+            password: hunter2
+"#,
+            r#"
+version 2
+wifis {
+    wlp2s0 dhcp4=#true {
+        access-points {
+            "Home Network" password="correct horse battery staple"
+            corp {
+                auth key-management=eap method=peap identity="jdoe@example.com" password=hunter2
+            }
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn bridge() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_netplan,
+            r#"
+network:
+  version: 2
+  ethernets:
+    enp3s0:
+      dhcp4: no
+    enp4s0:
+      dhcp4: no
+  bridges:
+    br0:
+      interfaces: [enp3s0, enp4s0]
+      addresses: [10.0.0.2/24]
+      routes:
+        - to: default
+          via: 10.0.0.1
+      parameters:
+        stp: true
+        forward-delay: 4
+"#,
+            r#"
+version 2
+ethernets {
+    enp3s0 dhcp4=no
+    enp4s0 dhcp4=no
+}
+bridges {
+    br0 {
+        interfaces enp3s0 enp4s0
+        addresses "10.0.0.2/24"
+        route to=default via="10.0.0.1"
+        parameters stp=#true forward-delay=4
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "- network\n",
+            "network: 2\n",
+            "version: 2\n",
+            "network:\n  version: 2\nextra: 1\n",
+            "network:\n  ethernets:\n    eth0:\n      routes: [default]\n",
+            "network: {\n",
+            "network: {}\n---\nnetwork: {}\n",
+        ] {
+            assert!(
+                KdlDocument::from_netplan(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+    }
+}
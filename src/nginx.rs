@@ -0,0 +1,246 @@
+use crate::{nesting::push_node, KdlDocument, KdlError, KdlNode};
+
+impl KdlDocument {
+    /// Parses an [nginx](https://nginx.org/) configuration file (such as
+    /// `nginx.conf`) into a document.
+    ///
+    /// Each `name arg1 arg2;` directive becomes a node named after the
+    /// directive, with its arguments as string arguments. Directives followed
+    /// by a `{ }` block, like `http`, `server`, `location` or `upstream`, get
+    /// the block's directives as children. Quoted arguments are unquoted and
+    /// their escapes are processed, and `#` comments are skipped.
+    ///
+    /// `include` directives are kept as regular `include` nodes. The included
+    /// files are not read.
+    ///
+    /// Fails if a directive isn't terminated by `;` or a block, if a block is
+    /// unbalanced, or if a quoted argument isn't closed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_nginx_config(r#"
+    /// server {
+    ///     listen 80;
+    ///     location / { root /var/www/html; }
+    /// }
+    /// "#).unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "server {\n    listen \"80\"\n    location \"/\" {\n        root \"/var/www/html\"\n    }\n}\n"
+    /// );
+    /// ```
+    pub fn from_nginx_config(s: &str) -> Result<KdlDocument, KdlError> {
+        // Stack of currently-open blocks, with the span of their directive's
+        // name, for error reporting.
+        let mut stack: Vec<(KdlNode, (usize, usize))> = Vec::new();
+        let mut doc = KdlDocument::new();
+        // The directive currently being read, if any.
+        let mut current: Option<(KdlNode, (usize, usize))> = None;
+        for token in tokenize(s)? {
+            match token {
+                Token::Word(word, span) => match &mut current {
+                    Some((node, _)) => node.push(word),
+                    None => current = Some((KdlNode::new(word), span)),
+                },
+                Token::Semicolon(span) => {
+                    let (node, _) = current.take().ok_or_else(|| {
+                        KdlError::single(s, span, "Unexpected `;` without a directive", None)
+                    })?;
+                    push_node(&mut doc, &mut stack, node);
+                }
+                Token::Open(span) => {
+                    let (mut node, span) = current.take().ok_or_else(|| {
+                        KdlError::single(s, span, "Unexpected `{` without a directive", None)
+                    })?;
+                    node.ensure_children();
+                    stack.push((node, span));
+                }
+                Token::Close(span) => {
+                    if let Some((_, span)) = current {
+                        return Err(unterminated(s, span));
+                    }
+                    let (node, _) = stack.pop().ok_or_else(|| {
+                        KdlError::single(s, span, "Unexpected `}` without a matching `{`", None)
+                    })?;
+                    push_node(&mut doc, &mut stack, node);
+                }
+            }
+        }
+        if let Some((_, span)) = current {
+            return Err(unterminated(s, span));
+        }
+        if let Some((node, span)) = stack.pop() {
+            return Err(KdlError::single(
+                s,
+                span,
+                format!("Unclosed `{}` block", node.name().value()),
+                Some("Add a matching `}` for this block."),
+            ));
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+enum Token {
+    Word(String, (usize, usize)),
+    Semicolon((usize, usize)),
+    Open((usize, usize)),
+    Close((usize, usize)),
+}
+
+fn tokenize(s: &str) -> Result<Vec<Token>, KdlError> {
+    let mut tokens = Vec::new();
+    let mut chars = s.char_indices().peekable();
+    while let Some((start, c)) = chars.next() {
+        match c {
+            c if c.is_whitespace() => {}
+            '#' => while chars.next_if(|(_, c)| *c != '\n').is_some() {},
+            ';' => tokens.push(Token::Semicolon((start, 1))),
+            '{' => tokens.push(Token::Open((start, 1))),
+            '}' => tokens.push(Token::Close((start, 1))),
+            '"' | '\'' => {
+                let unterminated = || {
+                    KdlError::single(
+                        s,
+                        (start, s.len() - start),
+                        "Unterminated quoted string",
+                        Some("Add a closing quote."),
+                    )
+                };
+                let mut word = String::new();
+                let end = loop {
+                    match chars.next() {
+                        Some((end, q)) if q == c => break end + 1,
+                        Some((_, '\\')) => match chars.next() {
+                            Some((_, 'n')) => word.push('\n'),
+                            Some((_, 't')) => word.push('\t'),
+                            Some((_, 'r')) => word.push('\r'),
+                            Some((_, escaped)) => word.push(escaped),
+                            None => return Err(unterminated()),
+                        },
+                        Some((_, c)) => word.push(c),
+                        None => return Err(unterminated()),
+                    }
+                };
+                tokens.push(Token::Word(word, (start, end - start)));
+            }
+            c => {
+                let mut word = String::from(c);
+                let mut end = start + c.len_utf8();
+                while let Some((idx, c)) =
+                    chars.next_if(|(_, c)| !c.is_whitespace() && !matches!(c, ';' | '{' | '}'))
+                {
+                    word.push(c);
+                    end = idx + c.len_utf8();
+                }
+                tokens.push(Token::Word(word, (start, end - start)));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn unterminated(s: &str, span: (usize, usize)) -> KdlError {
+    KdlError::single(
+        s,
+        span,
+        "Unterminated nginx directive",
+        Some("End the directive with `;`, or give it a `{ }` block."),
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn minimal_config() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_nginx_config,
+            r#"
+user www-data;
+worker_processes auto;
+include /etc/nginx/modules-enabled/*.conf;
+
+events { worker_connections 768; }
+
+http {
+    include mime.types;
+    upstream backend {
+        server 127.0.0.1:8080 weight=3;
+        server 127.0.0.1:8081;
+    }
+
+    server {
+        listen 80 default_server; # IPv4
+        server_name example.com www.example.com;
+        add_header X-Frame-Options "SAMEORIGIN" always;
+        log_format main '$remote_addr "$request"';
+
+        location / {
+            try_files $uri $uri/ =404;
+        }
+        location ~ \.php$ {
+            proxy_pass http://backend;
+        }
+        location = /empty {}
+    }
+}
+"#,
+            r#"
+user "www-data"
+worker_processes auto
+include "/etc/nginx/modules-enabled/*.conf"
+events {
+    worker_connections "768"
+}
+http {
+    include "mime.types"
+    upstream backend {
+        server "127.0.0.1:8080" "weight=3"
+        server "127.0.0.1:8081"
+    }
+    server {
+        listen "80" default_server
+        server_name "example.com" "www.example.com"
+        add_header X-Frame-Options SAMEORIGIN always
+        log_format main "$remote_addr \"$request\""
+        location "/" {
+            try_files "$uri" "$uri/" "=404"
+        }
+        location "~" "\\.php$" {
+            proxy_pass "http://backend"
+        }
+        location "=" "/empty" {}
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "worker_processes auto",
+            "http { server { }",
+            "http { } }",
+            "; user nobody;",
+            "{ listen 80; }",
+            "http { listen 80 }",
+            "add_header X \"unterminated;",
+        ] {
+            assert!(
+                KdlDocument::from_nginx_config(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        let err = KdlDocument::from_nginx_config("http {\n  server {\n  }\n").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (0, 4).into());
+        let err = KdlDocument::from_nginx_config("user nobody;\nlisten 80").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (13, 6).into());
+    }
+}
@@ -246,6 +246,14 @@ impl KdlNode {
         self.children_mut().as_mut().unwrap()
     }
 
+    /// Consumes this node, returning its children as a standalone document,
+    /// or `None` if it has no children block. Everything else about the node,
+    /// including its entries, is dropped. This is the inverse of
+    /// [`KdlDocument::wrap_in_node`].
+    pub fn into_document(self) -> Option<KdlDocument> {
+        self.children
+    }
+
     /// Gets the formatting details (including whitespace and comments) for this node.
     pub fn format(&self) -> Option<&KdlNodeFormat> {
         self.format.as_ref()
@@ -544,7 +552,7 @@ impl KdlNode {
     /// Returns a mutable iterator over the node's children, if any. Nodes
     /// without children will return an empty iterator.
     pub fn iter_children_mut(&mut self) -> ChildrenIterMut<'_> {
-        ChildrenIterMut(self.children.as_mut().map(|x| x.nodes.iter_mut()))
+        ChildrenIterMut(self.children.as_mut().map(|x| x.nodes_mut().iter_mut()))
     }
 
     /// Gets a value by key. Number keys will look up arguments, strings will
@@ -579,6 +587,36 @@ impl KdlNode {
         self.entries.push(entry.into());
     }
 
+    /// Consuming version of [`KdlNode::push`] for a new argument, for
+    /// building up nodes in a single expression.
+    pub fn with_appended_arg(mut self, value: impl Into<KdlValue>) -> Self {
+        self.push(KdlEntry::new(value));
+        self
+    }
+
+    /// Consuming version of [`KdlNode::insert`] for a property, for building
+    /// up nodes in a single expression. As with `insert`, an existing property
+    /// with the same name will be replaced.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlNode;
+    /// let node = KdlNode::new("server")
+    ///     .with_appended_arg("main")
+    ///     .with_property("host", "localhost")
+    ///     .with_property("port", 8080);
+    /// assert_eq!(node.to_string(), "server main host=localhost port=8080\n");
+    /// ```
+    pub fn with_property(
+        mut self,
+        name: impl Into<KdlIdentifier>,
+        value: impl Into<KdlValue>,
+    ) -> Self {
+        self.insert_impl(NodeKey::Key(name.into()), KdlEntry::new(value));
+        self
+    }
+
     /// Inserts an entry into this node. If an entry already exists with the
     /// same string key, it will be replaced and the previous entry will be
     /// returned.
@@ -949,6 +987,24 @@ mod test {
         Ok(())
     }
 
+    #[test]
+    fn keyword_strings_display_quoted() -> miette::Result<()> {
+        let mut node = KdlNode::new("null");
+        node.push(KdlEntry::new("inf"));
+        node.push(KdlEntry::new_prop("true", "nan"));
+        node.set_ty("false");
+        let expected = "(\"false\")\"null\" \"inf\" \"true\"=\"nan\"\n";
+        assert_eq!(node.to_string(), expected);
+        node.autoformat();
+        assert_eq!(node.to_string(), expected);
+        let parsed: KdlNode = node.to_string().parse()?;
+        assert_eq!(parsed.name().value(), "null");
+        assert_eq!(parsed.ty().map(|ty| ty.value()), Some("false"));
+        assert_eq!(parsed[0], "inf".into());
+        assert_eq!(parsed["true"], "nan".into());
+        Ok(())
+    }
+
     #[test]
     fn indexing() {
         let mut node = KdlNode::new("foo");
@@ -986,6 +1042,20 @@ mod test {
         assert_eq!(node[3], "pos1".into());
     }
 
+    #[test]
+    fn builder_methods() {
+        let node = KdlNode::new("foo")
+            .with_appended_arg(1)
+            .with_property("a", "x")
+            .with_appended_arg(false)
+            .with_property("a", "y");
+        assert_eq!(node.entries().len(), 3);
+        assert_eq!(node[0], 1.into());
+        assert_eq!(node[1], false.into());
+        assert_eq!(node["a"], "y".into());
+        assert_eq!(node.to_string(), "foo 1 a=y #false\n");
+    }
+
     #[test]
     fn removal() {
         let mut node = KdlNode::new("foo");
@@ -1018,4 +1088,19 @@ mod test {
         assert_eq!(node.entries().len(), 1, "key removal should succeed");
         node.remove(0); // should panic here
     }
+
+    #[test]
+    fn into_document() -> miette::Result<()> {
+        let node: KdlNode = "parent 1 {\n    a\n    b key=value\n}".parse()?;
+        let children = node.children().cloned();
+        assert_eq!(node.into_document(), children);
+        assert_eq!(KdlNode::new("leaf").into_document(), None);
+
+        let doc: KdlDocument = "a\nb key=value".parse()?;
+        let node = doc.clone().wrap_in_node("parent");
+        assert_eq!(node.name().value(), "parent");
+        assert!(node.entries().is_empty());
+        assert_eq!(node.into_document(), Some(doc));
+        Ok(())
+    }
 }
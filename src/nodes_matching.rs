@@ -0,0 +1,138 @@
+use regex::Regex;
+
+use crate::{KdlDocument, KdlNode};
+
+impl KdlDocument {
+    /// Returns an iterator over this document's toplevel nodes whose names
+    /// match the regular expression `pattern`. Like [`Regex::is_match`], the
+    /// pattern can match anywhere in the name unless it's anchored with `^`
+    /// or `$`.
+    ///
+    /// This is only available with the `regex` feature.
+    ///
+    /// Fails if `pattern` isn't a valid regular expression.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "server-a\nserver-b\nclient".parse().unwrap();
+    /// let servers: Vec<_> = doc
+    ///     .nodes_matching("^server-")
+    ///     .unwrap()
+    ///     .map(|n| n.name().value())
+    ///     .collect();
+    /// assert_eq!(servers, vec!["server-a", "server-b"]);
+    /// ```
+    pub fn nodes_matching<'a>(
+        &'a self,
+        pattern: &str,
+    ) -> Result<impl Iterator<Item = &'a KdlNode>, regex::Error> {
+        Ok(NodesMatching {
+            regex: Regex::new(pattern)?,
+            nodes: self.nodes().iter(),
+        })
+    }
+
+    /// Like [`KdlDocument::nodes_matching`], but also includes matching
+    /// nodes at any depth, in document order (each node comes right before
+    /// its children).
+    ///
+    /// This is only available with the `regex` feature.
+    pub fn nodes_matching_recursive<'a>(
+        &'a self,
+        pattern: &str,
+    ) -> Result<impl Iterator<Item = &'a KdlNode>, regex::Error> {
+        Ok(NodesMatching {
+            regex: Regex::new(pattern)?,
+            nodes: self.descendants(),
+        })
+    }
+}
+
+/// Filters `nodes` by name, holding on to the compiled regex.
+struct NodesMatching<I> {
+    regex: Regex,
+    nodes: I,
+}
+
+impl<'a, I: Iterator<Item = &'a KdlNode>> Iterator for NodesMatching<I> {
+    type Item = &'a KdlNode;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        let regex = &self.regex;
+        self.nodes.find(|node| regex.is_match(node.name().value()))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DOC: &str = r#"
+http-server port=80 {
+    route-index
+    static-files
+}
+grpc-server port=9000
+worker1
+worker2 {
+    worker-pool
+}
+workerA
+"#;
+
+    fn names<'a>(nodes: impl Iterator<Item = &'a KdlNode>) -> Vec<&'a str> {
+        nodes.map(|n| n.name().value()).collect()
+    }
+
+    #[test]
+    fn prefix() -> miette::Result<()> {
+        let doc: KdlDocument = DOC.parse()?;
+        assert_eq!(
+            names(doc.nodes_matching("^worker").unwrap()),
+            vec!["worker1", "worker2", "workerA"]
+        );
+        assert_eq!(
+            names(doc.nodes_matching_recursive("^(route|worker)-").unwrap()),
+            vec!["route-index", "worker-pool"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn suffix() -> miette::Result<()> {
+        let doc: KdlDocument = DOC.parse()?;
+        assert_eq!(
+            names(doc.nodes_matching("-server$").unwrap()),
+            vec!["http-server", "grpc-server"]
+        );
+        assert_eq!(
+            names(doc.nodes_matching_recursive("s$").unwrap()),
+            vec!["static-files"]
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn character_class() -> miette::Result<()> {
+        let doc: KdlDocument = DOC.parse()?;
+        assert_eq!(
+            names(doc.nodes_matching(r"^worker\d$").unwrap()),
+            vec!["worker1", "worker2"]
+        );
+        assert_eq!(
+            names(doc.nodes_matching_recursive("[-][a-z]+[-]?").unwrap()),
+            vec![
+                "http-server",
+                "route-index",
+                "static-files",
+                "grpc-server",
+                "worker-pool"
+            ]
+        );
+        assert!(doc.nodes_matching("[unclosed").is_err());
+        assert!(doc.nodes_matching_recursive("(").is_err());
+        Ok(())
+    }
+}
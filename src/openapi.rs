@@ -0,0 +1,375 @@
+#[cfg(feature = "yaml")]
+use yaml_rust2::Yaml;
+
+use crate::{KdlDocument, KdlError, KdlNode, KdlValue};
+
+impl KdlDocument {
+    /// Parses an [OpenAPI](https://spec.openapis.org/oas/v3.1.0) 3.x
+    /// specification into a document.
+    ///
+    /// Each toplevel key (`openapi`, `info`, `paths`, `components`, and any
+    /// others like `servers`) becomes a toplevel node of the same name. Under
+    /// `paths`, each path item becomes a node named after its path, with its
+    /// operations as children named after their HTTP method. Schemas, both
+    /// inline and under `components`, have their `properties` as further
+    /// children, named after each property:
+    ///
+    /// ```kdl
+    /// openapi "3.0.3"
+    /// paths {
+    ///     "/pets/{id}" {
+    ///         get {
+    ///             operationId getPet
+    ///             responses {
+    ///                 "200" {
+    ///                     description OK
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// components {
+    ///     schemas {
+    ///         Pet {
+    ///             type object
+    ///             required id
+    ///             properties {
+    ///                 id {
+    ///                     type integer
+    ///                 }
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Scalars become a node's only argument, mappings become children, and
+    /// lists of scalars become multiple arguments. Lists containing mappings
+    /// or lists, like `parameters` or `servers`, become one child named `-`
+    /// per item.
+    ///
+    /// With the `json` feature, specifications starting with `{` are parsed
+    /// as JSON. With the `yaml` feature, anything else is parsed as YAML, so
+    /// both `openapi.json` and `openapi.yaml` files can be read.
+    ///
+    /// Fails if the specification isn't valid JSON or YAML, if it isn't an
+    /// OpenAPI 3.x document (including older Swagger 2.0 ones), or if
+    /// `paths` or any of its path items aren't mappings.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_openapi(
+    ///     r#"{"openapi": "3.0.3", "info": {"title": "Pets", "version": "1.0"}, "paths": {}}"#,
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "openapi \"3.0.3\"\ninfo {\n    title Pets\n    version \"1.0\"\n}\npaths {\n}\n"
+    /// );
+    /// ```
+    pub fn from_openapi(spec: &str) -> Result<KdlDocument, KdlError> {
+        let error = |message: &str| KdlError::single(spec, (0, 0), message, None);
+        let SpecValue::Map(spec_map) = parse(spec)? else {
+            return Err(error("Expected an OpenAPI document to be a mapping"));
+        };
+        let version = spec_map.iter().find_map(|(key, value)| match value {
+            SpecValue::Scalar(KdlValue::String(version)) if key == "openapi" => Some(version),
+            _ => None,
+        });
+        if !version.is_some_and(|v| v.starts_with("3.")) {
+            return Err(error(
+                "Expected an OpenAPI 3.x document, with an `openapi: 3.x.y` version",
+            ));
+        }
+        let mut doc = KdlDocument::new();
+        for (key, value) in spec_map {
+            if key == "paths" {
+                let SpecValue::Map(paths) = &value else {
+                    return Err(error("Expected `paths` to be a mapping"));
+                };
+                if paths
+                    .iter()
+                    .any(|(_, item)| !matches!(item, SpecValue::Map(_)))
+                {
+                    return Err(error("Expected each path item to be a mapping"));
+                }
+            }
+            doc.nodes_mut().push(value.into_node(&key));
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// A JSON or YAML value, so both can be converted the same way.
+enum SpecValue {
+    Scalar(KdlValue),
+    List(Vec<SpecValue>),
+    Map(Vec<(String, SpecValue)>),
+}
+
+impl SpecValue {
+    fn into_node(self, name: &str) -> KdlNode {
+        let mut node = KdlNode::new(name);
+        match self {
+            SpecValue::Scalar(value) => node.push(value),
+            SpecValue::Map(map) => {
+                let children = node.ensure_children();
+                for (key, value) in map {
+                    children.nodes_mut().push(value.into_node(&key));
+                }
+            }
+            SpecValue::List(items) if items.iter().all(|i| matches!(i, SpecValue::Scalar(_))) => {
+                for item in items {
+                    if let SpecValue::Scalar(value) = item {
+                        node.push(value);
+                    }
+                }
+            }
+            SpecValue::List(items) => {
+                let children = node.ensure_children();
+                for item in items {
+                    children.nodes_mut().push(item.into_node("-"));
+                }
+            }
+        }
+        node
+    }
+}
+
+#[cfg(feature = "json")]
+fn parse_json(spec: &str) -> Result<SpecValue, KdlError> {
+    fn convert(value: serde_json::Value) -> SpecValue {
+        match value {
+            serde_json::Value::Array(items) => {
+                SpecValue::List(items.into_iter().map(convert).collect())
+            }
+            serde_json::Value::Object(object) => SpecValue::Map(
+                object
+                    .into_iter()
+                    .map(|(key, value)| (key, convert(value)))
+                    .collect(),
+            ),
+            value => SpecValue::Scalar(
+                crate::json::scalar(&value).expect("only arrays and objects aren't scalars"),
+            ),
+        }
+    }
+    let value = serde_json::from_str(spec).map_err(|e| {
+        let offset = crate::json::error_offset(spec, &e).min(spec.len());
+        KdlError::single(spec, (offset, 0), "Invalid JSON", Some(&e.to_string()))
+    })?;
+    Ok(convert(value))
+}
+
+#[cfg(feature = "yaml")]
+fn parse(spec: &str) -> Result<SpecValue, KdlError> {
+    fn convert(value: Yaml) -> Result<SpecValue, &'static str> {
+        Ok(match value {
+            Yaml::Array(items) => {
+                SpecValue::List(items.into_iter().map(convert).collect::<Result<_, _>>()?)
+            }
+            Yaml::Hash(hash) => SpecValue::Map(
+                hash.into_iter()
+                    .map(|(key, value)| {
+                        let key = crate::yaml::key_to_string(&key).ok_or("Unsupported YAML key")?;
+                        Ok((key, convert(value)?))
+                    })
+                    .collect::<Result<_, &'static str>>()?,
+            ),
+            value => {
+                SpecValue::Scalar(crate::yaml::scalar(&value).ok_or("Unsupported YAML value")?)
+            }
+        })
+    }
+    #[cfg(feature = "json")]
+    if spec.trim_start().starts_with('{') {
+        return parse_json(spec);
+    }
+    let value = crate::yaml::load_single(spec, "OpenAPI")?.unwrap_or(Yaml::Null);
+    convert(value).map_err(|message| KdlError::single(spec, (0, 0), message, None))
+}
+
+#[cfg(not(feature = "yaml"))]
+fn parse(spec: &str) -> Result<SpecValue, KdlError> {
+    parse_json(spec)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    const EXPECTED: &str = r##"
+openapi "3.0.3"
+info {
+    title "Pet Store"
+    version "1.0.0"
+}
+paths {
+    "/pets/{petId}" {
+        get {
+            operationId showPetById
+            parameters {
+                - {
+                    name petId
+                    in path
+                    required #true
+                    schema {
+                        type string
+                    }
+                }
+            }
+            responses {
+                "200" {
+                    description "A pet"
+                    content {
+                        "application/json" {
+                            schema {
+                                "$ref" "#/components/schemas/Pet"
+                            }
+                        }
+                    }
+                }
+            }
+        }
+    }
+}
+components {
+    schemas {
+        Pet {
+            type object
+            required id name
+            properties {
+                id {
+                    type integer
+                    format int64
+                }
+                name {
+                    type string
+                }
+                tag {
+                    type string
+                    nullable #true
+                }
+            }
+        }
+    }
+}
+"##;
+
+    #[cfg(feature = "json")]
+    #[test]
+    fn minimal_json() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_openapi,
+            r##"{
+  "openapi": "3.0.3",
+  "info": {"title": "Pet Store", "version": "1.0.0"},
+  "paths": {
+    "/pets/{petId}": {
+      "get": {
+        "operationId": "showPetById",
+        "parameters": [
+          {"name": "petId", "in": "path", "required": true, "schema": {"type": "string"}}
+        ],
+        "responses": {
+          "200": {
+            "description": "A pet",
+            "content": {
+              "application/json": {
+                "schema": {"$ref": "#/components/schemas/Pet"}
+              }
+            }
+          }
+        }
+      }
+    }
+  },
+  "components": {
+    "schemas": {
+      "Pet": {
+        "type": "object",
+        "required": ["id", "name"],
+        "properties": {
+          "id": {"type": "integer", "format": "int64"},
+          "name": {"type": "string"},
+          "tag": {"type": "string", "nullable": true}
+        }
+      }
+    }
+  }
+}"##,
+            EXPECTED,
+        )
+    }
+
+    #[cfg(feature = "yaml")]
+    #[test]
+    fn minimal_yaml() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_openapi,
+            r##"
+openapi: "3.0.3"
+info:
+  title: Pet Store
+  version: "1.0.0"
+paths:
+  /pets/{petId}:
+    get:
+      operationId: showPetById
+      parameters:
+        - name: petId
+          in: path
+          required: true
+          schema:
+            type: string
+      responses:
+        "200":
+          description: A pet
+          content:
+            application/json:
+              schema:
+                $ref: "#/components/schemas/Pet"
+components:
+  schemas:
+    Pet:
+      type: object
+      required: [id, name]
+      properties:
+        id:
+          type: integer
+          format: int64
+        name:
+          type: string
+        tag:
+          type: string
+          nullable: true
+"##,
+            EXPECTED,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        let mut bad = vec![
+            "[]",
+            r#"{"swagger": "2.0", "paths": {}}"#,
+            r#"{"openapi": 3, "paths": {}}"#,
+            r#"{"openapi": "3.1.0", "paths": []}"#,
+            r#"{"openapi": "3.1.0", "paths": {"/": 1}}"#,
+            r#"{"openapi": "3.1.0", "paths": {"#,
+        ];
+        if cfg!(feature = "yaml") {
+            bad.extend(["", "openapi: '3.0.0'\npaths: [\n", "swagger: '2.0'\n"]);
+        }
+        for bad in bad {
+            assert!(
+                KdlDocument::from_openapi(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+    }
+}
@@ -0,0 +1,226 @@
+use crate::{error::span_or_default, lines::lines, KdlDocument, KdlError, KdlNode, KdlValue};
+
+/// The fields of a `.pgpass` line, in order.
+const FIELDS: [&str; 5] = ["host", "port", "database", "user", "password"];
+
+impl KdlDocument {
+    /// Parses a PostgreSQL [password
+    /// file](https://www.postgresql.org/docs/current/libpq-pgpass.html)
+    /// (`~/.pgpass`) into a document.
+    ///
+    /// Each `hostname:port:database:username:password` line becomes a
+    /// `connection` node with `host`, `port`, `database`, `user` and
+    /// `password` properties. Numeric ports become integers, and everything
+    /// else is kept as a string, including `*` wildcards. Escaped colons and
+    /// backslashes (`\:` and `\\`) are unescaped. Empty lines and lines
+    /// starting with `#` are skipped.
+    ///
+    /// Fails if a line doesn't have exactly five fields.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_pgpass("db.example.com:5432:*:app:s3cr\\:t\n").unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "connection host=db.example.com port=5432 database=* user=app password=s3cr:t\n"
+    /// );
+    /// assert_eq!(doc.to_pgpass().unwrap(), "db.example.com:5432:*:app:s3cr\\:t\n");
+    /// ```
+    pub fn from_pgpass(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        for (start, line) in lines(s) {
+            if line.trim().is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let fields = split_fields(line);
+            if fields.len() != FIELDS.len() {
+                return Err(KdlError::single(
+                    s,
+                    (start, line.len()),
+                    format!(
+                        "Expected 5 colon-separated fields, found {}",
+                        fields.len()
+                    ),
+                    Some("Lines look like `hostname:port:database:username:password`. Escape literal colons as `\\:`."),
+                ));
+            }
+            let mut node = KdlNode::new("connection");
+            for (name, field) in FIELDS.iter().zip(fields) {
+                let value = match field.parse::<i128>() {
+                    Ok(port) if *name == "port" => KdlValue::Integer(port),
+                    _ => KdlValue::String(field),
+                };
+                node.insert(*name, value);
+            }
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+
+    /// Converts a document into a PostgreSQL password file, with one line
+    /// per `connection` node. This is the reverse of
+    /// [`KdlDocument::from_pgpass`].
+    ///
+    /// Missing `host`, `port`, `database` and `user` properties are written
+    /// as `*` wildcards, and colons and backslashes in values are escaped.
+    ///
+    /// Fails if there are nodes other than `connection` nodes, if a node has
+    /// arguments, children, or properties other than the five `.pgpass`
+    /// fields, if it doesn't have a `password`, or if a value isn't a string
+    /// (or an integer, for `port`).
+    pub fn to_pgpass(&self) -> Result<String, KdlError> {
+        let mut out = String::new();
+        for node in self.nodes() {
+            if node.name().value() != "connection" {
+                return Err(KdlError::in_document(
+                    self,
+                    span_or_default(node),
+                    "Only `connection` nodes can be written to a .pgpass file",
+                ));
+            }
+            if node.children().is_some_and(|c| !c.nodes().is_empty()) {
+                return Err(KdlError::in_document(
+                    self,
+                    span_or_default(node),
+                    "Connections can't have children",
+                ));
+            }
+            for entry in node.iter() {
+                let known = entry
+                    .name()
+                    .is_some_and(|name| FIELDS.contains(&name.value()));
+                if !known {
+                    return Err(KdlError::in_document(
+                        self,
+                        span_or_default(entry),
+                        "Connections can only have host, port, database, user and password properties",
+                    ));
+                }
+            }
+            let mut fields = Vec::with_capacity(FIELDS.len());
+            for name in FIELDS {
+                let field = match node.entry(name) {
+                    Some(entry) => match entry.value() {
+                        KdlValue::String(s) => escape(s),
+                        KdlValue::Integer(i) if name == "port" => i.to_string(),
+                        _ => {
+                            return Err(KdlError::in_document(
+                                self,
+                                span_or_default(entry),
+                                format!("`{name}` must be a string"),
+                            ))
+                        }
+                    },
+                    None if name == "password" => {
+                        return Err(KdlError::in_document(
+                            self,
+                            span_or_default(node),
+                            "Connections must have a `password`",
+                        ))
+                    }
+                    None => "*".into(),
+                };
+                fields.push(field);
+            }
+            out.push_str(&fields.join(":"));
+            out.push('\n');
+        }
+        Ok(out)
+    }
+}
+
+/// Splits a line on `:`, unescaping `\:` and `\\` along the way.
+fn split_fields(line: &str) -> Vec<String> {
+    let mut fields = vec![String::new()];
+    let mut chars = line.chars();
+    while let Some(c) = chars.next() {
+        match c {
+            '\\' => match chars.next() {
+                Some(escaped @ (':' | '\\')) => fields.last_mut().unwrap().push(escaped),
+                Some(other) => {
+                    let field = fields.last_mut().unwrap();
+                    field.push('\\');
+                    field.push(other);
+                }
+                None => fields.last_mut().unwrap().push('\\'),
+            },
+            ':' => fields.push(String::new()),
+            c => fields.last_mut().unwrap().push(c),
+        }
+    }
+    fields
+}
+
+fn escape(field: &str) -> String {
+    field.replace('\\', "\\\\").replace(':', "\\:")
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    const PGPASS: &str = r#"# Production
+db.example.com:5432:app:app_user:hunter2
+*:*:*:postgres:pa\:ss\\word
+
+# Local socket, default port
+localhost:*:test_db:tester:with\:colons\:
+"#;
+
+    #[test]
+    fn from_pgpass() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_pgpass,
+            PGPASS,
+            r#"
+connection host=db.example.com port=5432 database=app user=app_user password=hunter2
+connection host="*" port="*" database="*" user=postgres password="pa:ss\\word"
+connection host=localhost port="*" database=test_db user=tester password="with:colons:"
+"#,
+        )?;
+        assert!(KdlDocument::from_pgpass("").unwrap().nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn to_pgpass() -> miette::Result<()> {
+        let doc = KdlDocument::from_pgpass(PGPASS)?;
+        assert_eq!(
+            doc.to_pgpass()?,
+            "db.example.com:5432:app:app_user:hunter2\n*:*:*:postgres:pa\\:ss\\\\word\nlocalhost:*:test_db:tester:with\\:colons\\:\n"
+        );
+        assert_eq!(KdlDocument::from_pgpass(&doc.to_pgpass()?)?, doc);
+
+        let doc: KdlDocument = "connection user=admin password=x".parse()?;
+        assert_eq!(doc.to_pgpass()?, "*:*:*:admin:x\n");
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> miette::Result<()> {
+        // Unescaped colons in the password make for too many fields.
+        let err = KdlDocument::from_pgpass("localhost:*:db:user:with:colons\n").unwrap_err();
+        assert_eq!(
+            err.diagnostics[0].message.as_deref(),
+            Some("Expected 5 colon-separated fields, found 6")
+        );
+        assert!(KdlDocument::from_pgpass("host:5432:db:user\n").is_err());
+
+        for bad in [
+            "server password=x",
+            "connection user=admin",
+            "connection extra=1 password=x",
+            "connection \"arg\" password=x",
+            "connection port=#true password=x",
+            "connection password=x {\n    child\n}",
+        ] {
+            let doc: KdlDocument = bad.parse()?;
+            assert!(doc.to_pgpass().is_err(), "{bad:?} should fail");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,146 @@
+use crate::{lines::lines, KdlDocument, KdlError, KdlNode};
+
+impl KdlDocument {
+    /// Parses a Postfix [`main.cf`](https://www.postfix.org/postconf.5.html)
+    /// file into a document.
+    ///
+    /// Each `parameter = value` line becomes a node named after the
+    /// parameter, with the value as its only argument, always as a string:
+    ///
+    /// ```kdl
+    /// myhostname mail.example.com
+    /// smtpd_banner "$myhostname ESMTP $mail_name"
+    /// ```
+    ///
+    /// Lines starting with whitespace continue the value on the line before
+    /// them, and the pieces are joined with single spaces. References to
+    /// other parameters, like `$myhostname` or `${mydomain}`, are kept as
+    /// written, without being expanded. Blank lines and lines starting with
+    /// `#` are skipped, even in the middle of a multi-line value, but a `#`
+    /// anywhere else is part of the value, as it is for Postfix. Like
+    /// Postfix, a parameter that's set more than once keeps its last value.
+    ///
+    /// This is only available with the `postfix` feature.
+    ///
+    /// Fails if a line isn't a `parameter = value` line, or if the file
+    /// starts with a continuation line.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_postfix_main_cf(
+    ///     "# Domains.\nmydestination = $myhostname,\n    localhost\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.to_string(), "mydestination \"$myhostname, localhost\"\n");
+    /// ```
+    pub fn from_postfix_main_cf(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        let mut params: Vec<(String, String)> = Vec::new();
+        for (start, line) in lines(s) {
+            let trimmed = line.trim();
+            if trimmed.is_empty() || trimmed.starts_with('#') {
+                continue;
+            }
+            let error = |message: &str, help: Option<&str>| {
+                KdlError::single(s, (start, line.len()), message, help)
+            };
+            if line.starts_with(char::is_whitespace) {
+                let Some((_, value)) = params.last_mut() else {
+                    return Err(error(
+                        "Expected a parameter before this continuation line",
+                        Some("Lines starting with whitespace continue the previous parameter's value."),
+                    ));
+                };
+                if !value.is_empty() {
+                    value.push(' ');
+                }
+                value.push_str(trimmed);
+                continue;
+            }
+            let (name, value) = line
+                .split_once('=')
+                .map(|(name, value)| (name.trim_end(), value.trim()))
+                .filter(|(name, _)| !name.is_empty() && !name.contains(char::is_whitespace))
+                .ok_or_else(|| error("Expected a `parameter = value` line", None))?;
+            params.retain(|(existing, _)| existing != name);
+            params.push((name.into(), value.into()));
+        }
+        for (name, value) in params {
+            doc.nodes_mut()
+                .push(KdlNode::new(name).with_appended_arg(value));
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn main_cf() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_postfix_main_cf,
+            r#"# See /usr/share/postfix/main.cf.dist for a commented, more complete version
+
+smtpd_banner = $myhostname ESMTP $mail_name (Ubuntu)
+biff = no
+myhostname=mail.example.com
+mydomain = example.com
+mydestination = $myhostname, localhost.$mydomain, ${mydomain}
+mailbox_size_limit = 0
+relayhost =
+   # Transports, in order.
+transport_maps =
+    hash:/etc/postfix/transport,
+    # Not used yet.
+    regexp:/etc/postfix/transport.regexp
+alias_maps = hash:/etc/aliases # not a comment
+"#,
+            r##"
+smtpd_banner "$myhostname ESMTP $mail_name (Ubuntu)"
+biff no
+myhostname mail.example.com
+mydomain example.com
+mydestination "$myhostname, localhost.$mydomain, ${mydomain}"
+mailbox_size_limit "0"
+relayhost ""
+transport_maps "hash:/etc/postfix/transport, regexp:/etc/postfix/transport.regexp"
+alias_maps "hash:/etc/aliases # not a comment"
+"##,
+        )?;
+        assert!(KdlDocument::from_postfix_main_cf("")
+            .unwrap()
+            .nodes()
+            .is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn repeated_parameters() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_postfix_main_cf,
+            "myhostname = old.example.com\nbiff = no\nmyhostname = mail.example.com\n",
+            "biff no\nmyhostname mail.example.com\n",
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "  continued = value\n",
+            "not a parameter\n",
+            "= value\n",
+            "two words = value\n",
+        ] {
+            assert!(
+                KdlDocument::from_postfix_main_cf(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+    }
+}
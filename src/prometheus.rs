@@ -0,0 +1,259 @@
+use yaml_rust2::Yaml;
+
+use crate::{
+    yaml::{convert_yaml, key_to_string, load_single, scalar, ListItems, YamlConversion},
+    KdlDocument, KdlError, KdlNode,
+};
+
+impl KdlDocument {
+    /// Parses a [Prometheus](https://prometheus.io/docs/prometheus/latest/configuration/configuration/)
+    /// configuration file (`prometheus.yml`) into a document.
+    ///
+    /// * Each of the `scrape_configs` becomes a `scrape_config` node, with its
+    ///   `job_name` as its argument and the rest of its settings as its
+    ///   children.
+    /// * Label sets (`labels` and `external_labels`) become a single node with
+    ///   one property per label.
+    /// * Each rule in a relabeling list (`relabel_configs`,
+    ///   `metric_relabel_configs`, and so on) becomes a `rule` node, with its
+    ///   settings as properties, except for lists like `source_labels`, which
+    ///   become children with one argument per item.
+    /// * Everything else is converted generically: scalars become a node's
+    ///   only argument, mappings become children, and lists of scalars become
+    ///   multiple arguments. Other lists become one child per item, named after
+    ///   the list without its trailing `s` (so `static_configs` items become
+    ///   `static_config` nodes), or `-` if the list name doesn't end in `s`.
+    ///
+    /// Fails if the YAML is invalid, if it has more than one YAML document in
+    /// it, if it isn't a mapping at the toplevel, or if a scrape config
+    /// doesn't have a `job_name`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_prometheus_config(r#"
+    /// scrape_configs:
+    ///   - job_name: node
+    ///     static_configs:
+    ///       - targets: ["a:9100", "b:9100"]
+    ///         labels: {env: prod}
+    /// "#).unwrap();
+    /// assert_eq!(doc.to_string(), r#"scrape_configs {
+    ///     scrape_config node {
+    ///         static_configs {
+    ///             static_config {
+    ///                 targets a:9100 b:9100
+    ///                 labels env=prod
+    ///             }
+    ///         }
+    ///     }
+    /// }
+    /// "#);
+    /// ```
+    pub fn from_prometheus_config(yaml: &str) -> Result<KdlDocument, KdlError> {
+        let error = |message: &str| KdlError::single(yaml, (0, 0), message, None);
+        let mut doc = KdlDocument::new();
+        let hash = match load_single(yaml, "Prometheus config")? {
+            None | Some(Yaml::Null) => return Ok(doc),
+            Some(Yaml::Hash(hash)) => hash,
+            Some(_) => return Err(error("Expected Prometheus config to be a YAML mapping")),
+        };
+        for (key, value) in hash {
+            let key = key_to_string(&key).ok_or_else(|| error("Unsupported YAML key"))?;
+            let node = convert(&key, value).map_err(|msg| error(&msg))?;
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Prometheus uses flat label maps for target and external labels, and
+/// plural names for most lists, like `static_configs` and `targets`.
+const PROMETHEUS: YamlConversion = YamlConversion {
+    label_maps: &["labels", "external_labels"],
+    list_items: ListItems::Singular,
+};
+
+fn convert(name: &str, value: Yaml) -> Result<KdlNode, String> {
+    let mut node = KdlNode::new(name);
+    match (name, value) {
+        (name, Yaml::Array(rules)) if name.ends_with("relabel_configs") => {
+            for rule in rules {
+                node.ensure_children().nodes_mut().push(relabel_rule(rule)?);
+            }
+        }
+        ("scrape_configs", Yaml::Array(configs)) => {
+            for config in configs {
+                node.ensure_children()
+                    .nodes_mut()
+                    .push(scrape_config(config)?);
+            }
+        }
+        (name, value) => return convert_yaml(name, value, &PROMETHEUS, convert),
+    }
+    Ok(node)
+}
+
+fn scrape_config(config: Yaml) -> Result<KdlNode, String> {
+    let Yaml::Hash(hash) = config else {
+        return Err("Expected each scrape config to be a YAML mapping".into());
+    };
+    let mut node = KdlNode::new("scrape_config");
+    let mut job_name = None;
+    for (key, value) in hash {
+        let key = key_to_string(&key).ok_or("Unsupported YAML key")?;
+        if key == "job_name" {
+            job_name = Some(key_to_string(&value).ok_or("Invalid job_name")?);
+        } else {
+            node.ensure_children()
+                .nodes_mut()
+                .push(convert(&key, value)?);
+        }
+    }
+    let job_name = job_name.ok_or("Every scrape config must have a `job_name`")?;
+    node.entries_mut().insert(0, job_name.into());
+    Ok(node)
+}
+
+fn relabel_rule(rule: Yaml) -> Result<KdlNode, String> {
+    let Yaml::Hash(hash) = rule else {
+        return Err("Expected each relabeling rule to be a YAML mapping".into());
+    };
+    let mut node = KdlNode::new("rule");
+    for (key, value) in hash {
+        let key = key_to_string(&key).ok_or("Unsupported YAML key")?;
+        if let Some(value) = scalar(&value) {
+            node.insert(key, value);
+        } else {
+            node.ensure_children()
+                .nodes_mut()
+                .push(convert(&key, value)?);
+        }
+    }
+    Ok(node)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn prometheus_config() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_prometheus_config,
+            r#"
+global:
+  scrape_interval: 15s
+  evaluation_interval: 15s
+  external_labels:
+    cluster: eu-1
+    replica: 2
+
+rule_files:
+  - "rules/*.yml"
+
+alerting:
+  alertmanagers:
+    - static_configs:
+        - targets: ["alertmanager:9093"]
+
+scrape_configs:
+  - job_name: prometheus
+    static_configs:
+      - targets: ["localhost:9090"]
+
+  - job_name: node
+    scrape_interval: 5s
+    honor_labels: true
+    static_configs:
+      - targets: ["10.0.0.1:9100", "10.0.0.2:9100"]
+        labels:
+          env: prod
+          team: infra
+    relabel_configs:
+      - source_labels: [__address__]
+        regex: "(.*):9100"
+        target_label: instance
+        replacement: "$1"
+      - action: labeldrop
+        regex: tmp_.*
+    metric_relabel_configs:
+      - source_labels: [__name__, job]
+        separator: ";"
+        action: drop
+"#,
+            r#"
+global {
+    scrape_interval "15s"
+    evaluation_interval "15s"
+    external_labels cluster=eu-1 replica=2
+}
+rule_files "rules/*.yml"
+alerting {
+    alertmanagers {
+        alertmanager {
+            static_configs {
+                static_config {
+                    targets "alertmanager:9093"
+                }
+            }
+        }
+    }
+}
+scrape_configs {
+    scrape_config prometheus {
+        static_configs {
+            static_config {
+                targets "localhost:9090"
+            }
+        }
+    }
+    scrape_config node {
+        scrape_interval "5s"
+        honor_labels #true
+        static_configs {
+            static_config {
+                targets "10.0.0.1:9100" "10.0.0.2:9100"
+                labels env=prod team=infra
+            }
+        }
+        relabel_configs {
+            rule regex="(.*):9100" target_label=instance replacement="$1" {
+                source_labels __address__
+            }
+            rule action=labeldrop regex="tmp_.*"
+        }
+        metric_relabel_configs {
+            rule separator=";" action=drop {
+                source_labels __name__ job
+            }
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        assert!(KdlDocument::from_prometheus_config("")
+            .unwrap()
+            .nodes()
+            .is_empty());
+        for bad in [
+            "- not a mapping\n",
+            "scrape_configs:\n  - scrape_interval: 5s\n",
+            "scrape_configs:\n  - job_name: x\n    static_configs:\n      - labels: {a: [1]}\n",
+            "global: {}\n---\nglobal: {}\n",
+            "global: [\n",
+        ] {
+            assert!(
+                KdlDocument::from_prometheus_config(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+    }
+}
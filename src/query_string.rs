@@ -0,0 +1,207 @@
+use std::fmt::Write as _;
+
+use crate::{KdlDocument, KdlError, KdlNode, KdlValue};
+
+impl KdlNode {
+    /// Writes this node's properties out as a URL query string, such as
+    /// `key=value&key2=value2`. Keys and values are percent-encoded, and
+    /// `#null` properties are written as a bare `key`, with no `=`.
+    ///
+    /// Arguments and children are skipped. Use
+    /// [`KdlNode::to_query_string_strict`] if you'd rather get an error for
+    /// them.
+    ///
+    /// Fails if any of the properties is a non-finite float.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlNode;
+    /// let node: KdlNode = r#"search "ignored" q="kdl rs" page=2 safe=#true"#.parse().unwrap();
+    /// assert_eq!(node.to_query_string().unwrap(), "q=kdl%20rs&page=2&safe=true");
+    /// ```
+    pub fn to_query_string(&self) -> Result<String, KdlError> {
+        self.query_string_impl(false)
+    }
+
+    /// Like [`KdlNode::to_query_string`], but fails if this node has any
+    /// arguments or children, instead of skipping them.
+    pub fn to_query_string_strict(&self) -> Result<String, KdlError> {
+        self.query_string_impl(true)
+    }
+
+    fn query_string_impl(&self, strict: bool) -> Result<String, KdlError> {
+        let error = |message: &str| {
+            let input = self.to_string();
+            KdlError::single(&input, (0, input.len()), message, None)
+        };
+        if strict && self.iter().any(|e| e.name().is_none()) {
+            return Err(error("Query string parameters must be named"));
+        }
+        if strict && self.children().is_some_and(|c| !c.nodes().is_empty()) {
+            return Err(error("Query strings can't have children"));
+        }
+        let mut out = String::new();
+        for entry in self.iter() {
+            let Some(name) = entry.name() else {
+                continue;
+            };
+            if !out.is_empty() {
+                out.push('&');
+            }
+            encode(&mut out, name.value());
+            let value = match entry.value() {
+                KdlValue::String(s) => s.clone(),
+                KdlValue::Integer(i) => i.to_string(),
+                KdlValue::Float(f) if f.is_finite() => format!("{f:?}"),
+                KdlValue::Float(_) => {
+                    return Err(error("Query strings can't represent non-finite floats"))
+                }
+                KdlValue::Bool(b) => b.to_string(),
+                KdlValue::Null => continue,
+            };
+            out.push('=');
+            encode(&mut out, &value);
+        }
+        Ok(out)
+    }
+}
+
+impl KdlDocument {
+    /// Writes this document's first node's properties out as a URL query
+    /// string. See [`KdlNode::to_query_string`] for details. Empty documents
+    /// produce an empty query string.
+    pub fn to_query_string(&self) -> Result<String, KdlError> {
+        self.nodes()
+            .first()
+            .map_or_else(|| Ok(String::new()), KdlNode::to_query_string)
+    }
+
+    /// Parses a URL query string into a flat document with a single `query`
+    /// node holding one string property per parameter, in order. This is the
+    /// reverse of [`KdlDocument::to_query_string`].
+    ///
+    /// A leading `?` is ignored, `+` is decoded as a space, and parameters
+    /// without a `=` become `#null` properties. Repeated parameters are all
+    /// kept, as duplicate properties.
+    ///
+    /// Fails if a `%` escape is malformed, or if a decoded key or value
+    /// isn't valid UTF-8.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_query_string("?q=kdl+rs&tag=a&tag=b&debug").unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     r#"query q="kdl rs" tag=a tag=b debug=#null"#.to_string() + "\n"
+    /// );
+    /// assert_eq!(doc.to_query_string().unwrap(), "q=kdl%20rs&tag=a&tag=b&debug");
+    /// ```
+    pub fn from_query_string(qs: &str) -> Result<KdlDocument, KdlError> {
+        let start = usize::from(qs.starts_with('?'));
+        let mut node = KdlNode::new("query");
+        let mut offset = start;
+        for param in qs[start..].split('&') {
+            let param_start = offset;
+            offset += param.len() + 1;
+            if param.is_empty() {
+                continue;
+            }
+            let error = || {
+                KdlError::single(
+                    qs,
+                    (param_start, param.len()),
+                    "Invalid percent-encoding in query string",
+                    Some("`%` must be followed by two hex digits, and decode to valid UTF-8."),
+                )
+            };
+            let (key, value) = match param.split_once('=') {
+                Some((key, value)) => (key, Some(value)),
+                None => (param, None),
+            };
+            let key = decode(key).ok_or_else(error)?;
+            let value = match value {
+                Some(value) => KdlValue::String(decode(value).ok_or_else(error)?),
+                None => KdlValue::Null,
+            };
+            node.push((key, value));
+        }
+        Ok(KdlDocument::from_nodes(vec![node]))
+    }
+}
+
+fn encode(out: &mut String, s: &str) {
+    for byte in s.bytes() {
+        if byte.is_ascii_alphanumeric() || matches!(byte, b'-' | b'.' | b'_' | b'~') {
+            out.push(char::from(byte));
+        } else {
+            write!(out, "%{byte:02X}").unwrap();
+        }
+    }
+}
+
+fn decode(s: &str) -> Option<String> {
+    let mut bytes = Vec::with_capacity(s.len());
+    let mut iter = s.bytes();
+    while let Some(byte) = iter.next() {
+        match byte {
+            b'+' => bytes.push(b' '),
+            b'%' => {
+                let hex = [iter.next()?, iter.next()?];
+                let hex = std::str::from_utf8(&hex).ok()?;
+                bytes.push(u8::from_str_radix(hex, 16).ok()?);
+            }
+            _ => bytes.push(byte),
+        }
+    }
+    String::from_utf8(bytes).ok()
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn to_query_string() -> miette::Result<()> {
+        let node: KdlNode =
+            r#"params 1 name="José & co" n=-3 ratio=0.5 ok=#false flag=#null { child }"#.parse()?;
+        assert_eq!(
+            node.to_query_string()?,
+            "name=Jos%C3%A9%20%26%20co&n=-3&ratio=0.5&ok=false&flag"
+        );
+        assert!(node.to_query_string_strict().is_err());
+
+        let node: KdlNode = "params a=1 { }".parse()?;
+        assert_eq!(node.to_query_string_strict()?, "a=1");
+
+        let node: KdlNode = "params x=#nan".parse()?;
+        assert!(node.to_query_string().is_err());
+
+        assert_eq!(KdlDocument::new().to_query_string()?, "");
+        let doc: KdlDocument = "first a=1\nsecond b=2".parse()?;
+        assert_eq!(doc.to_query_string()?, "a=1");
+        Ok(())
+    }
+
+    #[test]
+    fn from_query_string() -> miette::Result<()> {
+        let mut doc = KdlDocument::from_query_string("a=1&&b=x+y%2Bz&c=&d&a=2&=e")?;
+        let mut expected: KdlDocument =
+            r#"query a="1" b="x y+z" c="" d=#null a="2" ""=e"#.parse()?;
+        expected.clear_format_recursive();
+        doc.clear_format_recursive();
+        assert_eq!(doc, expected);
+
+        let qs = "name=Jos%C3%A9%20%26%20co&flag&n=-3";
+        assert_eq!(KdlDocument::from_query_string(qs)?.to_query_string()?, qs);
+
+        let err = KdlDocument::from_query_string("ok=1&bad=%zz").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (5, 7).into());
+        assert!(KdlDocument::from_query_string("bad=%FF").is_err());
+        assert!(KdlDocument::from_query_string("bad=%4").is_err());
+        assert!(KdlDocument::from_query_string("")?.nodes()[0].is_empty());
+        Ok(())
+    }
+}
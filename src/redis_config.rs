@@ -0,0 +1,262 @@
+use crate::{lines::trimmed_lines, KdlDocument, KdlEntry, KdlError, KdlNode, KdlValue};
+
+/// How deep `include` directives may nest before we assume there's a cycle.
+const MAX_INCLUDE_DEPTH: usize = 16;
+
+type Resolver<'a> = &'a mut dyn FnMut(&str) -> Option<String>;
+
+impl KdlDocument {
+    /// Parses a [Redis](https://redis.io/) configuration file (`redis.conf`)
+    /// into a document.
+    ///
+    /// Each directive becomes a node named after the (lowercased) directive,
+    /// with one string argument per value, so something like `bind 127.0.0.1
+    /// ::1` turns into a node with two arguments. Values may be quoted the same
+    /// way Redis allows. The Redis booleans `yes` and `no` are converted to
+    /// `#true` and `#false`. Empty lines and `#` comments are skipped.
+    ///
+    /// `include` directives are kept as `include` nodes. Use
+    /// [`KdlDocument::from_redis_config_with_includes`] to inline them
+    /// instead.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_redis_config(
+    ///     "# network\nbind 127.0.0.1 ::1\nport 6379\nappendonly yes\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.get("bind").unwrap().entries().len(), 2);
+    /// assert_eq!(doc.get_arg("port"), Some(&"6379".into()));
+    /// assert_eq!(doc.get_arg("appendonly"), Some(&true.into()));
+    /// ```
+    pub fn from_redis_config(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        parse_redis_config(s, &mut doc, None, 0)?;
+        Ok(doc)
+    }
+
+    /// Like [`KdlDocument::from_redis_config`], but replaces `include`
+    /// directives with the contents of the files they point to.
+    ///
+    /// `resolve` is called with each included path, and should return that
+    /// file's contents, or `None` if it can't be read. Included files may
+    /// include more files in turn.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_redis_config_with_includes(
+    ///     "include /etc/redis/common.conf\nport 6380\n",
+    ///     |path| (path == "/etc/redis/common.conf").then(|| "maxclients 100".to_string()),
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.get_arg("maxclients"), Some(&"100".into()));
+    /// assert!(doc.get("include").is_none());
+    /// ```
+    pub fn from_redis_config_with_includes(
+        s: &str,
+        mut resolve: impl FnMut(&str) -> Option<String>,
+    ) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        parse_redis_config(s, &mut doc, Some(&mut resolve), 0)?;
+        Ok(doc)
+    }
+}
+
+fn parse_redis_config(
+    input: &str,
+    doc: &mut KdlDocument,
+    mut resolve: Option<Resolver<'_>>,
+    depth: usize,
+) -> Result<(), KdlError> {
+    for (start, trimmed) in trimmed_lines(input) {
+        if trimmed.is_empty() || trimmed.starts_with('#') {
+            continue;
+        }
+        let line_span = (start, trimmed.len());
+        let tokens = tokenize(trimmed).ok_or_else(|| {
+            KdlError::single(
+                input,
+                line_span,
+                "Unbalanced quotes in Redis configuration directive",
+                Some("Make sure every quoted value has a closing quote."),
+            )
+        })?;
+        let Some((name, args)) = tokens.split_first() else {
+            continue;
+        };
+        let name = name.to_lowercase();
+        if name == "include" {
+            if let Some(resolve) = resolve.as_deref_mut() {
+                let [path] = args else {
+                    return Err(KdlError::single(
+                        input,
+                        line_span,
+                        "`include` takes exactly one path",
+                        None,
+                    ));
+                };
+                if depth >= MAX_INCLUDE_DEPTH {
+                    return Err(KdlError::single(
+                        input,
+                        line_span,
+                        "Redis configuration includes are nested too deeply",
+                        Some("Check for files that include each other."),
+                    ));
+                }
+                let contents = resolve(path).ok_or_else(|| {
+                    KdlError::single(
+                        input,
+                        line_span,
+                        format!("Could not read included file `{path}`"),
+                        None,
+                    )
+                })?;
+                parse_redis_config(&contents, doc, Some(resolve), depth + 1)?;
+                continue;
+            }
+        }
+        let mut node = KdlNode::new(name);
+        for arg in args {
+            let value = match arg.to_lowercase().as_str() {
+                "yes" => KdlValue::Bool(true),
+                "no" => KdlValue::Bool(false),
+                _ => KdlValue::String(arg.clone()),
+            };
+            node.push(KdlEntry::new(value));
+        }
+        doc.nodes_mut().push(node);
+    }
+    Ok(())
+}
+
+/// Splits a directive line into its arguments, following the quoting rules of
+/// Redis' own `sdssplitargs`. Returns `None` if a quote is left open.
+fn tokenize(line: &str) -> Option<Vec<String>> {
+    let mut tokens = Vec::new();
+    let mut chars = line.chars().peekable();
+    loop {
+        while chars.next_if(|c| c.is_whitespace()).is_some() {}
+        let Some(&first) = chars.peek() else {
+            return Some(tokens);
+        };
+        let mut token = String::new();
+        match first {
+            '"' => {
+                chars.next();
+                loop {
+                    match chars.next()? {
+                        '"' => break,
+                        '\\' => match chars.next()? {
+                            'n' => token.push('\n'),
+                            'r' => token.push('\r'),
+                            't' => token.push('\t'),
+                            'b' => token.push('\u{08}'),
+                            'a' => token.push('\u{07}'),
+                            'x' => {
+                                let hex: String = chars.by_ref().take(2).collect();
+                                let byte = u8::from_str_radix(&hex, 16).ok()?;
+                                token.push(char::from(byte));
+                            }
+                            c => token.push(c),
+                        },
+                        c => token.push(c),
+                    }
+                }
+            }
+            '\'' => {
+                chars.next();
+                loop {
+                    match chars.next()? {
+                        '\'' => break,
+                        '\\' if chars.peek() == Some(&'\'') => {
+                            chars.next();
+                            token.push('\'');
+                        }
+                        c => token.push(c),
+                    }
+                }
+            }
+            _ => {
+                while let Some(c) = chars.next_if(|c| !c.is_whitespace()) {
+                    token.push(c);
+                }
+            }
+        }
+        tokens.push(token);
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn directives() -> miette::Result<()> {
+        let doc = KdlDocument::from_redis_config(
+            r#"
+# Redis configuration file example.
+bind 127.0.0.1 -::1
+Port 6379
+
+   daemonize no
+protected-mode YES
+save 3600 1 300 100
+requirepass "foo bar\"baz\x21"
+logfile ''
+include /etc/redis/local.conf
+"#,
+        )?;
+        let mut expected = KdlDocument::parse(
+            r#"
+bind "127.0.0.1" "-::1"
+port "6379"
+daemonize #false
+protected-mode #true
+save "3600" "1" "300" "100"
+requirepass "foo bar\"baz!"
+logfile ""
+include "/etc/redis/local.conf"
+"#,
+        )?;
+        expected.clear_format_recursive();
+        let mut doc = doc;
+        doc.clear_format_recursive();
+        assert_eq!(doc, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn includes() -> miette::Result<()> {
+        let doc =
+            KdlDocument::from_redis_config_with_includes("include a.conf\nport 1\n", |path| {
+                match path {
+                    "a.conf" => Some("maxmemory 2mb\ninclude b.conf".into()),
+                    "b.conf" => Some("appendonly yes".into()),
+                    _ => None,
+                }
+            })?;
+        let names: Vec<_> = doc.nodes().iter().map(|n| n.name().value()).collect();
+        assert_eq!(names, vec!["maxmemory", "appendonly", "port"]);
+
+        assert!(
+            KdlDocument::from_redis_config_with_includes("include missing.conf", |_| None).is_err()
+        );
+        assert!(
+            KdlDocument::from_redis_config_with_includes("include loop.conf", |_| Some(
+                "include loop.conf".into()
+            ))
+            .is_err()
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn unbalanced_quotes() {
+        let err = KdlDocument::from_redis_config("port 1\nrequirepass \"oops\n").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (7, 17).into());
+    }
+}
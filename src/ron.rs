@@ -0,0 +1,315 @@
+use miette::SourceSpan;
+
+use crate::{
+    error::span_or_default, KdlDocument, KdlEntry, KdlError, KdlIdentifier, KdlNode, KdlValue,
+};
+
+impl KdlDocument {
+    /// Converts this document to [RON](https://github.com/ron-rs/ron)
+    /// (Rusty Object Notation), as a list with one item per toplevel node.
+    ///
+    /// * Nodes with only arguments become tuple structs named after the node,
+    ///   like `point(1, 2)`, and nodes with no entries or children become
+    ///   unit structs.
+    /// * Nodes with properties or children become structs named after the
+    ///   node, with a field per property and child. Children with the same
+    ///   name are grouped into a list in a single field, and if the node also
+    ///   has arguments, they go in an `args` list field.
+    /// * Type annotations, on both nodes and values, become enum variants
+    ///   wrapping the annotated node or value, like `u16(8080)`.
+    /// * `#null` becomes `None`, and other values become the matching RON
+    ///   literal.
+    ///
+    /// This is only available with the `ron` feature.
+    ///
+    /// Fails if a node, property or type annotation name isn't a valid RON
+    /// identifier (even as a raw `r#identifier`), or if a struct would end up
+    /// with more than one field with the same name (for example, a node with
+    /// both an `args` property and arguments, or a property and a child with
+    /// the same name).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "point 1 2\nserver port=(u16)8080 {\n    route \"/\"\n}".parse().unwrap();
+    /// assert_eq!(doc.to_ron().unwrap(), r#"[
+    ///     point(1, 2),
+    ///     server(
+    ///         port: u16(8080),
+    ///         route: route("/"),
+    ///     ),
+    /// ]
+    /// "#);
+    /// ```
+    pub fn to_ron(&self) -> Result<String, KdlError> {
+        let input = self.to_string();
+        let mut out = String::from("[\n");
+        for node in self.nodes() {
+            out.push_str("    ");
+            write_node(&mut out, node, 1)
+                .map_err(|(span, message)| KdlError::single(&input, span, message, None))?;
+            out.push_str(",\n");
+        }
+        out.push_str("]\n");
+        Ok(out)
+    }
+}
+
+type RonResult = Result<(), (SourceSpan, String)>;
+
+fn write_node(out: &mut String, node: &KdlNode, depth: usize) -> RonResult {
+    let span = span_or_default(node);
+    let ty = match node.ty() {
+        Some(ty) => Some(ident(ty.value()).ok_or_else(|| invalid(span, ty))?),
+        None => None,
+    };
+    if let Some(ty) = &ty {
+        out.push_str(ty);
+        out.push('(');
+    }
+    out.push_str(&ident(node.name().value()).ok_or_else(|| invalid(span, node.name()))?);
+
+    let args = node
+        .iter()
+        .filter(|e| e.name().is_none())
+        .collect::<Vec<_>>();
+    let props = node
+        .iter()
+        .filter(|e| e.name().is_some())
+        .collect::<Vec<_>>();
+    let children = node.children().map_or(&[][..], |c| c.nodes());
+    if props.is_empty() && node.children().is_none() {
+        if !args.is_empty() {
+            out.push('(');
+            write_list(out, &args)?;
+            out.push(')');
+        }
+    } else {
+        // Fields, in order of first appearance, each with every child that
+        // goes in it.
+        let mut fields: Vec<(&str, Field<'_>)> = Vec::new();
+        if !args.is_empty() {
+            fields.push(("args", Field::Args(args)));
+        }
+        for prop in props {
+            let name = prop.name().unwrap();
+            if fields.iter().any(|(f, _)| *f == name.value()) {
+                return Err(duplicate(span_or_default(prop), name.value()));
+            }
+            fields.push((name.value(), Field::Prop(prop)));
+        }
+        for child in children {
+            let name = child.name().value();
+            match fields.iter_mut().find(|(f, _)| *f == name) {
+                Some((_, Field::Children(nodes))) => nodes.push(child),
+                Some(_) => return Err(duplicate(span_or_default(child), name)),
+                None => fields.push((name, Field::Children(vec![child]))),
+            }
+        }
+        let pad = "    ".repeat(depth + 1);
+        out.push_str("(\n");
+        for (name, field) in fields {
+            out.push_str(&pad);
+            out.push_str(&ident(name).ok_or_else(|| (span, invalid_name(name)))?);
+            out.push_str(": ");
+            match field {
+                Field::Args(args) => {
+                    out.push('[');
+                    write_list(out, &args)?;
+                    out.push(']');
+                }
+                Field::Prop(prop) => write_entry(out, prop)?,
+                Field::Children(nodes) if nodes.len() == 1 => {
+                    write_node(out, nodes[0], depth + 1)?;
+                }
+                Field::Children(nodes) => {
+                    out.push_str("[\n");
+                    for child in nodes {
+                        out.push_str(&pad);
+                        out.push_str("    ");
+                        write_node(out, child, depth + 2)?;
+                        out.push_str(",\n");
+                    }
+                    out.push_str(&pad);
+                    out.push(']');
+                }
+            }
+            out.push_str(",\n");
+        }
+        out.push_str(&"    ".repeat(depth));
+        out.push(')');
+    }
+    if ty.is_some() {
+        out.push(')');
+    }
+    Ok(())
+}
+
+enum Field<'a> {
+    Args(Vec<&'a KdlEntry>),
+    Prop(&'a KdlEntry),
+    Children(Vec<&'a KdlNode>),
+}
+
+fn write_list(out: &mut String, entries: &[&KdlEntry]) -> RonResult {
+    for (idx, entry) in entries.iter().enumerate() {
+        if idx > 0 {
+            out.push_str(", ");
+        }
+        write_entry(out, entry)?;
+    }
+    Ok(())
+}
+
+fn write_entry(out: &mut String, entry: &KdlEntry) -> RonResult {
+    let ty = match entry.ty() {
+        Some(ty) => Some(ident(ty.value()).ok_or_else(|| invalid(span_or_default(entry), ty))?),
+        None => None,
+    };
+    if let Some(ty) = &ty {
+        out.push_str(ty);
+        out.push('(');
+    }
+    match entry.value() {
+        KdlValue::String(s) => {
+            out.push_str(&::ron::to_string(s).map_err(|e| (span_or_default(entry), e.to_string()))?)
+        }
+        KdlValue::Integer(i) => out.push_str(&i.to_string()),
+        KdlValue::Float(f) => out.push_str(&format!("{f:?}")),
+        KdlValue::Bool(b) => out.push_str(&b.to_string()),
+        KdlValue::Null => out.push_str("None"),
+    }
+    if ty.is_some() {
+        out.push(')');
+    }
+    Ok(())
+}
+
+/// Returns `name` as a RON identifier, using a raw identifier if needed, or
+/// `None` if it can't be represented at all.
+fn ident(name: &str) -> Option<String> {
+    let mut chars = name.chars();
+    let first = chars.next()?;
+    if (first.is_ascii_alphabetic() || first == '_')
+        && chars.all(|c| c.is_ascii_alphanumeric() || c == '_')
+    {
+        if matches!(name, "true" | "false" | "inf" | "NaN" | "None" | "Some") {
+            return Some(format!("r#{name}"));
+        }
+        return Some(name.into());
+    }
+    name.chars()
+        .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '.' | '+' | '-'))
+        .then(|| format!("r#{name}"))
+}
+
+fn invalid(span: SourceSpan, ident: &KdlIdentifier) -> (SourceSpan, String) {
+    (span, invalid_name(ident.value()))
+}
+
+fn invalid_name(name: &str) -> String {
+    format!("`{name}` can't be used as a RON identifier")
+}
+
+fn duplicate(span: SourceSpan, name: &str) -> (SourceSpan, String) {
+    (span, format!("Duplicate RON struct field `{name}`"))
+}
+
+#[cfg(test)]
+mod test {
+    use ::ron::Value;
+
+    use super::*;
+
+    #[test]
+    fn to_ron() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+title "Tom's \"app\"\n"
+point 1 -2.5
+enabled
+(Duration)timeout 30
+server "main" host=localhost port=(u16)8080 tls=#null {
+    route "/" handler=index
+    route "/api" handler=api
+    limits {
+        rate #inf
+    }
+}
+"kebab-case" on=#true
+"#
+        .parse()?;
+        let ron = doc.to_ron()?;
+        assert_eq!(
+            ron,
+            r#"[
+    title("Tom\'s \"app\"\n"),
+    point(1, -2.5),
+    enabled,
+    Duration(timeout(30)),
+    server(
+        args: ["main"],
+        host: "localhost",
+        port: u16(8080),
+        tls: None,
+        route: [
+            route(
+                args: ["/"],
+                handler: "index",
+            ),
+            route(
+                args: ["/api"],
+                handler: "api",
+            ),
+        ],
+        limits: limits(
+            rate: rate(inf),
+        ),
+    ),
+    r#kebab-case(
+        on: true,
+    ),
+]
+"#
+        );
+        assert!(::ron::from_str::<Value>(&ron).is_ok());
+        Ok(())
+    }
+
+    #[test]
+    fn round_trip() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+point 1 2
+server port=8080 {
+    route "/" handler=index
+    route "/api"
+}
+"#
+        .parse()?;
+        let value: Value =
+            ::ron::from_str(&doc.to_ron()?).expect("to_ron output should be valid RON");
+        // RON parses structs as maps and tuple structs as sequences, dropping
+        // their names.
+        let expected: Value = ::ron::from_str(
+            r#"[[1, 2], {"port": 8080, "route": [{"args": ["/"], "handler": "index"}, ["/api"]]}]"#,
+        )
+        .unwrap();
+        assert_eq!(value, expected);
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> miette::Result<()> {
+        for bad in [
+            "\"has space\" 1",
+            "(\"bad type\")node",
+            "node \"a b\"=1 {\n    child\n}",
+            "node args=1 2",
+            "node child=1 {\n    child\n}",
+        ] {
+            let doc: KdlDocument = bad.parse()?;
+            assert!(doc.to_ron().is_err(), "{bad:?} should fail");
+        }
+        Ok(())
+    }
+}
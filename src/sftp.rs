@@ -0,0 +1,199 @@
+use crate::{lines::trimmed_lines, KdlDocument, KdlError, KdlNode};
+
+impl KdlDocument {
+    /// Parses an `sftp` batch file (as used with `sftp -b`) into a document.
+    ///
+    /// Each command becomes a toplevel node named after the command, with
+    /// its arguments, including flags like `-r`, as string arguments:
+    ///
+    /// ```kdl
+    /// cd /var/www
+    /// put -r build "release notes.txt"
+    /// rm "/tmp/old upload" ignore-errors=#true
+    /// ```
+    ///
+    /// Arguments can be quoted with double or single quotes to include
+    /// spaces, and any character outside of single quotes can be escaped
+    /// with a backslash. A command prefixed with `-`, which tells `sftp` to
+    /// keep going even if it fails, gets an `ignore-errors=#true` property.
+    /// Local shell escapes (`!command`) become a `!` node, with the rest of
+    /// the line as its only argument, as it's passed to the shell unchanged.
+    /// Blank lines and comments (lines starting with `#`) are skipped.
+    ///
+    /// This is only available with the `sftp` feature.
+    ///
+    /// Fails if a line has an unterminated quote or ends with a backslash,
+    /// or if a `-` isn't followed by a command.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_sftp_batch("# Upload.\nput \"my file.txt\" /upload\n-rm old.txt\n").unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "put \"my file.txt\" \"/upload\"\nrm old.txt ignore-errors=#true\n"
+    /// );
+    /// ```
+    pub fn from_sftp_batch(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        for (start, line) in trimmed_lines(s) {
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            if let Some(command) = line.strip_prefix('!') {
+                let mut node = KdlNode::new("!");
+                let command = command.trim_start();
+                if !command.is_empty() {
+                    node.push(command);
+                }
+                doc.nodes_mut().push(node);
+                continue;
+            }
+            let (ignore_errors, rest) = match line.strip_prefix('-') {
+                Some(rest) => (true, rest),
+                None => (false, line),
+            };
+            let rest_start = start + line.len() - rest.len();
+            let args = split_args(rest).map_err(|(pos, message)| {
+                KdlError::single(s, (rest_start + pos, 0), message, None)
+            })?;
+            let mut args = args.into_iter();
+            let Some(command) = args.next() else {
+                return Err(KdlError::single(
+                    s,
+                    (start, line.len()),
+                    "Expected a command after `-`",
+                    None,
+                ));
+            };
+            let mut node = KdlNode::new(command);
+            for arg in args {
+                node.push(arg);
+            }
+            if ignore_errors {
+                node.push(("ignore-errors", true));
+            }
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Splits a command line into arguments the way `sftp` does, handling
+/// quotes and backslash escapes. Errors carry the offset of the problem
+/// within `s`.
+fn split_args(s: &str) -> Result<Vec<String>, (usize, &'static str)> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quote: Option<(usize, char)> = None;
+    let mut chars = s.char_indices();
+    while let Some((idx, c)) = chars.next() {
+        match (c, quote) {
+            ('\'', Some((_, '\''))) | ('"', Some((_, '"'))) => quote = None,
+            ('\'' | '"', None) => {
+                quote = Some((idx, c));
+                in_arg = true;
+            }
+            ('\\', None | Some((_, '"'))) => match chars.next() {
+                Some((_, escaped)) => {
+                    current.push(escaped);
+                    in_arg = true;
+                }
+                None => return Err((idx, "Unexpected backslash at end of line")),
+            },
+            (c, None) if c.is_whitespace() => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            (c, _) => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if let Some((idx, _)) = quote {
+        return Err((idx, "Unterminated quote"));
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Ok(args)
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn commands() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_sftp_batch,
+            r#"
+# Deploy the site.
+cd /var/www
+lcd build
+
+  put -r -p . "site backup"
+chmod 644 index.html
+  # Clean up.
+-rm '/tmp/old upload'
+- rmdir /tmp/empty
+ls
+!echo "done" > log.txt
+!
+bye
+"#,
+            r#"
+cd "/var/www"
+lcd build
+put "-r" "-p" "." "site backup"
+chmod "644" index.html
+rm "/tmp/old upload" ignore-errors=#true
+rmdir "/tmp/empty" ignore-errors=#true
+ls
+! "echo \"done\" > log.txt"
+!
+bye
+"#,
+        )?;
+        assert!(KdlDocument::from_sftp_batch("").unwrap().nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn quoting() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_sftp_batch,
+            r#"get "it's here.txt" 'a "quoted" name' my\ file.txt "a\"b" 'c\d' x"y z"w"#,
+            r#"get "it's here.txt" "a \"quoted\" name" "my file.txt" "a\"b" "c\\d" "xy zw""#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "put \"unterminated\n",
+            "get 'also unterminated\n",
+            "rm trailing\\\n",
+            "cd /\n-\n",
+        ] {
+            assert!(
+                KdlDocument::from_sftp_batch(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+    }
+
+    #[test]
+    fn error_offset() {
+        let input = "cd /\nput \"unterminated\n";
+        let err = KdlDocument::from_sftp_batch(input).unwrap_err();
+        assert_eq!(err.diagnostics[0].span.offset(), 9);
+    }
+}
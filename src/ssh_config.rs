@@ -0,0 +1,288 @@
+use crate::{lines::trimmed_lines, KdlDocument, KdlError, KdlNode};
+
+impl KdlDocument {
+    /// Parses an OpenSSH client configuration file (like `~/.ssh/config`)
+    /// into a document.
+    ///
+    /// * Each `Host` block becomes a `host` node, with its patterns as
+    ///   arguments, and each `Match` block becomes a `match` node, with its
+    ///   criteria (like `exec "cmd"` or `user bob`) as arguments.
+    /// * Directives inside a block become string properties on the block's
+    ///   node. Directives that appear more than once in the same block (like
+    ///   `IdentityFile` or `LocalForward`) instead become a child node named
+    ///   after the directive, with one argument per value, in order.
+    /// * `Include` directives become `include` nodes, with one argument per
+    ///   path, where they appear. Included files aren't read.
+    /// * Directives before the first block become toplevel nodes, with their
+    ///   value as their only argument.
+    ///
+    /// Directive names are case-insensitive, so they're all lowercased.
+    /// Values are kept as written, except that a value that's entirely quoted
+    /// is unquoted. `Key=Value` and `Key Value` are both accepted, and empty
+    /// lines and lines starting with `#` are skipped.
+    ///
+    /// Fails if a directive doesn't have a value, if a `Host` or `Match` block
+    /// doesn't have any patterns or criteria, or if a quote isn't closed.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_ssh_config(
+    ///     "Host *.internal bastion\n    User admin\n    IdentityFile ~/.ssh/a\n    IdentityFile ~/.ssh/b\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(doc.to_string(), r#"host *.internal bastion user=admin {
+    ///     identityfile "~/.ssh/a" "~/.ssh/b"
+    /// }
+    /// "#);
+    /// ```
+    pub fn from_ssh_config(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut doc = KdlDocument::new();
+        let mut block: Option<Block> = None;
+        for (start, line) in trimmed_lines(s) {
+            let span = (start, line.len());
+            let error = |message: &str| KdlError::single(s, span, message, None);
+            if line.is_empty() || line.starts_with('#') {
+                continue;
+            }
+            let key_len = line
+                .find(|c: char| c.is_whitespace() || c == '=')
+                .unwrap_or(line.len());
+            let key = line[..key_len].to_lowercase();
+            let rest = line[key_len..].trim_start();
+            let value = rest.strip_prefix('=').unwrap_or(rest).trim_start();
+            if value.is_empty() {
+                return Err(error(&format!("`{}` is missing a value", &line[..key_len])));
+            }
+            match key.as_str() {
+                "host" | "match" => {
+                    if let Some(block) = block.take() {
+                        doc.nodes_mut().push(block.into_node());
+                    }
+                    let mut node = KdlNode::new(key);
+                    for arg in split_args(value).ok_or_else(|| error("Unterminated quote"))? {
+                        node.push(arg);
+                    }
+                    block = Some(Block {
+                        node,
+                        directives: Vec::new(),
+                    });
+                }
+                "include" => {
+                    let paths = split_args(value).ok_or_else(|| error("Unterminated quote"))?;
+                    match &mut block {
+                        Some(block) => block.directives.push(Directive::Include(paths)),
+                        None => doc.nodes_mut().push(include_node(paths)),
+                    }
+                }
+                _ => {
+                    let value = unquote(value).ok_or_else(|| error("Unterminated quote"))?;
+                    match &mut block {
+                        Some(block) => block.add(key, value),
+                        None => doc
+                            .nodes_mut()
+                            .push(KdlNode::new(key).with_appended_arg(value)),
+                    }
+                }
+            }
+        }
+        if let Some(block) = block {
+            doc.nodes_mut().push(block.into_node());
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// A `Host` or `Match` block that's still being parsed.
+struct Block {
+    node: KdlNode,
+    directives: Vec<Directive>,
+}
+
+enum Directive {
+    Setting(String, Vec<String>),
+    Include(Vec<String>),
+}
+
+impl Block {
+    fn add(&mut self, key: String, value: String) {
+        let existing = self.directives.iter_mut().find_map(|d| match d {
+            Directive::Setting(k, values) if *k == key => Some(values),
+            _ => None,
+        });
+        match existing {
+            Some(values) => values.push(value),
+            None => self.directives.push(Directive::Setting(key, vec![value])),
+        }
+    }
+
+    fn into_node(self) -> KdlNode {
+        let mut node = self.node;
+        for directive in self.directives {
+            match directive {
+                Directive::Setting(key, mut values) if values.len() == 1 => {
+                    node.insert(key, values.pop().unwrap());
+                }
+                Directive::Setting(key, values) => {
+                    let mut child = KdlNode::new(key);
+                    for value in values {
+                        child.push(value);
+                    }
+                    node.ensure_children().nodes_mut().push(child);
+                }
+                Directive::Include(paths) => {
+                    node.ensure_children().nodes_mut().push(include_node(paths));
+                }
+            }
+        }
+        node
+    }
+}
+
+fn include_node(paths: Vec<String>) -> KdlNode {
+    let mut node = KdlNode::new("include");
+    for path in paths {
+        node.push(path);
+    }
+    node
+}
+
+/// Splits `s` on whitespace, treating double-quoted sections as part of a
+/// single argument. Returns `None` if a quote isn't closed.
+fn split_args(s: &str) -> Option<Vec<String>> {
+    let mut args = Vec::new();
+    let mut current = String::new();
+    let mut in_arg = false;
+    let mut quoted = false;
+    for c in s.chars() {
+        match c {
+            '"' => {
+                quoted = !quoted;
+                in_arg = true;
+            }
+            c if c.is_whitespace() && !quoted => {
+                if in_arg {
+                    args.push(std::mem::take(&mut current));
+                    in_arg = false;
+                }
+            }
+            c => {
+                current.push(c);
+                in_arg = true;
+            }
+        }
+    }
+    if quoted {
+        return None;
+    }
+    if in_arg {
+        args.push(current);
+    }
+    Some(args)
+}
+
+/// Strips the quotes from a value that's entirely wrapped in double quotes,
+/// and returns any other value as-is. Returns `None` if the value starts with
+/// a quote that isn't closed.
+fn unquote(s: &str) -> Option<String> {
+    match s.strip_prefix('"') {
+        Some(inner) => match inner.find('"') {
+            Some(end) if end == inner.len() - 1 => Some(inner[..end].into()),
+            Some(_) => Some(s.into()),
+            None => None,
+        },
+        None => Some(s.into()),
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn wildcard_hosts() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_ssh_config,
+            r#"
+# Global settings
+Include ~/.ssh/config.d/*
+ServerAliveInterval 60
+
+Host *.example.com !legacy.example.com
+    User deploy
+    port=2222
+    IdentityFile ~/.ssh/deploy
+    IdentityFile "~/.ssh/my keys/fallback"
+    LocalForward 8080 localhost:80
+
+Host *
+    AddKeysToAgent yes
+    Include defaults.conf
+    IdentitiesOnly = yes
+"#,
+            r#"
+include "~/.ssh/config.d/*"
+serveraliveinterval "60"
+host "*.example.com" "!legacy.example.com" user=deploy port="2222" localforward="8080 localhost:80" {
+    identityfile "~/.ssh/deploy" "~/.ssh/my keys/fallback"
+}
+host "*" addkeystoagent=yes identitiesonly=yes {
+    include defaults.conf
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn match_exec() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_ssh_config,
+            r#"
+Match exec "test -f ~/.vpn-up" host *.corp
+    ProxyJump none
+Match all
+    ForwardAgent no
+"#,
+            r#"
+match exec "test -f ~/.vpn-up" host "*.corp" proxyjump=none
+match all forwardagent=no
+"#,
+        )
+    }
+
+    #[test]
+    fn proxy_command() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_ssh_config,
+            r#"
+Host internal
+    HostName 10.0.0.5
+    ProxyCommand ssh -W %h:%p bastion.example.com
+Host quoted
+    ProxyCommand "nc -X 5 -x proxy:1080 %h %p"
+"#,
+            r#"
+host internal hostname="10.0.0.5" proxycommand="ssh -W %h:%p bastion.example.com"
+host quoted proxycommand="nc -X 5 -x proxy:1080 %h %p"
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in ["Host\n", "Host a\n  User\n", "Match exec \"unterminated\n"] {
+            assert!(
+                KdlDocument::from_ssh_config(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        let err = KdlDocument::from_ssh_config("Host a\n  User\n").unwrap_err();
+        assert_eq!(
+            err.diagnostics[0].message.as_deref(),
+            Some("`User` is missing a value")
+        );
+    }
+}
@@ -0,0 +1,174 @@
+use std::collections::HashMap;
+
+use crate::{error::span_or_default, KdlDocument, KdlError, KdlValue};
+
+/// A single structured log event, extracted from a [`KdlDocument`] with
+/// [`KdlDocument::to_structured_log_entry`]. This is meant to be easy to hand
+/// off to log consumers such as `tracing` subscribers or OpenTelemetry
+/// exporters.
+#[derive(Debug, Clone, Default, PartialEq)]
+pub struct KdlLogEntry {
+    /// When the event happened, as written in the document.
+    pub timestamp: Option<String>,
+    /// The event's level, such as `info` or `warn`.
+    pub level: Option<String>,
+    /// The event's human-readable message.
+    pub message: Option<String>,
+    /// All the other properties of the event.
+    pub fields: HashMap<String, KdlValue>,
+}
+
+impl KdlDocument {
+    /// Converts a document representing a single event into a
+    /// [`KdlLogEntry`].
+    ///
+    /// The document must have exactly one node. The node's name is the
+    /// event's level, unless it has a `level` property. The `timestamp` and
+    /// `message` properties fill in the corresponding fields of the entry, and
+    /// all other properties go into [`KdlLogEntry::fields`]. If there's no
+    /// `message` property, a single string argument is used as the message
+    /// instead.
+    ///
+    /// Fails if the document doesn't have exactly one node, if that node has
+    /// children or any other arguments, or if `timestamp`, `level` or
+    /// `message` aren't strings (integer timestamps are also accepted).
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = r#"warn "disk almost full" target=storage used=0.93"#.parse().unwrap();
+    /// let entry = doc.to_structured_log_entry().unwrap();
+    /// assert_eq!(entry.level.as_deref(), Some("warn"));
+    /// assert_eq!(entry.message.as_deref(), Some("disk almost full"));
+    /// assert_eq!(entry.fields["target"], "storage".into());
+    /// ```
+    pub fn to_structured_log_entry(&self) -> Result<KdlLogEntry, KdlError> {
+        let [node] = self.nodes() else {
+            let input = self.to_string();
+            return Err(KdlError::single(
+                &input,
+                (0, input.len()),
+                "A log entry must be a document with exactly one node",
+                None,
+            ));
+        };
+        if node.children().is_some_and(|c| !c.nodes().is_empty()) {
+            return Err(KdlError::in_document(
+                self,
+                span_or_default(node),
+                "Log entries can't have children",
+            ));
+        }
+        let mut entry = KdlLogEntry {
+            level: Some(node.name().value().into()),
+            ..Default::default()
+        };
+        let mut args = node.iter().filter(|e| e.name().is_none());
+        if let Some(arg) = args.next() {
+            let KdlValue::String(message) = arg.value() else {
+                return Err(KdlError::in_document(
+                    self,
+                    span_or_default(arg),
+                    "Log messages must be strings",
+                ));
+            };
+            entry.message = Some(message.clone());
+        }
+        if let Some(arg) = args.next() {
+            return Err(KdlError::in_document(
+                self,
+                span_or_default(arg),
+                "Log entries can only have a single argument, for their message",
+            ));
+        }
+        for prop in node.iter() {
+            let Some(name) = prop.name() else {
+                continue;
+            };
+            let field = match name.value() {
+                "timestamp" => &mut entry.timestamp,
+                "level" => &mut entry.level,
+                "message" => &mut entry.message,
+                name => {
+                    entry.fields.insert(name.into(), prop.value().clone());
+                    continue;
+                }
+            };
+            *field = Some(match prop.value() {
+                KdlValue::String(s) => s.clone(),
+                KdlValue::Integer(i) if name.value() == "timestamp" => i.to_string(),
+                _ => {
+                    return Err(KdlError::in_document(
+                        self,
+                        span_or_default(prop),
+                        format!("`{}` must be a string", name.value()),
+                    ))
+                }
+            });
+        }
+        Ok(entry)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn to_structured_log_entry() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+warn message="connection pool exhausted" target="db::pool" timestamp="2024-05-01T12:00:00Z" size=32 retry=#true
+"#
+        .parse()?;
+        assert_eq!(
+            doc.to_structured_log_entry()?,
+            KdlLogEntry {
+                timestamp: Some("2024-05-01T12:00:00Z".into()),
+                level: Some("warn".into()),
+                message: Some("connection pool exhausted".into()),
+                fields: HashMap::from([
+                    ("target".into(), "db::pool".into()),
+                    ("size".into(), 32.into()),
+                    ("retry".into(), true.into()),
+                ]),
+            }
+        );
+
+        let doc: KdlDocument = r#"event "started" level=info timestamp=1714564800"#.parse()?;
+        let entry = doc.to_structured_log_entry()?;
+        assert_eq!(entry.level.as_deref(), Some("info"));
+        assert_eq!(entry.message.as_deref(), Some("started"));
+        assert_eq!(entry.timestamp.as_deref(), Some("1714564800"));
+        assert!(entry.fields.is_empty());
+
+        let doc: KdlDocument = "debug".parse()?;
+        assert_eq!(
+            doc.to_structured_log_entry()?,
+            KdlLogEntry {
+                level: Some("debug".into()),
+                ..Default::default()
+            }
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn errors() -> miette::Result<()> {
+        for bad in [
+            "",
+            "warn\nerror",
+            "warn 1",
+            "warn a b",
+            "warn message=1",
+            "warn { nested }",
+        ] {
+            let doc: KdlDocument = bad.parse()?;
+            assert!(
+                doc.to_structured_log_entry().is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        Ok(())
+    }
+}
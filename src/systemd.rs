@@ -0,0 +1,173 @@
+use crate::{lines::logical_lines, KdlDocument, KdlError, KdlNode};
+
+/// A section's name, and its settings, in order. Each setting can have
+/// multiple values.
+type Section = (String, Vec<(String, Vec<String>)>);
+
+impl KdlDocument {
+    /// Parses a [systemd unit
+    /// file](https://www.freedesktop.org/software/systemd/man/latest/systemd.unit.html)
+    /// (such as a `.service`, `.socket` or `.timer` file) into a document.
+    ///
+    /// Each `[Section]` becomes a toplevel node named after the section, and
+    /// its `Key=Value` settings become string properties on that node. Keys
+    /// that are set more than once in a section (like `After=` or
+    /// `ExecStartPre=`) instead become a child node named after the key, with
+    /// one string argument per value, in order. Repeated sections are merged
+    /// together.
+    ///
+    /// Values are kept as-is: `%` specifiers, quotes and escapes are not
+    /// expanded, and empty assignments (`Key=`) are kept as empty strings.
+    /// Lines ending in `\` are joined with the following line, and empty lines
+    /// and lines starting with `#` or `;` are skipped.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_systemd_unit(
+    ///     "[Unit]\nDescription=My %i service\nAfter=network.target\nAfter=db.service\n",
+    /// )
+    /// .unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "Unit Description=\"My %i service\" {\n    After network.target db.service\n}\n"
+    /// );
+    /// ```
+    pub fn from_systemd_unit(s: &str) -> Result<KdlDocument, KdlError> {
+        let mut sections: Vec<Section> = Vec::new();
+        let mut current = None;
+        for (line, span) in logical_lines(s) {
+            let line = line.as_str();
+            let error = |message: &str| KdlError::single(s, span, message, None);
+            if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+                continue;
+            }
+            if line.starts_with('[') {
+                let name = line
+                    .strip_prefix('[')
+                    .and_then(|l| l.strip_suffix(']'))
+                    .map(str::trim)
+                    .filter(|n| !n.is_empty())
+                    .ok_or_else(|| error("Invalid systemd section header"))?;
+                current = Some(match sections.iter().position(|(n, _)| n == name) {
+                    Some(idx) => idx,
+                    None => {
+                        sections.push((name.into(), Vec::new()));
+                        sections.len() - 1
+                    }
+                });
+                continue;
+            }
+            let (key, value) = line
+                .split_once('=')
+                .map(|(k, v)| (k.trim(), v.trim()))
+                .filter(|(k, _)| !k.is_empty())
+                .ok_or_else(|| error("Expected a `Key=Value` setting"))?;
+            let idx =
+                current.ok_or_else(|| error("systemd settings must be inside a [Section]"))?;
+            let settings = &mut sections[idx].1;
+            match settings.iter_mut().find(|(k, _)| k == key) {
+                Some((_, values)) => values.push(value.into()),
+                None => settings.push((key.into(), vec![value.into()])),
+            }
+        }
+
+        let mut doc = KdlDocument::new();
+        for (name, settings) in sections {
+            let mut node = KdlNode::new(name);
+            for (key, mut values) in settings {
+                if values.len() == 1 {
+                    node.insert(key, values.pop().unwrap());
+                } else {
+                    let mut child = KdlNode::new(key);
+                    for value in values {
+                        child.push(value);
+                    }
+                    node.ensure_children().nodes_mut().push(child);
+                }
+            }
+            doc.nodes_mut().push(node);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn service_unit() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_systemd_unit,
+            r#"
+# /etc/systemd/system/app@.service
+[Unit]
+Description=App instance %i
+Documentation=https://example.com/docs
+After=network-online.target
+After=postgresql.service
+Wants=network-online.target
+
+[Service]
+Type=notify
+User=%i
+Environment="RUST_LOG=info" "PORT=8080"
+ExecStartPre=/usr/bin/mkdir -p /run/app/%i
+ExecStartPre=/usr/bin/app migrate \
+    --config /etc/app/%i.kdl
+ExecStart=/usr/bin/app serve --config /etc/app/%i.kdl
+; ExecReload is intentionally left empty
+ExecReload=
+Restart=on-failure
+
+[Install]
+WantedBy=multi-user.target
+"#,
+            r#"
+Unit Description="App instance %i" Documentation="https://example.com/docs" Wants=network-online.target {
+    After network-online.target postgresql.service
+}
+Service Type=notify User="%i" Environment="\"RUST_LOG=info\" \"PORT=8080\"" ExecStart="/usr/bin/app serve --config /etc/app/%i.kdl" ExecReload="" Restart=on-failure {
+    ExecStartPre "/usr/bin/mkdir -p /run/app/%i" "/usr/bin/app migrate --config /etc/app/%i.kdl"
+}
+Install WantedBy=multi-user.target
+"#,
+        )
+    }
+
+    #[test]
+    fn repeated_sections() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_systemd_unit,
+            "[Unit]\nAfter=a\n[Install]\nWantedBy=x\n[Unit]\nAfter=b\nDescription=d\n",
+            r#"
+Unit Description=d {
+    After a b
+}
+Install WantedBy=x
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        for bad in [
+            "Description=no section",
+            "[Unit\nDescription=x",
+            "[]\n",
+            "[Unit]\nnot a setting",
+            "[Unit]\n=value",
+        ] {
+            assert!(
+                KdlDocument::from_systemd_unit(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        let err = KdlDocument::from_systemd_unit("[Unit]\n  oops\n").unwrap_err();
+        assert_eq!(err.diagnostics[0].span, (9, 4).into());
+    }
+}
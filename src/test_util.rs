@@ -0,0 +1,34 @@
+//! Helpers shared by the format converters' tests.
+
+// Converters are behind feature flags, so some of these can go unused.
+#![allow(dead_code)]
+
+use crate::{KdlDocument, KdlError};
+
+/// Asserts that `convert` turns `input` into the document described by
+/// `kdl`, ignoring formatting.
+pub(crate) fn assert_converts(
+    convert: impl FnOnce(&str) -> Result<KdlDocument, KdlError>,
+    input: &str,
+    kdl: &str,
+) -> miette::Result<()> {
+    let mut doc = convert(input)?;
+    let mut expected: KdlDocument = kdl.parse()?;
+    doc.clear_format_recursive();
+    expected.clear_format_recursive();
+    pretty_assertions::assert_eq!(doc, expected);
+    Ok(())
+}
+
+/// Like [`assert_converts`], but compares the converted document's formatted
+/// output against `kdl` instead. Type annotations parsed from `kdl` would
+/// keep their original repr, so this is for converters that add them.
+pub(crate) fn assert_converts_formatted(
+    convert: impl FnOnce(&str) -> Result<KdlDocument, KdlError>,
+    input: &str,
+    kdl: &str,
+) -> miette::Result<()> {
+    let doc = convert(input)?;
+    pretty_assertions::assert_eq!(doc.to_string(), kdl.trim_start());
+    Ok(())
+}
@@ -0,0 +1,215 @@
+use crate::{KdlDocument, KdlNode, KdlValue};
+
+impl KdlDocument {
+    /// Asserts that this document matches the structure described by
+    /// `expected`, panicking with a list of every mismatch if it doesn't.
+    ///
+    /// `expected` is a KDL "structure descriptor" which describes which nodes,
+    /// arguments and properties should be present, and what type their values
+    /// should have, but not what the values themselves should be:
+    ///
+    /// * Each node in the descriptor requires at least one node with the same
+    ///   name at the same level, and every node with that name has to match
+    ///   the rest of the descriptor node.
+    /// * Arguments and property values are type names: `string`, `integer`,
+    ///   `float`, `number` (integers or floats), `bool`, `#null`, or `any`. An
+    ///   argument requires an argument of that type in the same position, and
+    ///   a property requires a property with that name and type.
+    /// * Children are matched recursively.
+    ///
+    /// Anything not mentioned in the descriptor, like extra nodes,
+    /// properties, or arguments, is allowed.
+    ///
+    /// This is only available with the `testing` feature.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the document doesn't match, if `expected` isn't valid KDL, or
+    /// if it uses an unknown type name.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = r#"
+    /// server host=localhost port=8080 {
+    ///     route "/" handler=index
+    /// }
+    /// "#.parse().unwrap();
+    /// doc.assert_structure("server host=string port=integer { route string handler=string }");
+    /// ```
+    #[track_caller]
+    pub fn assert_structure(&self, expected: &str) {
+        let descriptor: KdlDocument = expected
+            .parse()
+            .unwrap_or_else(|e| panic!("invalid structure descriptor: {e:?}"));
+        let mut mismatches = Vec::new();
+        check_document(self, &descriptor, "", &mut mismatches);
+        if !mismatches.is_empty() {
+            panic!(
+                "document does not match the expected structure:\n{}\n\nexpected structure:\n{}\n\ndocument:\n{}",
+                mismatches
+                    .iter()
+                    .map(|m| format!("  - {m}"))
+                    .collect::<Vec<_>>()
+                    .join("\n"),
+                expected.trim(),
+                self.to_string().trim(),
+            );
+        }
+    }
+}
+
+fn check_document(doc: &KdlDocument, descriptor: &KdlDocument, path: &str, out: &mut Vec<String>) {
+    for expected in descriptor.nodes() {
+        let name = expected.name().value();
+        let matching = doc
+            .nodes()
+            .iter()
+            .filter(|n| n.name().value() == name)
+            .collect::<Vec<_>>();
+        if matching.is_empty() {
+            out.push(format!("{path}{name}: missing node"));
+        }
+        for (idx, node) in matching.iter().enumerate() {
+            let node_path = if idx == 0 {
+                format!("{path}{name}")
+            } else {
+                format!("{path}{name}[{idx}]")
+            };
+            check_node(node, expected, &node_path, out);
+        }
+    }
+}
+
+fn check_node(node: &KdlNode, descriptor: &KdlNode, path: &str, out: &mut Vec<String>) {
+    let mut args = node.iter().filter(|e| e.name().is_none());
+    for (idx, expected) in descriptor.iter().filter(|e| e.name().is_none()).enumerate() {
+        let ty = type_name(expected.value());
+        match args.next() {
+            Some(arg) if type_matches(ty, arg.value()) => {}
+            Some(arg) => out.push(format!(
+                "{path}: argument {idx} should be {ty}, found {}",
+                arg.value()
+            )),
+            None => out.push(format!("{path}: missing argument {idx} ({ty})")),
+        }
+    }
+    for expected in descriptor.iter() {
+        let Some(name) = expected.name() else {
+            continue;
+        };
+        let ty = type_name(expected.value());
+        match node.entry(name.value()) {
+            Some(prop) if type_matches(ty, prop.value()) => {}
+            Some(prop) => out.push(format!(
+                "{path}: property `{}` should be {ty}, found {}",
+                name.value(),
+                prop.value()
+            )),
+            None => out.push(format!(
+                "{path}: missing property `{}` ({ty})",
+                name.value()
+            )),
+        }
+    }
+    if let Some(expected) = descriptor.children() {
+        let empty = KdlDocument::new();
+        let children = node.children().unwrap_or(&empty);
+        check_document(children, expected, &format!("{path}/"), out);
+    }
+}
+
+#[track_caller]
+fn type_name(value: &KdlValue) -> &str {
+    if value.is_null() {
+        return "null";
+    }
+    match value.as_string() {
+        Some(ty @ ("string" | "integer" | "float" | "number" | "bool" | "null" | "any")) => ty,
+        _ => panic!(
+            "invalid structure descriptor: unknown type {value}. Expected one of string, integer, float, number, bool, null or any"
+        ),
+    }
+}
+
+fn type_matches(ty: &str, value: &KdlValue) -> bool {
+    matches!(
+        (ty, value),
+        ("any", _)
+            | ("string", KdlValue::String(_))
+            | ("integer" | "number", KdlValue::Integer(_))
+            | ("float" | "number", KdlValue::Float(_))
+            | ("bool", KdlValue::Bool(_))
+            | ("null", KdlValue::Null)
+    )
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    const DOC: &str = r#"
+server host=localhost port=8080 {
+    route "/" handler=index
+    route "/api" 2 handler=api timeout=1.5
+}
+database url="postgres://db" pool=#null
+"#;
+
+    #[test]
+    fn matching_structure() -> miette::Result<()> {
+        let doc: KdlDocument = DOC.parse()?;
+        doc.assert_structure("");
+        doc.assert_structure(
+            r#"
+server host=string port=integer {
+    route string handler=string
+}
+database url=any pool=#null
+"#,
+        );
+        doc.assert_structure("server port=number { route }");
+        Ok(())
+    }
+
+    #[test]
+    fn mismatches() -> miette::Result<()> {
+        let doc: KdlDocument = DOC.parse()?;
+        let panic = std::panic::catch_unwind(|| {
+            doc.assert_structure(
+                r#"
+server host=integer debug=bool {
+    route string integer
+    middleware
+}
+cache
+"#,
+            )
+        })
+        .unwrap_err();
+        let message = panic.downcast_ref::<String>().unwrap();
+        let mismatches = message
+            .lines()
+            .filter_map(|l| l.strip_prefix("  - "))
+            .collect::<Vec<_>>();
+        assert_eq!(
+            mismatches,
+            vec![
+                "server: property `host` should be integer, found localhost",
+                "server: missing property `debug` (bool)",
+                "server/route: missing argument 1 (integer)",
+                "server/middleware: missing node",
+                "cache: missing node",
+            ]
+        );
+        Ok(())
+    }
+
+    #[test]
+    #[should_panic(expected = "unknown type")]
+    fn unknown_type() {
+        let doc: KdlDocument = "node 1".parse().unwrap();
+        doc.assert_structure("node int");
+    }
+}
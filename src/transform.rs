@@ -0,0 +1,231 @@
+use std::fmt::Display;
+
+use crate::{KdlDocument, KdlEntry, KdlNode, KdlValue};
+
+/// A declarative change to apply to a [`KdlDocument`], using
+/// [`KdlDocument::apply_transformations`].
+///
+/// Transforms that target a node by name apply to every node with that name,
+/// at any depth in the document.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum KdlTransform {
+    /// Renames all nodes named `from` to `to`.
+    RenameNode {
+        /// Name of the nodes to rename.
+        from: String,
+        /// Their new name.
+        to: String,
+    },
+    /// Removes the `prop` property from all nodes named `node`.
+    RemoveProp {
+        /// Name of the nodes to remove the property from.
+        node: String,
+        /// Name of the property to remove.
+        prop: String,
+    },
+    /// Sets the `prop` property to `value` on all nodes named `node` that
+    /// don't already have it.
+    SetPropDefault {
+        /// Name of the nodes to set the property on.
+        node: String,
+        /// Name of the property to set.
+        prop: String,
+        /// Value to set the property to.
+        value: KdlValue,
+    },
+}
+
+impl Display for KdlTransform {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::RenameNode { from, to } => write!(f, "rename node `{from}` to `{to}`"),
+            Self::RemoveProp { node, prop } => {
+                write!(f, "remove property `{prop}` from node `{node}`")
+            }
+            Self::SetPropDefault { node, prop, value } => {
+                write!(f, "default property `{prop}` of node `{node}` to {value}")
+            }
+        }
+    }
+}
+
+/// The outcome of applying a single [`KdlTransform`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum KdlTransformResult {
+    /// The transform changed this many nodes.
+    Applied(usize),
+    /// The transform didn't match any nodes, so the document was left as-is.
+    NoMatch,
+}
+
+impl KdlTransformResult {
+    fn from_count(count: usize) -> Self {
+        if count == 0 {
+            Self::NoMatch
+        } else {
+            Self::Applied(count)
+        }
+    }
+}
+
+impl Display for KdlTransformResult {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            Self::Applied(1) => write!(f, "changed 1 node"),
+            Self::Applied(count) => write!(f, "changed {count} nodes"),
+            Self::NoMatch => write!(f, "no matching nodes"),
+        }
+    }
+}
+
+impl KdlDocument {
+    /// Applies each of `transforms` to this document, in order, returning one
+    /// [`KdlTransformResult`] per transform describing what it did.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::{KdlDocument, KdlTransform, KdlTransformResult};
+    /// let mut doc: KdlDocument = "server host=a\nserver host=b debug=#true".parse().unwrap();
+    /// let results = doc.apply_transformations(&[
+    ///     KdlTransform::RenameNode { from: "server".into(), to: "backend".into() },
+    ///     KdlTransform::RemoveProp { node: "backend".into(), prop: "debug".into() },
+    ///     KdlTransform::RemoveProp { node: "backend".into(), prop: "nope".into() },
+    /// ]);
+    /// assert_eq!(
+    ///     results,
+    ///     vec![
+    ///         KdlTransformResult::Applied(2),
+    ///         KdlTransformResult::Applied(1),
+    ///         KdlTransformResult::NoMatch,
+    ///     ]
+    /// );
+    /// assert_eq!(doc.to_string(), "backend host=a\nbackend host=b");
+    /// ```
+    pub fn apply_transformations(
+        &mut self,
+        transforms: &[KdlTransform],
+    ) -> Vec<KdlTransformResult> {
+        transforms
+            .iter()
+            .map(|transform| {
+                let mut count = 0;
+                for_each_node_mut(self, &mut |node| {
+                    if apply_transform(node, transform) {
+                        count += 1;
+                    }
+                });
+                KdlTransformResult::from_count(count)
+            })
+            .collect()
+    }
+}
+
+fn for_each_node_mut(doc: &mut KdlDocument, f: &mut impl FnMut(&mut KdlNode)) {
+    for node in doc.nodes_mut() {
+        f(node);
+        if let Some(children) = node.children_mut() {
+            for_each_node_mut(children, f);
+        }
+    }
+}
+
+/// Applies `transform` to `node`, returning whether anything changed.
+fn apply_transform(node: &mut KdlNode, transform: &KdlTransform) -> bool {
+    match transform {
+        KdlTransform::RenameNode { from, to } => {
+            if node.name().value() != from {
+                return false;
+            }
+            node.set_name(to.as_str());
+            true
+        }
+        KdlTransform::RemoveProp { node: name, prop } => {
+            if node.name().value() != name {
+                return false;
+            }
+            let before = node.entries().len();
+            node.retain(|e| e.name().map(|n| n.value()) != Some(prop.as_str()));
+            node.entries().len() != before
+        }
+        KdlTransform::SetPropDefault {
+            node: name,
+            prop,
+            value,
+        } => {
+            if node.name().value() != name || node.entry(prop.as_str()).is_some() {
+                return false;
+            }
+            node.push(KdlEntry::new_prop(prop.as_str(), value.clone()));
+            true
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn apply_transformations() -> miette::Result<()> {
+        let mut doc: KdlDocument = r#"
+service name=web port=80 legacy=#true {
+    service name=sidecar legacy=#false
+}
+database port=5432
+"#
+        .parse()?;
+        let transforms = [
+            KdlTransform::SetPropDefault {
+                node: "service".into(),
+                prop: "port".into(),
+                value: 8080.into(),
+            },
+            KdlTransform::RemoveProp {
+                node: "service".into(),
+                prop: "legacy".into(),
+            },
+            KdlTransform::RenameNode {
+                from: "database".into(),
+                to: "db".into(),
+            },
+            KdlTransform::RenameNode {
+                from: "cache".into(),
+                to: "redis".into(),
+            },
+        ];
+        let results = doc.apply_transformations(&transforms);
+        assert_eq!(
+            results,
+            vec![
+                KdlTransformResult::Applied(1),
+                KdlTransformResult::Applied(2),
+                KdlTransformResult::Applied(1),
+                KdlTransformResult::NoMatch,
+            ]
+        );
+        assert_eq!(
+            results
+                .iter()
+                .zip(&transforms)
+                .map(|(r, t)| format!("{t}: {r}"))
+                .collect::<Vec<_>>(),
+            vec![
+                "default property `port` of node `service` to 8080: changed 1 node",
+                "remove property `legacy` from node `service`: changed 2 nodes",
+                "rename node `database` to `db`: changed 1 node",
+                "rename node `cache` to `redis`: no matching nodes",
+            ]
+        );
+        doc.autoformat();
+        assert_eq!(
+            doc.to_string(),
+            r#"service name=web port=80 {
+    service name=sidecar port=8080
+}
+db port=5432
+"#
+        );
+        Ok(())
+    }
+}
@@ -280,6 +280,22 @@ pub(crate) fn document(input: &mut Input<'_>) -> PResult<KdlDocument> {
     Ok(doc)
 }
 
+/// A bare node list, as it would appear inside a `node-children` block:
+/// `nodes`, without a BOM, through to the end of the input.
+pub(crate) fn fragment(input: &mut Input<'_>) -> PResult<KdlDocument> {
+    let doc = nodes.parse_next(input)?;
+    let badend = resume_after_cut(
+        cut_err(eof).context(cx().lbl("EOF").msg("Expected end of fragment")),
+        any.void(),
+    )
+    .parse_next(input)?
+    .is_none();
+    if badend {
+        fragment.parse_next(input)?;
+    }
+    Ok(doc)
+}
+
 /// `nodes := (line-space* node)* line-space*`
 fn nodes(input: &mut Input<'_>) -> PResult<KdlDocument> {
     let leading = repeat(0.., alt((line_space.void(), (slashdash, base_node).void())))
@@ -307,6 +323,7 @@ fn nodes(input: &mut Input<'_>) -> PResult<KdlDocument> {
         }),
         #[cfg(feature = "span")]
         span: _span,
+        name_index: None,
     })
 }
 
@@ -372,12 +389,12 @@ fn base_node(input: &mut Input<'_>) -> PResult<KdlNode> {
     // _both_ the error message for a string/ident parser error _and_ the error
     // message for a node name being expected.
     if !name_is_valid {
-        resume_after_cut(|input: &mut Input<'_>| -> PResult<()> {
+        resume_after_cut((|input: &mut Input<'_>| -> PResult<()> {
                 Err(ErrMode::Cut(KdlParseError {
                    span: Some(span_from_checkpoint(input, &_before_ident)),
                    ..Default::default()
                 }))
-            }.context(cx().msg("Found invalid node name")
+            }).context(cx().msg("Found invalid node name")
                           .lbl("node name")
                           .hlp("This can be any string type, including a quoted, raw, or multiline string, as well as a plain identifier string.")),
         empty).parse_next(input)?;
@@ -148,6 +148,18 @@ impl KdlValue {
             None
         }
     }
+
+    /// Returns the KDL v2 literal for this value, exactly as it would be
+    /// written out as part of an entry whose formatting has been cleared:
+    /// `#true`/`#false`, `#null`, decimal integers, floats in their shortest
+    /// round-tripping decimal form (or `#inf`, `#-inf`, `#nan`), and strings
+    /// either as bare identifiers or quoted and escaped when necessary.
+    ///
+    /// Use [`KdlEntry::to_kdl_literal_with_repr`](crate::KdlEntry::to_kdl_literal_with_repr) if you'd rather keep the
+    /// representation the value was originally parsed from.
+    pub fn to_kdl_literal(&self) -> String {
+        self.to_string()
+    }
 }
 
 impl Display for KdlValue {
@@ -186,6 +198,7 @@ pub(crate) fn is_plain_ident(ident: &str) -> bool {
             .map(|c| c == '.' || c == '-' || c == '+')
             == Some(true)
             && ident_bytes.get(1).map(|c| c.is_ascii_digit()) == Some(true))
+        && !matches!(ident, "true" | "false" | "null" | "inf" | "-inf" | "nan")
 }
 
 #[cfg(test)]
@@ -193,6 +206,8 @@ pub(crate) fn is_plain_ident(ident: &str) -> bool {
 fn plain_ident_test() {
     assert!(is_plain_ident("foo123,bar"));
     assert!(is_plain_ident("foo123~!@$%^&*.:'|?+<>,"));
+    assert!(!is_plain_ident("true"));
+    assert!(!is_plain_ident("-inf"));
 }
 
 impl KdlValue {
@@ -299,4 +314,28 @@ mod test {
         let null = KdlValue::Null;
         assert_eq!(format!("{}", null), "#null");
     }
+
+    #[test]
+    fn keyword_strings_are_quoted() {
+        for keyword in ["true", "false", "null", "inf", "-inf", "nan"] {
+            let value = KdlValue::String(keyword.into());
+            assert_eq!(value.to_string(), format!("\"{keyword}\""));
+        }
+        assert_eq!(KdlValue::String("truth".into()).to_string(), "truth");
+        assert_eq!(KdlValue::String("infinity".into()).to_string(), "infinity");
+    }
+
+    #[test]
+    fn to_kdl_literal() {
+        assert_eq!(KdlValue::from("foo").to_kdl_literal(), "foo");
+        assert_eq!(KdlValue::from("foo bar").to_kdl_literal(), r#""foo bar""#);
+        assert_eq!(KdlValue::from("").to_kdl_literal(), r#""""#);
+        assert_eq!(KdlValue::from("1.0").to_kdl_literal(), r#""1.0""#);
+        assert_eq!(KdlValue::from("null").to_kdl_literal(), r#""null""#);
+        assert_eq!(KdlValue::Integer(-42).to_kdl_literal(), "-42");
+        assert_eq!(KdlValue::Float(1.5).to_kdl_literal(), "1.5");
+        assert_eq!(KdlValue::Float(f64::NEG_INFINITY).to_kdl_literal(), "#-inf");
+        assert_eq!(KdlValue::Bool(false).to_kdl_literal(), "#false");
+        assert_eq!(KdlValue::Null.to_kdl_literal(), "#null");
+    }
 }
@@ -0,0 +1,193 @@
+use std::collections::HashMap;
+
+use crate::{KdlDocument, KdlNode, KdlValue};
+
+/// A node's contents in the schema-free structure produced by
+/// [`KdlDocument::into_value_map`].
+#[derive(Debug, Clone, PartialEq)]
+pub enum KdlNodeValue {
+    /// A node with only arguments (or nothing at all), as its list of
+    /// argument values.
+    Leaf(Vec<KdlValue>),
+    /// A node with properties or children, as a map from property and child
+    /// names to their values.
+    Node(HashMap<String, KdlNodeValue>),
+}
+
+impl KdlDocument {
+    /// Consumes this document, turning it into a map from node names to
+    /// their values. This is a lightweight, schema-free alternative to
+    /// deserializing a document with `serde`.
+    ///
+    /// Nodes with only arguments become a [`KdlNodeValue::Leaf`] with their
+    /// argument values. Nodes with properties or children become a
+    /// [`KdlNodeValue::Node`], where each property becomes a single-value
+    /// leaf, each child is converted recursively, and any arguments are kept
+    /// in a leaf named `-`.
+    ///
+    /// This conversion is lossy: if several nodes (or properties) at the same
+    /// level have the same name, only the last one is kept, and node order,
+    /// type annotations, and formatting are all discarded.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::{KdlDocument, KdlNodeValue};
+    /// let doc: KdlDocument = "name kdl\nserver port=8080 {\n    tls #true\n}".parse().unwrap();
+    /// let map = doc.into_value_map();
+    /// assert_eq!(map["name"], KdlNodeValue::Leaf(vec!["kdl".into()]));
+    /// let KdlNodeValue::Node(server) = &map["server"] else { panic!() };
+    /// assert_eq!(server["port"], KdlNodeValue::Leaf(vec![8080.into()]));
+    /// assert_eq!(server["tls"], KdlNodeValue::Leaf(vec![true.into()]));
+    /// ```
+    pub fn into_value_map(self) -> HashMap<String, KdlNodeValue> {
+        self.nodes
+            .into_iter()
+            .map(|node| (node.name().value().to_string(), node_value(node)))
+            .collect()
+    }
+
+    /// Builds a document from a map produced by
+    /// [`KdlDocument::into_value_map`] (or written by hand).
+    ///
+    /// Each [`KdlNodeValue::Leaf`] becomes a node with its values as
+    /// arguments, and each [`KdlNodeValue::Node`] becomes a node with its
+    /// entries as children. Since maps are unordered, nodes at each level are
+    /// sorted by name. Arguments kept in a `-` leaf become the node's
+    /// arguments again, but properties are written back as children, so
+    /// `server "main" port=8080` round-trips to `server main { port 8080 }`.
+    pub fn from_value_map(map: HashMap<String, KdlNodeValue>) -> KdlDocument {
+        let mut doc = value_map_document(map);
+        doc.autoformat();
+        doc
+    }
+}
+
+fn node_value(node: KdlNode) -> KdlNodeValue {
+    let has_props = node.entries().iter().any(|e| e.name().is_some());
+    if node.children().is_none() && !has_props {
+        return KdlNodeValue::Leaf(node.entries.into_iter().map(|e| e.value).collect());
+    }
+    let mut map = HashMap::new();
+    let mut args = Vec::new();
+    for entry in node.entries {
+        match &entry.name {
+            Some(name) => {
+                map.insert(
+                    name.value().to_string(),
+                    KdlNodeValue::Leaf(vec![entry.value]),
+                );
+            }
+            None => args.push(entry.value),
+        }
+    }
+    if !args.is_empty() {
+        map.insert("-".into(), KdlNodeValue::Leaf(args));
+    }
+    if let Some(children) = node.children {
+        map.extend(children.into_value_map());
+    }
+    KdlNodeValue::Node(map)
+}
+
+fn value_map_document(map: HashMap<String, KdlNodeValue>) -> KdlDocument {
+    let mut entries = map.into_iter().collect::<Vec<_>>();
+    entries.sort_by(|(a, _), (b, _)| a.cmp(b));
+    let mut doc = KdlDocument::new();
+    for (name, value) in entries {
+        let mut node = KdlNode::new(name);
+        match value {
+            KdlNodeValue::Leaf(values) => {
+                for value in values {
+                    node.push(value);
+                }
+            }
+            KdlNodeValue::Node(mut map) => {
+                match map.remove("-") {
+                    Some(KdlNodeValue::Leaf(args)) => {
+                        for arg in args {
+                            node.push(arg);
+                        }
+                    }
+                    Some(value) => {
+                        map.insert("-".into(), value);
+                    }
+                    None => {}
+                }
+                node.set_children(value_map_document(map));
+            }
+        }
+        doc.nodes_mut().push(node);
+    }
+    doc
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn into_value_map() -> miette::Result<()> {
+        let doc: KdlDocument = r#"
+title "My App"
+tags web api
+empty
+server "main" host=localhost port=8080 {
+    tls {
+        cert "a.pem"
+    }
+}
+title "Overridden"
+"#
+        .parse()?;
+        let leaf = KdlNodeValue::Leaf;
+        assert_eq!(
+            doc.into_value_map(),
+            HashMap::from([
+                ("title".into(), leaf(vec!["Overridden".into()])),
+                ("tags".into(), leaf(vec!["web".into(), "api".into()])),
+                ("empty".into(), leaf(vec![])),
+                (
+                    "server".into(),
+                    KdlNodeValue::Node(HashMap::from([
+                        ("-".into(), leaf(vec!["main".into()])),
+                        ("host".into(), leaf(vec!["localhost".into()])),
+                        ("port".into(), leaf(vec![8080.into()])),
+                        (
+                            "tls".into(),
+                            KdlNodeValue::Node(HashMap::from([(
+                                "cert".into(),
+                                leaf(vec!["a.pem".into()])
+                            )]))
+                        ),
+                    ]))
+                ),
+            ])
+        );
+        Ok(())
+    }
+
+    #[test]
+    fn from_value_map() -> miette::Result<()> {
+        let doc: KdlDocument = "tags web api\nserver port=8080 {\n    tls {\n    }\n}".parse()?;
+        let map = doc.into_value_map();
+        let doc = KdlDocument::from_value_map(map.clone());
+        assert_eq!(
+            doc.to_string(),
+            "server {\n    port 8080\n    tls {\n    }\n}\ntags web api\n"
+        );
+        assert_eq!(doc.into_value_map(), map);
+
+        // Arguments stored under `-` become arguments again.
+        let doc: KdlDocument = "server \"main\" port=1".parse()?;
+        let map = doc.into_value_map();
+        let doc = KdlDocument::from_value_map(map.clone());
+        assert_eq!(doc.to_string(), "server main {\n    port 1\n}\n");
+        assert_eq!(doc.into_value_map(), map);
+
+        assert!(KdlDocument::from_value_map(HashMap::new())
+            .nodes()
+            .is_empty());
+        Ok(())
+    }
+}
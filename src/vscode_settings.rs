@@ -0,0 +1,555 @@
+use miette::SourceSpan;
+use serde_json::{Map, Value};
+
+use crate::{
+    error::span_or_default,
+    json::{error_offset, scalar},
+    KdlDocument, KdlEntry, KdlError, KdlNode, KdlValue,
+};
+
+impl KdlDocument {
+    /// Parses a VS Code `settings.json` file into a document.
+    ///
+    /// Each dotted setting name is split on `.` into nested nodes, with the
+    /// setting's value on the innermost one, so settings that share a prefix
+    /// end up next to each other:
+    ///
+    /// ```kdl
+    /// editor {
+    ///     fontSize 14
+    ///     rulers 80 120
+    /// }
+    /// "[python]" {
+    ///     editor {
+    ///         tabSize 4
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// * Scalars become the node's only argument.
+    /// * Arrays of scalars become multiple arguments. Arrays with a single
+    ///   item, or with objects or arrays in them, are instead marked with an
+    ///   `(array)` type annotation, with any nested values as children named
+    ///   `-`, so they stay arrays when converted back.
+    /// * Objects, like `files.exclude`'s patterns, become children of an
+    ///   `(object)` node. Their keys aren't split.
+    /// * Language-specific settings, like `"[python]"`, become a node with
+    ///   their settings as children, split the same way.
+    ///
+    /// Comments and trailing commas, which VS Code allows, are ignored.
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// Fails if the file isn't valid JSON (with comments), if it isn't an
+    /// object, if a setting name has an empty part (like `editor..fontSize`),
+    /// or if a setting with an object or array value is also the prefix of
+    /// another setting.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_vscode_settings(r#"{
+    ///     // Bigger, please.
+    ///     "editor.fontSize": 14,
+    ///     "editor.rulers": [80, 120],
+    ///     "files.autoSave": "afterDelay",
+    /// }"#).unwrap();
+    /// assert_eq!(
+    ///     doc.to_string(),
+    ///     "editor {\n    fontSize 14\n    rulers 80 120\n}\nfiles {\n    autoSave afterDelay\n}\n"
+    /// );
+    /// ```
+    pub fn from_vscode_settings(json: &str) -> Result<KdlDocument, KdlError> {
+        let error = |message: &str| KdlError::single(json, (0, 0), message, None);
+        let value: Value = serde_json::from_str(&strip_jsonc(json)).map_err(|e| {
+            let offset = error_offset(json, &e).min(json.len());
+            KdlError::single(json, (offset, 0), "Invalid JSON", Some(&e.to_string()))
+        })?;
+        let Value::Object(settings) = value else {
+            return Err(error("Expected VS Code settings to be a JSON object"));
+        };
+        let mut doc = KdlDocument::new();
+        add_settings(&mut doc, settings, true).map_err(|message| error(&message))?;
+        doc.autoformat();
+        Ok(doc)
+    }
+
+    /// Writes this document out as a VS Code `settings.json` file. This is
+    /// the reverse of [`KdlDocument::from_vscode_settings`]: the names of
+    /// nested nodes are joined with `.`, and each node's arguments become its
+    /// setting's value.
+    ///
+    /// A node with a single argument is a scalar setting, and any other
+    /// number of arguments make an array. Nodes with an `(array)` or
+    /// `(object)` type annotation become arrays or objects, and toplevel
+    /// nodes named like `[python]` hold language-specific settings.
+    ///
+    /// This is only available with the `json` feature.
+    ///
+    /// Fails if a node has properties, a type annotation other than `(array)`
+    /// or `(object)`, or a value that can't be represented in JSON, like an
+    /// infinite float or an integer that doesn't fit in 64 bits.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc: KdlDocument = "editor {\n    fontSize 14\n    rulers 80 120\n}".parse().unwrap();
+    /// assert_eq!(
+    ///     doc.to_vscode_settings().unwrap(),
+    ///     "{\n  \"editor.fontSize\": 14,\n  \"editor.rulers\": [\n    80,\n    120\n  ]\n}\n"
+    /// );
+    /// ```
+    pub fn to_vscode_settings(&self) -> Result<String, KdlError> {
+        let mut settings = Map::new();
+        for node in self.nodes() {
+            let name = node.name().value();
+            if name.starts_with('[') && name.ends_with(']') && node.ty().is_none() {
+                if !node.entries().is_empty() {
+                    return Err(KdlError::in_document(
+                        self,
+                        span_or_default(node),
+                        "Language-specific settings can't have a value",
+                    ));
+                }
+                let mut language = Map::new();
+                if let Some(children) = node.children() {
+                    for child in children.nodes() {
+                        write_settings(&mut language, "", child).map_err(|(span, message)| {
+                            KdlError::in_document(self, span, &message)
+                        })?;
+                    }
+                }
+                settings.insert(name.into(), Value::Object(language));
+            } else {
+                write_settings(&mut settings, "", node)
+                    .map_err(|(span, message)| KdlError::in_document(self, span, &message))?;
+            }
+        }
+        let mut out = serde_json::to_string_pretty(&Value::Object(settings))
+            .expect("writing JSON to a String can't fail");
+        out.push('\n');
+        Ok(out)
+    }
+}
+
+/// Adds `settings` to `doc`, splitting their names on `.` if `split` is set.
+fn add_settings(
+    doc: &mut KdlDocument,
+    settings: Map<String, Value>,
+    split: bool,
+) -> Result<(), String> {
+    for (key, value) in settings {
+        if split && key.starts_with('[') && key.ends_with(']') {
+            if let Value::Object(language) = value {
+                let mut node = KdlNode::new(key);
+                add_settings(node.ensure_children(), language, true)?;
+                doc.nodes_mut().push(node);
+                continue;
+            }
+        }
+        let mut parts: Vec<&str> = if split {
+            key.split('.').collect()
+        } else {
+            vec![key.as_str()]
+        };
+        if split && parts.iter().any(|part| part.is_empty()) {
+            return Err(format!("Invalid setting name `{key}`"));
+        }
+        let last = parts.pop().unwrap();
+        let mut current = &mut *doc;
+        for part in parts {
+            current = prefix_node(current, part, &key)?.ensure_children();
+        }
+        let node = prefix_node(current, last, &key)?;
+        match value {
+            Value::Array(items)
+                if items.len() != 1 && items.iter().all(|i| scalar(i).is_some()) =>
+            {
+                for item in &items {
+                    node.push(scalar(item).unwrap());
+                }
+            }
+            Value::Array(_) | Value::Object(_) if node.children().is_some() => {
+                return Err(format!(
+                    "`{key}` can't have an array or object value and also prefix other settings"
+                ));
+            }
+            Value::Array(items) => {
+                node.set_ty("array");
+                if items.iter().all(|i| scalar(i).is_some()) {
+                    for item in &items {
+                        node.push(scalar(item).unwrap());
+                    }
+                } else {
+                    for item in items {
+                        let mut item_doc = KdlDocument::new();
+                        add_value(&mut item_doc, "-", item)?;
+                        node.ensure_children().nodes_mut().extend(item_doc.nodes);
+                    }
+                }
+            }
+            Value::Object(object) => {
+                node.set_ty("object");
+                add_settings(node.ensure_children(), object, false)?;
+            }
+            value => node.push(scalar(&value).unwrap()),
+        }
+    }
+    Ok(())
+}
+
+/// Adds a single, unsplit `name` node with `value` to `doc`.
+fn add_value(doc: &mut KdlDocument, name: &str, value: Value) -> Result<(), String> {
+    add_settings(doc, Map::from_iter([(name.to_string(), value)]), false)
+}
+
+/// Finds the untyped node called `name` in `doc`, or adds it.
+fn prefix_node<'a>(
+    doc: &'a mut KdlDocument,
+    name: &str,
+    key: &str,
+) -> Result<&'a mut KdlNode, String> {
+    let nodes = doc.nodes_mut();
+    match nodes.iter().position(|node| node.name().value() == name) {
+        Some(idx) if nodes[idx].ty().is_some() => Err(format!(
+            "`{key}` is nested inside a setting with an array or object value"
+        )),
+        Some(idx) => Ok(&mut nodes[idx]),
+        None => {
+            nodes.push(KdlNode::new(name));
+            Ok(nodes.last_mut().unwrap())
+        }
+    }
+}
+
+type SettingsResult<T> = Result<T, (SourceSpan, String)>;
+
+/// Writes `node`'s value, if it has one, and its children's settings into
+/// `settings`, prefixing their names with `prefix`.
+fn write_settings(
+    settings: &mut Map<String, Value>,
+    prefix: &str,
+    node: &KdlNode,
+) -> SettingsResult<()> {
+    let name = format!("{prefix}{}", node.name().value());
+    let has_children = node.children().is_some_and(|c| !c.nodes().is_empty());
+    if node.ty().is_some() || !node.entries().is_empty() || !has_children {
+        settings.insert(name.clone(), node_value(node)?);
+    }
+    if node.ty().is_none() {
+        if let Some(children) = node.children() {
+            for child in children.nodes() {
+                write_settings(settings, &format!("{name}."), child)?;
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Converts a node's arguments (or, for `(array)` and `(object)` nodes,
+/// children) into a JSON value.
+fn node_value(node: &KdlNode) -> SettingsResult<Value> {
+    if let Some(entry) = node.entries().iter().find(|e| e.name().is_some()) {
+        return Err((
+            span_or_default(entry),
+            "VS Code settings can't have properties".into(),
+        ));
+    }
+    let args = node
+        .entries()
+        .iter()
+        .map(entry_value)
+        .collect::<SettingsResult<Vec<_>>>()?;
+    let children = node.children().map_or(&[][..], |c| c.nodes());
+    match node.ty().map(|ty| ty.value()) {
+        None if args.len() == 1 => Ok(args.into_iter().next().unwrap()),
+        None => Ok(Value::Array(args)),
+        Some("array") => {
+            let mut items = args;
+            for child in children {
+                items.push(node_value(child)?);
+            }
+            Ok(Value::Array(items))
+        }
+        Some("object") if args.is_empty() => {
+            let mut object = Map::new();
+            for child in children {
+                object.insert(child.name().value().into(), node_value(child)?);
+            }
+            Ok(Value::Object(object))
+        }
+        Some("object") => Err((span_or_default(node), "Objects can't have arguments".into())),
+        Some(ty) => Err((
+            span_or_default(node),
+            format!("Unsupported type annotation `({ty})`, expected `(array)` or `(object)`"),
+        )),
+    }
+}
+
+fn entry_value(entry: &KdlEntry) -> SettingsResult<Value> {
+    let error = |message: &str| (span_or_default(entry), message.into());
+    Ok(match entry.value() {
+        KdlValue::String(s) => Value::String(s.clone()),
+        KdlValue::Integer(i) => {
+            if let Ok(i) = i64::try_from(*i) {
+                i.into()
+            } else if let Ok(u) = u64::try_from(*i) {
+                u.into()
+            } else {
+                return Err(error("Integer is too large for JSON"));
+            }
+        }
+        KdlValue::Float(f) => serde_json::Number::from_f64(*f)
+            .map(Value::Number)
+            .ok_or_else(|| error("JSON can't represent infinite or NaN floats"))?,
+        KdlValue::Bool(b) => Value::Bool(*b),
+        KdlValue::Null => Value::Null,
+    })
+}
+
+/// Blanks out `//` and `/* */` comments and trailing commas, which VS Code
+/// allows in its settings files, so the result can be parsed as plain JSON.
+/// Everything else keeps its position, so error offsets still line up.
+fn strip_jsonc(json: &str) -> String {
+    let mut out = String::with_capacity(json.len());
+    let mut chars = json.char_indices().peekable();
+    let mut in_string = false;
+    // Offset (in `out`) of a comma that might turn out to be a trailing one.
+    let mut comma = None;
+    while let Some((idx, c)) = chars.next() {
+        if in_string {
+            out.push(c);
+            match c {
+                '\\' => {
+                    if let Some((_, escaped)) = chars.next() {
+                        out.push(escaped);
+                    }
+                }
+                '"' => in_string = false,
+                _ => {}
+            }
+            continue;
+        }
+        match c {
+            '/' if json[idx + 1..].starts_with('/') => {
+                out.push(' ');
+                while let Some((_, c)) = chars.next_if(|(_, c)| *c != '\n') {
+                    out.push(if c == '\r' { c } else { ' ' });
+                }
+            }
+            '/' if json[idx + 1..].starts_with('*') => {
+                out.push(' ');
+                let (_, star) = chars.next().unwrap();
+                out.push(if star == '\n' { star } else { ' ' });
+                let mut prev = ' ';
+                for (_, c) in chars.by_ref() {
+                    out.push(if c == '\n' { c } else { ' ' });
+                    if prev == '*' && c == '/' {
+                        break;
+                    }
+                    prev = c;
+                }
+            }
+            ',' => {
+                comma = Some(out.len());
+                out.push(c);
+            }
+            '}' | ']' => {
+                if let Some(comma) = comma.take() {
+                    out.replace_range(comma..comma + 1, " ");
+                }
+                out.push(c);
+            }
+            c if c.is_whitespace() => out.push(c),
+            c => {
+                comma = None;
+                in_string = c == '"';
+                out.push(c);
+            }
+        }
+    }
+    out
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts_formatted;
+
+    fn assert_round_trips(json: &str) -> miette::Result<()> {
+        let doc = KdlDocument::from_vscode_settings(json)?;
+        let expected: Value = serde_json::from_str(&strip_jsonc(json)).unwrap();
+        let actual: Value = serde_json::from_str(&doc.to_vscode_settings()?).unwrap();
+        pretty_assertions::assert_eq!(actual, expected);
+        Ok(())
+    }
+
+    const NESTED: &str = r#"{
+    "editor.fontSize": 14,
+    "editor.fontFamily": "Fira Code, monospace",
+    "workbench.colorTheme": "Default Dark+",
+    "editor.minimap.enabled": false,
+    "editor.minimap.scale": 1.5,
+    "editor": null,
+    "[python]": {
+        "editor.tabSize": 4,
+        "editor.defaultFormatter": "ms-python.black-formatter"
+    }
+}"#;
+
+    #[test]
+    fn nested_settings() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_vscode_settings,
+            NESTED,
+            r#"
+editor #null {
+    fontSize 14
+    fontFamily "Fira Code, monospace"
+    minimap {
+        enabled #false
+        scale 1.5
+    }
+}
+workbench {
+    colorTheme "Default Dark+"
+}
+"[python]" {
+    editor {
+        tabSize 4
+        defaultFormatter ms-python.black-formatter
+    }
+}
+"#,
+        )?;
+        assert_round_trips(NESTED)
+    }
+
+    const ARRAYS: &str = r##"{
+    "editor.rulers": [80, 120],
+    "editor.tokenColorCustomizations": {
+        "comments": "#808080",
+        "textMateRules": [{"scope": "keyword", "settings": {"fontStyle": "bold"}}]
+    },
+    "files.exclude": {"**/.git": true, "**/node_modules": true},
+    "search.exclude": {},
+    "git.ignoredRepositories": [],
+    "cSpell.words": ["kdl"],
+    "terminal.integrated.env.linux": [["a", 1], null]
+}"##;
+
+    #[test]
+    fn array_values() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_vscode_settings,
+            ARRAYS,
+            r##"
+editor {
+    rulers 80 120
+    (object)tokenColorCustomizations {
+        comments "#808080"
+        (array)textMateRules {
+            (object)- {
+                scope keyword
+                (object)settings {
+                    fontStyle bold
+                }
+            }
+        }
+    }
+}
+files {
+    (object)exclude {
+        "**/.git" #true
+        "**/node_modules" #true
+    }
+}
+search {
+    (object)exclude {
+    }
+}
+git {
+    ignoredRepositories
+}
+cSpell {
+    (array)words kdl
+}
+terminal {
+    integrated {
+        env {
+            (array)linux {
+                - a 1
+                - #null
+            }
+        }
+    }
+}
+"##,
+        )?;
+        assert_round_trips(ARRAYS)
+    }
+
+    const BOOLEANS: &str = r#"// Settings
+{
+    /* Save often. */
+    "files.autoSave": true,
+    "telemetry.telemetryLevel": "off", // url: "https://example.com"
+    "editor.formatOnSave": false,
+    "git.enabled": true,
+}
+"#;
+
+    #[test]
+    fn boolean_settings() -> miette::Result<()> {
+        assert_converts_formatted(
+            KdlDocument::from_vscode_settings,
+            BOOLEANS,
+            r#"
+files {
+    autoSave #true
+}
+telemetry {
+    telemetryLevel off
+}
+editor {
+    formatOnSave #false
+}
+git {
+    enabled #true
+}
+"#,
+        )?;
+        assert_round_trips(BOOLEANS)
+    }
+
+    #[test]
+    fn errors() -> miette::Result<()> {
+        for bad in [
+            "[]",
+            "{\"a\": }",
+            "{\"editor..fontSize\": 1}",
+            "{\"a.\": 1}",
+            "{\"a.b\": 1, \"a\": [1, 2, 3, {}]}",
+            "{\"a\": {}, \"a.b\": 1}",
+        ] {
+            assert!(
+                KdlDocument::from_vscode_settings(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        for bad in [
+            "editor size=14",
+            "(u8)editor 1",
+            "(object)editor 1",
+            "editor #inf",
+            "editor 0xffffffffffffffffff",
+            "\"[python]\" 1",
+        ] {
+            let doc: KdlDocument = bad.parse()?;
+            assert!(doc.to_vscode_settings().is_err(), "{bad:?} should fail");
+        }
+        Ok(())
+    }
+}
@@ -0,0 +1,545 @@
+use yaml_rust2::{yaml::Hash, Yaml, YamlLoader};
+
+use crate::{KdlDocument, KdlError, KdlNode, KdlValue};
+
+impl KdlDocument {
+    /// Parses a Kubernetes
+    /// [kubeconfig](https://kubernetes.io/docs/concepts/configuration/organize-cluster-access-kubeconfig/)
+    /// file into a document.
+    ///
+    /// The `clusters`, `contexts` and `users` lists become nodes whose
+    /// children are one `cluster`, `context` or `user` node per list item,
+    /// with the item's `name` as their argument, and the item's settings as
+    /// their children:
+    ///
+    /// ```kdl
+    /// clusters {
+    ///     cluster dev {
+    ///         server "https://dev.example.com:6443"
+    ///     }
+    /// }
+    /// ```
+    ///
+    /// Everything else is converted generically: scalars become a node's
+    /// only argument, mappings become children, lists of scalars become
+    /// multiple arguments, and other lists become children named `-`, one per
+    /// item.
+    ///
+    /// Fails if the YAML is invalid, if it has more than one YAML document in
+    /// it, if it isn't a mapping at the toplevel, or if a cluster, context or
+    /// user doesn't have a `name`.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_kubeconfig(r#"
+    /// apiVersion: v1
+    /// kind: Config
+    /// current-context: dev
+    /// clusters:
+    /// - name: dev
+    ///   cluster:
+    ///     server: https://dev.example.com:6443
+    /// "#).unwrap();
+    /// assert_eq!(
+    ///     doc.get("clusters").unwrap().children().unwrap().get_arg("cluster"),
+    ///     Some(&"dev".into())
+    /// );
+    /// assert_eq!(doc.get_arg("current-context"), Some(&"dev".into()));
+    /// ```
+    pub fn from_kubeconfig(yaml: &str) -> Result<KdlDocument, KdlError> {
+        let error = |message: &str| KdlError::single(yaml, (0, 0), message, None);
+        let mut doc = KdlDocument::new();
+        let hash = match load_single(yaml, "kubeconfig")? {
+            None | Some(Yaml::Null) => return Ok(doc),
+            Some(Yaml::Hash(hash)) => hash,
+            Some(_) => return Err(error("Expected kubeconfig to be a YAML mapping")),
+        };
+        for (key, value) in hash {
+            let key = key_to_string(&key).ok_or_else(|| error("Unsupported YAML key"))?;
+            let singular = match key.as_str() {
+                "clusters" => "cluster",
+                "contexts" => "context",
+                "users" => "user",
+                _ => {
+                    doc.nodes_mut()
+                        .push(yaml_to_node(&key, value).map_err(|msg| error(&msg))?);
+                    continue;
+                }
+            };
+            let mut list = KdlNode::new(key.as_str());
+            let items = match value {
+                Yaml::Array(items) => items,
+                Yaml::Null => Vec::new(),
+                _ => return Err(error(&format!("Expected `{key}` to be a YAML list"))),
+            };
+            for item in items {
+                let node = named_item(singular, item).map_err(|msg| error(&msg))?;
+                list.ensure_children().nodes_mut().push(node);
+            }
+            doc.nodes_mut().push(list);
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+
+    /// Parses a flat YAML document, where every toplevel key maps to a single
+    /// scalar (like many Python application configs), into a document.
+    ///
+    /// Each key becomes a node with its value as its only argument. Strings,
+    /// integers, floats, booleans and nulls are converted to the matching
+    /// [`KdlValue`].
+    ///
+    /// Fails if the YAML is invalid, if it has more than one YAML document in
+    /// it, if it isn't a mapping at the toplevel, or if any value is a
+    /// mapping or a list.
+    ///
+    /// # Examples
+    ///
+    /// ```rust
+    /// # use kdl::KdlDocument;
+    /// let doc = KdlDocument::from_flat_yaml("debug: true\nworkers: 4\nname: my-app\n").unwrap();
+    /// assert_eq!(doc.to_string(), "debug #true\nworkers 4\nname my-app\n");
+    /// ```
+    pub fn from_flat_yaml(s: &str) -> Result<KdlDocument, KdlError> {
+        let error = |message: String| {
+            KdlError::single(
+                s,
+                (0, 0),
+                message,
+                Some("Only flat YAML is supported here. For nested Kubernetes YAML, use `KdlDocument::from_kubeconfig` or `KdlDocument::from_k8s_manifest`."),
+            )
+        };
+        let mut doc = KdlDocument::new();
+        let hash = match load_single(s, "flat")? {
+            None | Some(Yaml::Null) => return Ok(doc),
+            Some(Yaml::Hash(hash)) => hash,
+            Some(_) => return Err(error("Expected a YAML mapping".into())),
+        };
+        for (key, value) in hash {
+            let key = key_to_string(&key).ok_or_else(|| error("Unsupported YAML key".into()))?;
+            let value = scalar(&value)
+                .ok_or_else(|| error(format!("Expected `{key}` to have a scalar value")))?;
+            doc.nodes_mut()
+                .push(KdlNode::new(key).with_appended_arg(value));
+        }
+        doc.autoformat();
+        Ok(doc)
+    }
+}
+
+/// Loads every YAML document in `yaml`.
+pub(crate) fn load_all(yaml: &str) -> Result<Vec<Yaml>, KdlError> {
+    YamlLoader::load_from_str(yaml).map_err(|e| {
+        // The marker is a char index, which only matches the byte offset for
+        // ASCII input.
+        let offset = yaml
+            .char_indices()
+            .nth(e.marker().index())
+            .map_or(yaml.len(), |(offset, _)| offset);
+        KdlError::single(yaml, (offset, 0), "Invalid YAML", Some(e.info()))
+    })
+}
+
+/// Loads `yaml`, which must contain at most one YAML document.
+pub(crate) fn load_single(yaml: &str, what: &str) -> Result<Option<Yaml>, KdlError> {
+    let mut docs = load_all(yaml)?;
+    if docs.len() > 1 {
+        return Err(KdlError::single(
+            yaml,
+            (0, 0),
+            format!("Expected a single {what} YAML document"),
+            None,
+        ));
+    }
+    Ok(docs.pop())
+}
+
+/// Converts a `{name: foo, cluster: {...}, ...}` kubeconfig list item into a
+/// `cluster foo { ... }` node. The `cluster` (or `context`, or `user`)
+/// mapping is merged into the node's children, along with any other keys.
+fn named_item(singular: &str, item: Yaml) -> Result<KdlNode, String> {
+    let Yaml::Hash(hash) = item else {
+        return Err(format!("Expected each {singular} to be a YAML mapping"));
+    };
+    let mut node = KdlNode::new(singular);
+    let mut name = None;
+    for (key, value) in hash {
+        let key = key_to_string(&key).ok_or("Unsupported YAML key")?;
+        match (key.as_str(), value) {
+            ("name", value) => name = Some(key_to_string(&value).ok_or("Invalid name")?),
+            (key, Yaml::Hash(settings)) if key == singular => {
+                for (key, value) in settings {
+                    let key = key_to_string(&key).ok_or("Unsupported YAML key")?;
+                    let child = yaml_to_node(&key, value)?;
+                    node.ensure_children().nodes_mut().push(child);
+                }
+            }
+            (key, value) => {
+                let child = yaml_to_node(key, value)?;
+                node.ensure_children().nodes_mut().push(child);
+            }
+        }
+    }
+    let name = name.ok_or_else(|| format!("Every {singular} must have a `name`"))?;
+    node.entries_mut().insert(0, name.into());
+    Ok(node)
+}
+
+/// How [`convert_yaml`] turns items of a list that isn't all scalars into
+/// nodes.
+#[derive(Debug, Clone, Copy)]
+pub(crate) enum ListItems {
+    /// Every item is a node named `-`.
+    Dashes,
+    /// Items are named after the list, without its trailing `s`, so each of
+    /// the `targets` is a `target` node. Lists whose name doesn't end in `s`
+    /// fall back to `-`.
+    Singular,
+    /// Like [`ListItems::Singular`], but mapping items with a scalar `name`
+    /// use it as their node's first argument instead of a child.
+    NamedSingular,
+}
+
+/// Describes how a YAML-based format maps onto KDL nodes, for
+/// [`convert_yaml`].
+#[derive(Debug, Clone, Copy)]
+pub(crate) struct YamlConversion {
+    /// Keys whose mappings are flat labels, like Kubernetes' `labels`, and
+    /// become properties rather than children.
+    pub(crate) label_maps: &'static [&'static str],
+    /// How items of lists that aren't all scalars are converted.
+    pub(crate) list_items: ListItems,
+}
+
+/// Generically converts a YAML value into a node called `name`: mappings
+/// become children, lists of scalars become arguments, and other lists
+/// become children depending on `conversion.list_items`.
+///
+/// Nested values are converted with `recurse`, which lets formats handle
+/// some keys specially at any depth and pass everything else back here.
+pub(crate) fn convert_yaml(
+    name: &str,
+    value: Yaml,
+    conversion: &YamlConversion,
+    recurse: fn(&str, Yaml) -> Result<KdlNode, String>,
+) -> Result<KdlNode, String> {
+    let mut node = KdlNode::new(name);
+    match value {
+        Yaml::Hash(labels) if conversion.label_maps.contains(&name) => {
+            for (key, value) in labels {
+                let key = key_to_string(&key).ok_or("Unsupported label name")?;
+                node.insert(key, scalar(&value).ok_or("Label values must be scalars")?);
+            }
+        }
+        Yaml::Hash(hash) => {
+            let children = node.ensure_children();
+            for (key, value) in hash {
+                let key = key_to_string(&key).ok_or("Unsupported YAML key")?;
+                children.nodes_mut().push(recurse(&key, value)?);
+            }
+        }
+        Yaml::Array(items) if items.iter().all(|i| scalar(i).is_some()) => {
+            for item in &items {
+                node.push(scalar(item).unwrap());
+            }
+        }
+        Yaml::Array(items) => {
+            let item_name = match conversion.list_items {
+                ListItems::Dashes => "-",
+                ListItems::Singular | ListItems::NamedSingular => name
+                    .strip_suffix('s')
+                    .filter(|s| !s.is_empty())
+                    .unwrap_or("-"),
+            };
+            let children = node.ensure_children();
+            for item in items {
+                let child = match (conversion.list_items, item) {
+                    (ListItems::NamedSingular, Yaml::Hash(hash)) => {
+                        named_list_item(item_name, hash, recurse)?
+                    }
+                    (_, item) => recurse(item_name, item)?,
+                };
+                children.nodes_mut().push(child);
+            }
+        }
+        value => node.push(scalar(&value).ok_or("Unsupported YAML value")?),
+    }
+    Ok(node)
+}
+
+/// Converts a mapping item of a list, like a container, using its `name`
+/// (if it has one) as the node's argument.
+fn named_list_item(
+    name: &str,
+    hash: Hash,
+    recurse: fn(&str, Yaml) -> Result<KdlNode, String>,
+) -> Result<KdlNode, String> {
+    let mut node = KdlNode::new(name);
+    for (key, value) in hash {
+        let key = key_to_string(&key).ok_or("Unsupported YAML key")?;
+        match scalar(&value) {
+            Some(item_name) if key == "name" => node.entries_mut().insert(0, item_name.into()),
+            _ => {
+                let child = recurse(&key, value)?;
+                node.ensure_children().nodes_mut().push(child);
+            }
+        }
+    }
+    Ok(node)
+}
+
+/// Converts a YAML value into a node called `name`, with no format-specific
+/// handling.
+fn yaml_to_node(name: &str, value: Yaml) -> Result<KdlNode, String> {
+    const GENERIC: YamlConversion = YamlConversion {
+        label_maps: &[],
+        list_items: ListItems::Dashes,
+    };
+    convert_yaml(name, value, &GENERIC, yaml_to_node)
+}
+
+pub(crate) fn scalar(value: &Yaml) -> Option<KdlValue> {
+    Some(match value {
+        Yaml::String(s) => KdlValue::String(s.clone()),
+        Yaml::Integer(i) => KdlValue::Integer((*i).into()),
+        Yaml::Real(_) => KdlValue::Float(value.as_f64()?),
+        Yaml::Boolean(b) => KdlValue::Bool(*b),
+        Yaml::Null => KdlValue::Null,
+        Yaml::Array(_) | Yaml::Hash(_) | Yaml::Alias(_) | Yaml::BadValue => return None,
+    })
+}
+
+pub(crate) fn key_to_string(key: &Yaml) -> Option<String> {
+    match key {
+        Yaml::String(s) | Yaml::Real(s) => Some(s.clone()),
+        Yaml::Integer(i) => Some(i.to_string()),
+        Yaml::Boolean(b) => Some(b.to_string()),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::test_util::assert_converts;
+
+    #[test]
+    fn single_cluster() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_kubeconfig,
+            r#"
+apiVersion: v1
+kind: Config
+clusters:
+- cluster:
+    certificate-authority-data: LS0tLS1CRUdJTg==
+    server: https://127.0.0.1:6443
+  name: kind-kind
+contexts:
+- context:
+    cluster: kind-kind
+    user: kind-kind
+  name: kind-kind
+current-context: kind-kind
+preferences: {}
+users:
+- name: kind-kind
+  user:
+    client-certificate-data: LS0tLS1CRUdJTg==
+    client-key-data: LS0tLS1CRUdJTg==
+"#,
+            r#"
+apiVersion v1
+kind Config
+clusters {
+    cluster kind-kind {
+        certificate-authority-data "LS0tLS1CRUdJTg=="
+        server "https://127.0.0.1:6443"
+    }
+}
+contexts {
+    context kind-kind {
+        cluster kind-kind
+        user kind-kind
+    }
+}
+current-context kind-kind
+preferences {}
+users {
+    user kind-kind {
+        client-certificate-data "LS0tLS1CRUdJTg=="
+        client-key-data "LS0tLS1CRUdJTg=="
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn multi_cluster() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_kubeconfig,
+            r#"
+apiVersion: v1
+kind: Config
+current-context: prod
+clusters:
+- name: dev
+  cluster:
+    server: https://dev.example.com:6443
+    insecure-skip-tls-verify: true
+- name: prod
+  cluster:
+    server: https://prod.example.com:6443
+    proxy-url: http://proxy:3128
+contexts:
+- name: dev
+  context: {cluster: dev, user: alice, namespace: sandbox}
+- name: prod
+  context:
+    cluster: prod
+    user: ci
+users:
+- name: alice
+  user:
+    token: abc123
+- name: ci
+  user:
+    exec:
+      apiVersion: client.authentication.k8s.io/v1
+      command: aws
+      args: [eks, get-token, --cluster-name, prod]
+      env:
+      - name: AWS_PROFILE
+        value: ci
+      - name: RETRIES
+        value: 3
+"#,
+            r#"
+apiVersion v1
+kind Config
+current-context prod
+clusters {
+    cluster dev {
+        server "https://dev.example.com:6443"
+        insecure-skip-tls-verify #true
+    }
+    cluster prod {
+        server "https://prod.example.com:6443"
+        proxy-url "http://proxy:3128"
+    }
+}
+contexts {
+    context dev {
+        cluster dev
+        user alice
+        namespace sandbox
+    }
+    context prod {
+        cluster prod
+        user ci
+    }
+}
+users {
+    user alice {
+        token abc123
+    }
+    user ci {
+        exec {
+            apiVersion "client.authentication.k8s.io/v1"
+            command aws
+            args eks get-token --cluster-name prod
+            env {
+                - {
+                    name AWS_PROFILE
+                    value ci
+                }
+                - {
+                    name RETRIES
+                    value 3
+                }
+            }
+        }
+    }
+}
+"#,
+        )
+    }
+
+    #[test]
+    fn errors() {
+        assert!(KdlDocument::from_kubeconfig("").unwrap().nodes().is_empty());
+        for bad in [
+            "- just\n- a list\n",
+            "clusters: nope\n",
+            "clusters:\n- cluster: {server: x}\n",
+            "kind: Config\n---\nkind: Config\n",
+        ] {
+            assert!(
+                KdlDocument::from_kubeconfig(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        let err = KdlDocument::from_kubeconfig("kind: Config\nclusters: [\n").unwrap_err();
+        assert!(matches!(err.diagnostics[0].span.offset(), 23..=26));
+    }
+
+    #[test]
+    fn flat_yaml() -> miette::Result<()> {
+        assert_converts(
+            KdlDocument::from_flat_yaml,
+            r#"
+# Scalars of every type
+enabled: true
+verbose: false
+workers: 8
+offset: -3
+ratio: 0.75
+big: 1.5e3
+name: my-app
+quoted: "42"
+missing: null
+tilde: ~
+empty:
+"#,
+            r#"
+enabled #true
+verbose #false
+workers 8
+offset -3
+ratio 0.75
+big 1500.0
+name my-app
+quoted "42"
+missing #null
+tilde #null
+empty #null
+"#,
+        )?;
+        assert!(KdlDocument::from_flat_yaml("").unwrap().nodes().is_empty());
+        Ok(())
+    }
+
+    #[test]
+    fn flat_yaml_errors() {
+        for bad in [
+            "server:\n  port: 80\n",
+            "hosts: [a, b]\n",
+            "- item\n",
+            "a: 1\n---\nb: 2\n",
+            "a: [\n",
+        ] {
+            assert!(
+                KdlDocument::from_flat_yaml(bad).is_err(),
+                "{bad:?} should fail"
+            );
+        }
+        let err = KdlDocument::from_flat_yaml("server:\n  port: 80\n").unwrap_err();
+        assert_eq!(
+            err.diagnostics[0].message.as_deref(),
+            Some("Expected `server` to have a scalar value")
+        );
+    }
+}